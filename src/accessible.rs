@@ -0,0 +1,227 @@
+use crate::config::LatencyUnit;
+use crate::monitor::{Health, MonitorStatus, TargetStats};
+use crate::units::format_latency;
+use chrono::Local;
+use color_eyre::Result;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+const REFRESH_INTERVAL_MS: u64 = 2000;
+// Keyed apart from targets, which are keyed by position since the same IP can be listed twice
+// (say, once per interface)
+const WATCHDOG_KEY: &str = "watchdog";
+const HOOK_KEY: &str = "hook";
+const COLLECTOR_KEY: &str = "collector";
+const LOCAL_KEY: &str = "local";
+const MASS_OUTAGE_KEY: &str = "mass_outage";
+
+// Only lines whose text changed are re-announced, so a screen reader isn't flooded with
+// identical status every refresh
+pub async fn run_accessible(
+    targets: Arc<Mutex<Vec<TargetStats>>>,
+    status: Arc<Mutex<MonitorStatus>>,
+) -> Result<()> {
+    let mut last_lines: HashMap<String, String> = HashMap::new();
+    let mut interval = tokio::time::interval(std::time::Duration::from_millis(REFRESH_INTERVAL_MS));
+
+    println!("boxmonitor accessible mode. Press Ctrl+C to quit.");
+    if let Some(warning) = &status.lock().await.config_warning {
+        println!("warning: {}", warning);
+    }
+
+    loop {
+        interval.tick().await;
+
+        let lines: Vec<(String, String)> = {
+            let targets = targets.lock().await;
+            targets
+                .iter()
+                .enumerate()
+                .map(|(index, target)| (format!("target {}", index), describe_target(target)))
+                .collect()
+        };
+
+        let (stalled_since, hook_error, collector_error, local_outage, mass_outage) = {
+            let status = status.lock().await;
+            (
+                status.stalled_for().map(|_| status.last_cycle_at),
+                status.hook_error.clone(),
+                status.collector_error.clone(),
+                status.local_outage.clone(),
+                status.mass_outage,
+            )
+        };
+
+        let mut stdout = io::stdout().lock();
+        match stalled_since {
+            Some(since) => {
+                let line = format!(
+                    "warning: monitoring stalled, no cycle completed since {}",
+                    since.with_timezone(&Local).format("%H:%M:%S")
+                );
+                if last_lines.get(WATCHDOG_KEY) != Some(&line) {
+                    writeln!(stdout, "{} {}", Local::now().format("%H:%M:%S"), line)?;
+                    last_lines.insert(WATCHDOG_KEY.to_string(), line);
+                }
+            }
+            None => {
+                if last_lines.remove(WATCHDOG_KEY).is_some() {
+                    writeln!(
+                        stdout,
+                        "{} monitoring resumed",
+                        Local::now().format("%H:%M:%S")
+                    )?;
+                }
+            }
+        }
+
+        match local_outage {
+            Some(outage) => {
+                let line = format!(
+                    "warning: local network unavailable since {} ({}), failures not counted",
+                    outage.since.with_timezone(&Local).format("%H:%M:%S"),
+                    outage.reason
+                );
+                if last_lines.get(LOCAL_KEY) != Some(&line) {
+                    writeln!(stdout, "{} {}", Local::now().format("%H:%M:%S"), line)?;
+                    last_lines.insert(LOCAL_KEY.to_string(), line);
+                }
+            }
+            None => {
+                if last_lines.remove(LOCAL_KEY).is_some() {
+                    writeln!(
+                        stdout,
+                        "{} local network restored",
+                        Local::now().format("%H:%M:%S")
+                    )?;
+                }
+            }
+        }
+
+        match mass_outage {
+            Some(outage) => {
+                let line = format!(
+                    "warning: {} of {} targets down at once, likely a local network issue",
+                    outage.down, outage.total
+                );
+                if last_lines.get(MASS_OUTAGE_KEY) != Some(&line) {
+                    writeln!(stdout, "{} {}", Local::now().format("%H:%M:%S"), line)?;
+                    last_lines.insert(MASS_OUTAGE_KEY.to_string(), line);
+                }
+            }
+            None => {
+                if last_lines.remove(MASS_OUTAGE_KEY).is_some() {
+                    writeln!(
+                        stdout,
+                        "{} targets recovering, no longer looks like a local issue",
+                        Local::now().format("%H:%M:%S")
+                    )?;
+                }
+            }
+        }
+
+        match hook_error {
+            Some(error) => {
+                let line = format!("warning: on_result_command failed: {}", error);
+                if last_lines.get(HOOK_KEY) != Some(&line) {
+                    writeln!(stdout, "{} {}", Local::now().format("%H:%M:%S"), line)?;
+                    last_lines.insert(HOOK_KEY.to_string(), line);
+                }
+            }
+            None => {
+                last_lines.remove(HOOK_KEY);
+            }
+        }
+
+        match collector_error {
+            Some(error) => {
+                let line = format!(
+                    "warning: could not send statistics to the collector: {}",
+                    error
+                );
+                if last_lines.get(COLLECTOR_KEY) != Some(&line) {
+                    writeln!(stdout, "{} {}", Local::now().format("%H:%M:%S"), line)?;
+                    last_lines.insert(COLLECTOR_KEY.to_string(), line);
+                }
+            }
+            None => {
+                last_lines.remove(COLLECTOR_KEY);
+            }
+        }
+
+        for (key, line) in lines {
+            if last_lines.get(&key) != Some(&line) {
+                writeln!(stdout, "{} {}", Local::now().format("%H:%M:%S"), line)?;
+                last_lines.insert(key, line);
+            }
+        }
+        stdout.flush()?;
+    }
+}
+
+// Sub-millisecond LAN latencies in µs rather than rounded to 0ms
+fn lan_precise(ms: f64) -> String {
+    format_latency(ms, LatencyUnit::Auto, 1)
+}
+
+fn describe_target(target: &TargetStats) -> String {
+    let name = target.target.name.as_ref().unwrap_or(&target.target.ip);
+
+    let mut parts = Vec::new();
+    match target.ping_history.back() {
+        None => parts.push("waiting for first ping".to_string()),
+        Some(latest) => match latest.latency_ms {
+            Some(latency) if latest.success => parts.push(format!("up, {}", lan_precise(latency))),
+            _ if latest.local_outage => parts.push("unreachable, local network down".to_string()),
+            _ if target.health == Health::Down => parts.push("down".to_string()),
+            _ => parts.push("no reply".to_string()),
+        },
+    }
+
+    if target.health == Health::Degraded {
+        parts.push(format!(
+            "degraded: {}",
+            target.degraded_reasons().join(", ")
+        ));
+    }
+
+    if let Some(ack) = &target.ack {
+        parts.push(format!("acknowledged by {}: {}", ack.by, ack.note));
+    }
+
+    if let Some(stats) = &target.ping_stats {
+        parts.push(format!(
+            "{:.0}% over last {} (95% CI {:.0}-{:.0}%)",
+            stats.success_rate, stats.total_count, stats.success_ci_low, stats.success_ci_high
+        ));
+    }
+
+    if let Some((loss, bursts)) = target.burst_loss() {
+        parts.push(format!("{:.1}% burst loss over {} bursts", loss, bursts));
+    }
+
+    if target.target.ssh_port.is_some() {
+        match target.ssh_history.back() {
+            None => parts.push("ssh pending".to_string()),
+            Some(latest) => match latest.connection_time_ms {
+                Some(time) if latest.success => {
+                    parts.push(format!("ssh up, {}", lan_precise(time)))
+                }
+                _ => parts.push("ssh down".to_string()),
+            },
+        }
+    }
+
+    match target.failure_log.back() {
+        None => parts.push("no recent failures".to_string()),
+        Some(failure) => parts.push(format!(
+            "last failure at {}: {}",
+            failure.timestamp.with_timezone(&Local).format("%H:%M:%S"),
+            failure.reason
+        )),
+    }
+
+    format!("{}: {}", name, parts.join(", "))
+}
@@ -0,0 +1,504 @@
+use crate::clock;
+use crate::config::{Config, EscalationTier, LatencyUnit, MassOutageCheck, QuietHours};
+use crate::monitor::{Health, TargetStats};
+use crate::ui::format_downtime;
+use crate::units::format_latency;
+use chrono::{DateTime, Datelike, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AlertKind {
+    LatencyJump,
+    Degraded,
+    Down,
+    // Still down past an escalation tier's threshold
+    Escalated,
+    // Composite availability below availability_alert_percent
+    LowAvailability,
+    // Enough targets down together to suggest this machine's network, in place of their own
+    LikelyLocalOutage,
+}
+
+impl AlertKind {
+    pub fn level(self) -> AlertLevel {
+        match self {
+            AlertKind::LatencyJump | AlertKind::Degraded | AlertKind::LowAvailability => {
+                AlertLevel::Warning
+            }
+            AlertKind::Down | AlertKind::Escalated | AlertKind::LikelyLocalOutage => {
+                AlertLevel::Critical
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum AlertLevel {
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub timestamp: DateTime<Utc>,
+    pub ip: String,
+    pub target_name: String,
+    pub kind: AlertKind,
+    pub message: String,
+}
+
+// Someone is looking into a target's problem. Its alerts are held back until it recovers,
+// which clears the acknowledgement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Acknowledgement {
+    pub by: String,
+    pub at: DateTime<Utc>,
+    pub note: String,
+}
+
+impl Acknowledgement {
+    pub fn new(note: String) -> Self {
+        // Under sudo, USER is root; SUDO_USER is the person who ran it
+        let by = std::env::var("SUDO_USER")
+            .or_else(|_| std::env::var("USER"))
+            .unwrap_or_else(|_| "unknown".to_string());
+        Self {
+            by,
+            at: clock::now(),
+            note,
+        }
+    }
+}
+
+// Outcome of one round of post-cycle analysis, for the alert outputs
+#[derive(Debug, Clone, Default)]
+pub struct AlertUpdate {
+    pub active: Vec<Alert>,
+    pub fired: Vec<Alert>,
+    pub quiet_hours: bool,
+    pub muted_until: Option<DateTime<Utc>>,
+    // The tiers that fired, for running their commands
+    pub escalations: Vec<Escalation>,
+    pub mass_outage: Option<MassOutage>,
+}
+
+// How many of the counted targets are down together
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MassOutage {
+    pub down: usize,
+    pub total: usize,
+}
+
+impl MassOutage {
+    fn detect(check: &MassOutageCheck, targets: &[TargetStats]) -> Option<Self> {
+        let counted = targets.iter().filter(|t| t.target.synthetic.is_none());
+        let (down, total) = counted.fold((0, 0), |(down, total), t| {
+            (down + (t.health == Health::Down) as usize, total + 1)
+        });
+        let percent = down as f64 / total.max(1) as f64 * 100.0;
+        (total >= check.min_targets && down > 0 && percent >= check.down_percent)
+            .then_some(Self { down, total })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Escalation {
+    // Counted from 1, shortest threshold first
+    pub tier: usize,
+    pub command: Option<Vec<String>>,
+    pub down_secs: i64,
+    pub alert: Alert,
+}
+
+#[derive(Debug, Clone)]
+pub struct AlertSettings {
+    pub latency_jump_percent: Option<f64>,
+    pub latency_jump_samples: usize,
+    pub latency_baseline_samples: usize,
+    pub quiet_hours: Vec<QuietHours>,
+    pub availability_alert_percent: Option<f64>,
+    pub mass_outage: Option<MassOutageCheck>,
+    // Shortest first
+    pub escalation: Vec<EscalationTier>,
+}
+
+impl AlertSettings {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            latency_jump_percent: config.latency_jump_percent,
+            latency_jump_samples: config.latency_jump_samples.max(1),
+            latency_baseline_samples: config.latency_baseline_samples.max(1),
+            quiet_hours: config.quiet_hours.clone(),
+            availability_alert_percent: config.availability_alert_percent,
+            mass_outage: config.mass_outage,
+            escalation: {
+                let mut tiers = config.escalation.clone();
+                tiers.sort_by_key(|tier| tier.after_secs);
+                tiers
+            },
+        }
+    }
+}
+
+impl QuietHours {
+    fn contains(&self, now: DateTime<Local>) -> bool {
+        let time = now.time();
+        let applies_on = |day: chrono::Weekday| self.days.is_empty() || self.days.contains(&day);
+
+        if self.start <= self.end {
+            applies_on(now.weekday()) && time >= self.start && time < self.end
+        } else if time >= self.start {
+            applies_on(now.weekday())
+        } else {
+            // After midnight in a range that started the previous evening
+            time < self.end && applies_on(now.weekday().pred())
+        }
+    }
+}
+
+// Stands in for a target index; the local outage alert is about none of them
+const LOCAL_OUTAGE: usize = usize::MAX;
+
+// Post-cycle analysis over all targets. Alerts are edge-triggered: a condition fires once when
+// it starts and stays active, without re-firing, until it clears
+pub struct AlertEngine {
+    settings: AlertSettings,
+    active: HashMap<(usize, AlertKind), Alert>,
+    // When each (target, escalation tier) last fired, for repeats
+    escalated: HashMap<(usize, usize), DateTime<Utc>>,
+    // Everything is held back until then, set by hand for ad-hoc maintenance
+    muted_until: Option<DateTime<Utc>>,
+}
+
+impl AlertEngine {
+    pub fn new(settings: AlertSettings) -> Self {
+        Self {
+            settings,
+            active: HashMap::new(),
+            escalated: HashMap::new(),
+            muted_until: None,
+        }
+    }
+
+    pub fn mute(&mut self, until: Option<DateTime<Utc>>) {
+        self.muted_until = until;
+    }
+
+    pub fn muted_until(&self) -> Option<DateTime<Utc>> {
+        self.muted_until
+    }
+
+    // Monitoring carries on through quiet hours and mutes, but nothing is raised; conditions
+    // still present when they end fire then
+    pub fn evaluate(&mut self, targets: &[TargetStats]) -> AlertUpdate {
+        self.muted_until = self.muted_until.filter(|&until| until > clock::now());
+        // Worked out even while alerts are held back, since the banner isn't an alert
+        let mass_outage = self
+            .settings
+            .mass_outage
+            .and_then(|check| MassOutage::detect(&check, targets));
+        let now = Local::now();
+        if self.settings.quiet_hours.iter().any(|q| q.contains(now)) {
+            self.active.clear();
+            self.escalated.clear();
+            return AlertUpdate {
+                quiet_hours: true,
+                muted_until: self.muted_until,
+                mass_outage,
+                ..Default::default()
+            };
+        }
+        if self.muted_until.is_some() {
+            self.active.clear();
+            self.escalated.clear();
+            return AlertUpdate {
+                muted_until: self.muted_until,
+                mass_outage,
+                ..Default::default()
+            };
+        }
+
+        let mut fired = Vec::new();
+        let mut escalations = Vec::new();
+        let suppress = mass_outage.is_some()
+            && self
+                .settings
+                .mass_outage
+                .is_some_and(|check| check.suppress_target_alerts);
+        let key = (LOCAL_OUTAGE, AlertKind::LikelyLocalOutage);
+        match mass_outage.filter(|_| suppress) {
+            Some(outage) => {
+                if let Entry::Vacant(entry) = self.active.entry(key) {
+                    let alert = Alert {
+                        timestamp: clock::now(),
+                        ip: String::new(),
+                        target_name: "local network".to_string(),
+                        kind: AlertKind::LikelyLocalOutage,
+                        message: format!(
+                            "{} of {} targets down at once, likely a local network issue",
+                            outage.down, outage.total
+                        ),
+                    };
+                    fired.push(alert.clone());
+                    entry.insert(alert);
+                }
+            }
+            None => {
+                self.active.remove(&key);
+            }
+        }
+
+        for (index, target) in targets.iter().enumerate() {
+            // Dropped rather than kept active, so clearing an ack by hand re-raises anything
+            // still wrong
+            // Likewise for targets only failing because something upstream is down, whose
+            // own alert covers them
+            // And for every target while one local outage alert stands in for them all
+            if target.ack.is_some() || target.upstream_down.is_some() || suppress {
+                self.active.retain(|&(i, _), _| i != index);
+                self.escalated.retain(|&(i, _), _| i != index);
+                continue;
+            }
+            for escalation in self.escalate(index, target) {
+                fired.push(escalation.alert.clone());
+                escalations.push(escalation);
+            }
+
+            let conditions = [
+                (AlertKind::LatencyJump, self.latency_jump(target)),
+                (AlertKind::Degraded, health_alert(target, Health::Degraded)),
+                (AlertKind::Down, health_alert(target, Health::Down)),
+                (AlertKind::LowAvailability, self.low_availability(target)),
+            ];
+
+            for (kind, condition) in conditions {
+                let key = (index, kind);
+                match condition {
+                    Some(alert) => {
+                        if let Entry::Vacant(entry) = self.active.entry(key) {
+                            fired.push(alert.clone());
+                            entry.insert(alert);
+                        }
+                    }
+                    None => {
+                        self.active.remove(&key);
+                    }
+                }
+            }
+        }
+
+        // Most severe last, then newest last, so the banner shows the worst recent alert
+        let mut active: Vec<Alert> = self.active.values().cloned().collect();
+        active.sort_by_key(|alert| (alert.kind.level(), alert.timestamp));
+        AlertUpdate {
+            active,
+            fired,
+            quiet_hours: false,
+            muted_until: None,
+            escalations,
+            mass_outage,
+        }
+    }
+
+    // Tiers a down target has newly reached, or is due to repeat. The highest one reached
+    // stays active until the target is back up
+    fn escalate(&mut self, index: usize, target: &TargetStats) -> Vec<Escalation> {
+        let key = (index, AlertKind::Escalated);
+        let Some(down_for) = target
+            .down_since
+            .filter(|_| target.health == Health::Down)
+            .map(|since| clock::now() - since)
+        else {
+            self.active.remove(&key);
+            self.escalated.retain(|&(i, _), _| i != index);
+            return Vec::new();
+        };
+
+        let mut fired = Vec::new();
+        for (number, tier) in self.settings.escalation.iter().enumerate() {
+            if down_for < chrono::Duration::seconds(tier.after_secs as i64) {
+                break;
+            }
+            let due = match self.escalated.get(&(index, number)) {
+                None => true,
+                Some(&last) => tier.repeat_secs.is_some_and(|repeat| {
+                    clock::now() - last >= chrono::Duration::seconds(repeat as i64)
+                }),
+            };
+            if !due {
+                continue;
+            }
+            let alert = new_alert(
+                target,
+                AlertKind::Escalated,
+                format!(
+                    "down for {} (escalation tier {})",
+                    format_downtime(down_for),
+                    number + 1
+                ),
+            );
+            self.escalated.insert((index, number), clock::now());
+            self.active.insert(key, alert.clone());
+            fired.push(Escalation {
+                tier: number + 1,
+                command: tier.command.clone(),
+                down_secs: down_for.num_seconds(),
+                alert,
+            });
+        }
+        fired
+    }
+
+    // Weighted across the target's checks, so a web host whose SSH keeps failing can alert
+    // even while it answers every ping
+    fn low_availability(&self, target: &TargetStats) -> Option<Alert> {
+        let threshold_percent = self.settings.availability_alert_percent?;
+        let percent = target.composite_availability()?.ratio * 100.0;
+        (percent < threshold_percent).then(|| {
+            let detail = format!(
+                "availability {:.2}% (below {}%)",
+                percent, threshold_percent
+            );
+            new_alert(target, AlertKind::LowAvailability, detail)
+        })
+    }
+
+    // Compares the mean of the latest samples with the mean of the window just before them, so
+    // the trigger is relative to each link's own normal rather than a fixed threshold
+    fn latency_jump(&self, target: &TargetStats) -> Option<Alert> {
+        let threshold_percent = self.settings.latency_jump_percent?;
+        let recent_count = self.settings.latency_jump_samples;
+        let baseline_count = self.settings.latency_baseline_samples;
+
+        let latencies: Vec<f64> = target
+            .ping_history
+            .iter()
+            .filter(|r| !r.warmup)
+            .filter_map(|r| r.latency_ms)
+            .collect();
+        if latencies.len() < recent_count + baseline_count {
+            return None;
+        }
+
+        let (earlier, recent) = latencies.split_at(latencies.len() - recent_count);
+        let baseline = &earlier[earlier.len() - baseline_count..];
+        let recent_mean = recent.iter().sum::<f64>() / recent.len() as f64;
+        let baseline_mean = baseline.iter().sum::<f64>() / baseline.len() as f64;
+        if baseline_mean <= 0.0 {
+            return None;
+        }
+
+        let increase_percent = (recent_mean - baseline_mean) / baseline_mean * 100.0;
+        if increase_percent <= threshold_percent {
+            return None;
+        }
+
+        Some(new_alert(
+            target,
+            AlertKind::LatencyJump,
+            format!(
+                "latency up {:.0}% ({} vs {} baseline)",
+                increase_percent,
+                format_latency(recent_mean, LatencyUnit::Auto, 1),
+                format_latency(baseline_mean, LatencyUnit::Auto, 1)
+            ),
+        ))
+    }
+}
+
+fn health_alert(target: &TargetStats, health: Health) -> Option<Alert> {
+    if target.health != health {
+        return None;
+    }
+
+    let (kind, detail) = match health {
+        Health::Down => (
+            AlertKind::Down,
+            target
+                .down_reason()
+                .map(|reason| format!("down ({})", reason))
+                .unwrap_or_else(|| "down".to_string()),
+        ),
+        _ => (
+            AlertKind::Degraded,
+            format!("degraded ({})", target.degraded_reasons().join(", ")),
+        ),
+    };
+    Some(new_alert(target, kind, detail))
+}
+
+fn new_alert(target: &TargetStats, kind: AlertKind, detail: String) -> Alert {
+    let target_name = target
+        .target
+        .name
+        .clone()
+        .unwrap_or_else(|| target.target.ip.clone());
+    Alert {
+        timestamp: clock::now(),
+        ip: target.target.ip.clone(),
+        message: format!("{}: {}", target_name, detail),
+        target_name,
+        kind,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::tests::{ping, test_target};
+    use std::time::Duration;
+
+    fn kinds(alerts: &[Alert]) -> Vec<AlertKind> {
+        alerts.iter().map(|alert| alert.kind).collect()
+    }
+
+    async fn advance_secs(secs: u64) {
+        tokio::time::advance(Duration::from_secs(secs)).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn alerts_fire_once_and_escalations_repeat_on_schedule() {
+        let config = Config {
+            escalation: vec![EscalationTier {
+                after_secs: 60,
+                command: None,
+                repeat_secs: Some(120),
+            }],
+            ..Default::default()
+        };
+        let mut engine = AlertEngine::new(AlertSettings::from_config(&config));
+        let mut target = test_target("10.0.0.1");
+        for _ in 0..5 {
+            target.add_ping_result(ping(false), 1000);
+        }
+        let mut fire =
+            |target: &TargetStats| kinds(&engine.evaluate(std::slice::from_ref(target)).fired);
+
+        assert_eq!(fire(&target), vec![AlertKind::Down]);
+        // Still down, but already raised
+        assert!(fire(&target).is_empty());
+
+        advance_secs(61).await;
+        target.add_ping_result(ping(false), 1000);
+        assert_eq!(fire(&target), vec![AlertKind::Escalated]);
+
+        advance_secs(60).await;
+        assert!(fire(&target).is_empty());
+        advance_secs(61).await;
+        assert_eq!(fire(&target), vec![AlertKind::Escalated]);
+
+        // Back up clears the outage (the loss may still leave it degraded), and the next one
+        // raises it afresh
+        for _ in 0..5 {
+            target.add_ping_result(ping(true), 1000);
+        }
+        let fired = fire(&target);
+        assert!(!fired.contains(&AlertKind::Down) && !fired.contains(&AlertKind::Escalated));
+        for _ in 0..5 {
+            target.add_ping_result(ping(false), 1000);
+        }
+        assert_eq!(fire(&target), vec![AlertKind::Down]);
+    }
+}
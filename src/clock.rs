@@ -0,0 +1,87 @@
+use chrono::{DateTime, Utc};
+use std::sync::Mutex;
+use std::time::Instant;
+
+// Set while replaying a recording: time follows the recording, `speed` times as fast
+static REPLAY: Mutex<Option<ReplayClock>> = Mutex::new(None);
+
+// Test builds keep tokio's clock, so nothing reads this there
+#[cfg_attr(test, allow(dead_code))]
+#[derive(Clone, Copy)]
+struct ReplayClock {
+    origin: DateTime<Utc>,
+    started: Instant,
+    speed: f64,
+}
+
+// Where every timestamp comes from, so time can be controlled in one place
+#[cfg(not(test))]
+pub fn now() -> DateTime<Utc> {
+    match *REPLAY.lock().unwrap() {
+        Some(replay) => {
+            let elapsed = replay.started.elapsed().mul_f64(replay.speed);
+            replay.origin + chrono::Duration::from_std(elapsed).unwrap_or_default()
+        }
+        None => Utc::now(),
+    }
+}
+
+// Test builds follow tokio's clock instead, so under tokio::time::pause and advance the
+// timestamps behind windowing, snapshots and alert debouncing move in step with the intervals
+// and sleeps that drive the monitor. The anchor is per thread, which for #[tokio::test]'s
+// current-thread runtime means per test: each test's paused clock is its own
+#[cfg(test)]
+pub fn now() -> DateTime<Utc> {
+    thread_local! {
+        static ANCHOR: (DateTime<Utc>, tokio::time::Instant) =
+            (Utc::now(), tokio::time::Instant::now());
+    }
+    ANCHOR.with(|(wall, instant)| {
+        *wall + chrono::Duration::from_std(instant.elapsed()).unwrap_or_default()
+    })
+}
+
+// Moves the clock to `origin` for a replay, running from there `speed` times as fast; a speed
+// of 0 stops it
+pub fn replay_from(origin: DateTime<Utc>, speed: f64) {
+    *REPLAY.lock().unwrap() = Some(ReplayClock {
+        origin,
+        started: Instant::now(),
+        speed,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test(start_paused = true)]
+    async fn follows_paused_time() {
+        let start = now();
+        tokio::time::advance(Duration::from_secs(90)).await;
+        assert_eq!((now() - start).num_seconds(), 90);
+    }
+
+    // Two runtimes advancing by different amounts at once don't see each other's time
+    #[test]
+    fn each_runtime_keeps_its_own_time() {
+        let advanced_by = |secs: u64| {
+            std::thread::spawn(move || {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .enable_time()
+                    .start_paused(true)
+                    .build()
+                    .unwrap();
+                runtime.block_on(async {
+                    let start = now();
+                    tokio::time::advance(Duration::from_secs(secs)).await;
+                    (now() - start).num_seconds()
+                })
+            })
+        };
+        let (short, long) = (advanced_by(5), advanced_by(3600));
+        assert_eq!(short.join().unwrap(), 5);
+        assert_eq!(long.join().unwrap(), 3600);
+    }
+}
@@ -0,0 +1,115 @@
+use crate::clock;
+use crate::config::{Config, ExportTimestamps, OutboundHttp};
+use crate::http::{HttpUrl, Outbox};
+use crate::monitor::{MonitorStatus, Statistics, TargetStats};
+use crate::units::ExportedTimestamp;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+const SYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+// One roll-up: the statistics each target already has, never its samples
+#[derive(Debug, Serialize)]
+struct Summary<'a> {
+    instance: &'a str,
+    sent_at: ExportedTimestamp,
+    targets: Vec<TargetSummary>,
+}
+
+#[derive(Debug, Serialize)]
+struct TargetSummary {
+    ip: String,
+    name: Option<String>,
+    health: &'static str,
+    ping_stats: Option<Statistics>,
+    ssh_stats: Option<Statistics>,
+}
+
+impl TargetSummary {
+    fn new(target: &TargetStats) -> Self {
+        Self {
+            ip: target.target.ip.clone(),
+            name: target.target.name.clone(),
+            health: target.health.label(),
+            ping_stats: target.ping_stats.clone(),
+            ssh_stats: target.ssh_stats.clone(),
+        }
+    }
+}
+
+pub struct Collector {
+    url: String,
+    instance: String,
+    timestamps: ExportTimestamps,
+    http: OutboundHttp,
+}
+
+impl Collector {
+    // `url` is --collector-url, which wins over the config's
+    pub fn from_config(config: &Config, url: Option<String>) -> Option<Self> {
+        let url = url.or_else(|| config.collector_url.clone())?;
+        let instance = config
+            .instance_id
+            .clone()
+            .or_else(hostname)
+            .unwrap_or_else(|| "boxmonitor".to_string());
+        Some(Self {
+            url,
+            instance,
+            timestamps: config.export_timestamps,
+            http: config.outbound_http,
+        })
+    }
+
+    // Sends the summary every minute. A failed send is only reported in the status; monitoring
+    // carries on, and the summaries that didn't get through go out with the next one
+    pub async fn run(
+        self,
+        targets: Arc<Mutex<Vec<TargetStats>>>,
+        status: Arc<Mutex<MonitorStatus>>,
+    ) {
+        let mut outbox = match HttpUrl::parse(&self.url) {
+            Ok(url) => Outbox::new(url, self.http),
+            Err(e) => {
+                status.lock().await.collector_error = Some(format!("collector_url: {}", e));
+                return;
+            }
+        };
+        let start = tokio::time::Instant::now() + SYNC_INTERVAL;
+        let mut interval = tokio::time::interval_at(start, SYNC_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let body = {
+                let targets = targets.lock().await;
+                let summary = Summary {
+                    instance: &self.instance,
+                    sent_at: self.timestamps.stamp(clock::now()),
+                    targets: targets.iter().map(TargetSummary::new).collect(),
+                };
+                serde_json::to_vec(&summary)
+            };
+            let outcome = match body {
+                Ok(body) => outbox.send(body).await.map_err(|e| match outbox.queued() {
+                    0 => e,
+                    queued => format!("{} ({} summaries queued)", e, queued),
+                }),
+                Err(e) => Err(e.to_string()),
+            };
+            status.lock().await.collector_error = outcome.err();
+        }
+    }
+}
+
+fn hostname() -> Option<String> {
+    let mut buffer = [0u8; 256];
+    let result = unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) };
+    if result != 0 {
+        return None;
+    }
+    let end = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+    let name = String::from_utf8_lossy(&buffer[..end]).into_owned();
+    (!name.is_empty()).then_some(name)
+}
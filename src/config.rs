@@ -1,7 +1,7 @@
 use color_eyre::Result;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -9,14 +9,177 @@ pub struct Config {
     pub ping_interval_ms: u64,
     pub ssh_timeout_ms: u64,
     pub history_size: usize,
+    #[serde(default = "default_snapshot_interval_secs")]
+    pub snapshot_interval_secs: u64,
+    #[serde(default = "default_snapshot_retention")]
+    pub snapshot_retention: usize,
+    #[serde(default)]
+    pub warmup_samples: usize,
+    #[serde(default = "default_loss_penalty_ms")]
+    pub loss_penalty_ms: f64,
+    #[serde(default)]
+    pub ssh_persistent: bool,
+    // Private key for SSH sessions in place of the ssh-agent, which sudo usually hides
+    #[serde(default)]
+    pub ssh_key_file: Option<PathBuf>,
+    // Each target's probe starts after a random delay of up to this much, so a cycle isn't one
+    // synchronized burst; 0 probes everything at once
+    #[serde(default)]
+    pub probe_jitter_ms: u64,
+    // Probes beyond this many wait for a running one to finish, trading cycle length for less
+    // CPU and fewer open sockets with large target lists
+    #[serde(default = "default_max_concurrent_probes")]
+    pub max_concurrent_probes: usize,
+    #[serde(default)]
+    pub chart_styles: ChartStyles,
+    #[serde(default)]
+    pub latency_unit: LatencyUnit,
+    #[serde(default)]
+    pub add_gateway: bool,
+    #[serde(default = "default_latency_jump_percent")]
+    pub latency_jump_percent: Option<f64>,
+    #[serde(default = "default_latency_jump_samples")]
+    pub latency_jump_samples: usize,
+    #[serde(default = "default_latency_baseline_samples")]
+    pub latency_baseline_samples: usize,
+    #[serde(default)]
+    pub quiet_hours: Vec<QuietHours>,
+    // Warn when a target's composite availability over the history drops below this percent;
+    // unset never alerts on availability
+    #[serde(default)]
+    pub availability_alert_percent: Option<f64>,
+    // Most targets down at once points at this machine's own network; unset never says so
+    #[serde(default)]
+    pub mass_outage: Option<MassOutageCheck>,
+    // Stronger alerts for targets that stay down, by how long they have been down
+    #[serde(default)]
+    pub escalation: Vec<EscalationTier>,
+    #[serde(default)]
+    pub health: HealthThresholds,
+    #[serde(default)]
+    pub histogram: HistogramSettings,
+    // Program and arguments run with each probe result as JSON on stdin; unset runs nothing
+    #[serde(default)]
+    pub on_result_command: Option<Vec<String>>,
+    #[serde(default = "default_on_result_timeout_ms")]
+    pub on_result_timeout_ms: u64,
+    // Hostname targets are looked up again this often, and only move to a new address once
+    // that many lookups in a row agree on it
+    #[serde(default = "default_dns_refresh_secs")]
+    pub dns_refresh_secs: u64,
+    #[serde(default = "default_dns_stable_resolutions")]
+    pub dns_stable_resolutions: usize,
+    // Resolver the hostname lookups go to, and the protocol they use
+    #[serde(default)]
+    pub dns_check: DnsCheck,
+    // A panic in the monitor loop restarts it, with backoff, instead of ending monitoring
+    #[serde(default = "default_restart_on_panic")]
+    pub restart_on_panic: bool,
+    // How timestamps are written in snapshot files and hook payloads
+    #[serde(default)]
+    pub export_timestamps: ExportTimestamps,
+    // Targets that keep failing are pinged less often; unset pings every target every cycle
+    #[serde(default)]
+    pub down_backoff: Option<DownBackoff>,
+    // Address like "127.0.0.1:9101" to answer HTTP `GET /healthz` on; unset serves nothing
+    #[serde(default)]
+    pub healthz_listen: Option<String>,
+    // Timeouts, retries and queueing shared by every HTTP push, such as the collector's
+    #[serde(default)]
+    pub outbound_http: OutboundHttp,
+    // http:// or https:// URL each target's statistics are POSTed to once a minute, for
+    // rolling up many monitors in one place; unset sends nothing
+    #[serde(default)]
+    pub collector_url: Option<String>,
+    // Names this monitor in what it sends the collector; unset uses the hostname
+    #[serde(default)]
+    pub instance_id: Option<String>,
+    // Recent samples count for more in the mean, median and percentiles; unset weighs every
+    // sample in the window the same
+    #[serde(default)]
+    pub recency_weighting: Option<RecencyWeighting>,
+    // Latency samples left out of the statistics and chart scaling; unset keeps every one
+    #[serde(default)]
+    pub outlier_filter: Option<OutlierFilter>,
+    // Targets that end up on the same address are always flagged; with this set, only the
+    // first is pinged and the others share its results
+    #[serde(default)]
+    pub coalesce_duplicate_addresses: bool,
+    // Address or name of a target, such as the gateway, that every other target's latency can
+    // be shown relative to
+    #[serde(default)]
+    pub reference_target: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Target {
     pub ip: String,
     pub name: Option<String>,
     pub ssh_port: Option<u16>,
     pub ssh_user: Option<String>,
+    // Higher values are probed first each cycle; unset counts as 0
+    pub priority: Option<i32>,
+    // Expected latency, drawn as a reference line on the ping chart
+    pub baseline_ms: Option<f64>,
+    // Probe `ip` as a multicast group or broadcast address, counting responders
+    pub multicast: Option<MulticastCheck>,
+    // Linux network namespace (as named by `ip netns`) to send pings from
+    pub netns: Option<String>,
+    // Interface the ping socket is bound to, such as a VLAN interface like "eth0.100" to test
+    // that VLAN from a trunk port (Linux only)
+    #[serde(default)]
+    pub interface: Option<String>,
+    // Periodic bursts of back-to-back pings, with their loss kept apart from steady probing
+    pub burst: Option<BurstProfile>,
+    // The target throttles ICMP, so misses between its replies aren't counted as loss
+    #[serde(default)]
+    pub icmp_rate_limited: bool,
+    // Periodic upload over SSH to measure throughput; needs ssh_user, like persistent sessions
+    #[serde(default)]
+    pub throughput: Option<ThroughputTest>,
+    // The check that decides health, the tab color and alerting, for hosts that block ICMP
+    #[serde(default)]
+    pub primary_check: PrimaryCheck,
+    // Free-form labels like "prod" or "us-east"; the TUI filters and summarizes by them
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // Address or name of the target this one is reached through, such as its gateway. While
+    // that one is down, this one's failures are put down to it and not alerted separately
+    #[serde(default)]
+    pub depends_on: Option<String>,
+    // How much each check counts toward the target's composite availability
+    #[serde(default)]
+    pub check_weights: CheckWeights,
+    // Made-up ping results in place of real pings, for a canary target
+    #[serde(default)]
+    pub synthetic: Option<SyntheticCheck>,
+}
+
+// Relative weights, so 70 and 30 mean the same as 0.7 and 0.3. A check the target doesn't
+// run, or that has no samples yet, drops out and the others share its weight
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CheckWeights {
+    pub ping: f64,
+    pub ssh: f64,
+}
+
+impl Default for CheckWeights {
+    fn default() -> Self {
+        Self {
+            ping: 1.0,
+            ssh: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrimaryCheck {
+    #[default]
+    Ping,
+    // Falls back to ping for targets without an SSH check
+    Ssh,
 }
 
 impl Default for Config {
@@ -26,23 +189,467 @@ impl Default for Config {
                 Target {
                     ip: "8.8.8.8".to_string(),
                     name: Some("Google DNS".to_string()),
-                    ssh_port: None,
-                    ssh_user: None,
+                    ..Default::default()
                 },
                 Target {
                     ip: "1.1.1.1".to_string(),
                     name: Some("Cloudflare DNS".to_string()),
-                    ssh_port: None,
-                    ssh_user: None,
+                    ..Default::default()
                 },
             ],
             ping_interval_ms: 1000,
             ssh_timeout_ms: 5000,
             history_size: 100,
+            snapshot_interval_secs: default_snapshot_interval_secs(),
+            snapshot_retention: default_snapshot_retention(),
+            warmup_samples: 0,
+            loss_penalty_ms: default_loss_penalty_ms(),
+            ssh_persistent: false,
+            ssh_key_file: None,
+            probe_jitter_ms: 0,
+            max_concurrent_probes: default_max_concurrent_probes(),
+            chart_styles: ChartStyles::default(),
+            latency_unit: LatencyUnit::default(),
+            add_gateway: false,
+            latency_jump_percent: default_latency_jump_percent(),
+            latency_jump_samples: default_latency_jump_samples(),
+            latency_baseline_samples: default_latency_baseline_samples(),
+            quiet_hours: Vec::new(),
+            availability_alert_percent: None,
+            mass_outage: None,
+            escalation: Vec::new(),
+            health: HealthThresholds::default(),
+            histogram: HistogramSettings::default(),
+            on_result_command: None,
+            on_result_timeout_ms: default_on_result_timeout_ms(),
+            dns_refresh_secs: default_dns_refresh_secs(),
+            dns_stable_resolutions: default_dns_stable_resolutions(),
+            dns_check: DnsCheck::default(),
+            restart_on_panic: default_restart_on_panic(),
+            export_timestamps: ExportTimestamps::default(),
+            down_backoff: None,
+            healthz_listen: None,
+            outbound_http: OutboundHttp::default(),
+            collector_url: None,
+            instance_id: None,
+            recency_weighting: None,
+            outlier_filter: None,
+            coalesce_duplicate_addresses: false,
+            reference_target: None,
+        }
+    }
+}
+
+// After `after_failures` misses in a row the gap between a target's pings doubles with each
+// further miss, up to `max_interval_ms`; the first reply restores the normal interval
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DownBackoff {
+    pub after_failures: usize,
+    pub max_interval_ms: u64,
+}
+
+impl Default for DownBackoff {
+    fn default() -> Self {
+        Self {
+            after_failures: 5,
+            max_interval_ms: 60_000,
+        }
+    }
+}
+
+// Each push gets `timeout_ms` per attempt and `retries` more attempts, `backoff_ms` apart and
+// doubling. One that still fails is queued for the next push, keeping up to `queue_size`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutboundHttp {
+    pub timeout_ms: u64,
+    pub retries: u32,
+    pub backoff_ms: u64,
+    pub queue_size: usize,
+}
+
+impl Default for OutboundHttp {
+    fn default() -> Self {
+        Self {
+            timeout_ms: 10_000,
+            retries: 2,
+            backoff_ms: 1000,
+            queue_size: 60,
+        }
+    }
+}
+
+// At least `down_percent` of the targets down together, among at least `min_targets`, is taken
+// as a likely local network issue. Synthetic targets aren't counted
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MassOutageCheck {
+    pub down_percent: f64,
+    pub min_targets: usize,
+    // Hold back the per-target alerts meanwhile, raising one local outage alert in their place
+    pub suppress_target_alerts: bool,
+}
+
+impl Default for MassOutageCheck {
+    fn default() -> Self {
+        Self {
+            down_percent: 80.0,
+            min_targets: 3,
+            suppress_target_alerts: false,
         }
     }
 }
 
+// A sample `half_life_samples` older than the latest counts half as much, one twice that old
+// a quarter, and so on
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecencyWeighting {
+    pub half_life_samples: f64,
+}
+
+impl Default for RecencyWeighting {
+    fn default() -> Self {
+        Self {
+            half_life_samples: 20.0,
+        }
+    }
+}
+
+// Once a target has been down for `after_secs`, an escalated alert, with `command` run on it
+// like on_result_command. With `repeat_secs` it fires again that often until the target is up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationTier {
+    pub after_secs: u64,
+    #[serde(default)]
+    pub command: Option<Vec<String>>,
+    #[serde(default)]
+    pub repeat_secs: Option<u64>,
+}
+
+// Which latency samples count as outliers. Replies are still counted towards the success rate
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "method")]
+pub enum OutlierFilter {
+    // More than `k` interquartile ranges above the third quartile or below the first
+    Iqr {
+        #[serde(default = "default_outlier_iqr_k")]
+        k: f64,
+    },
+    // Slower than `max_ms`
+    Cap {
+        max_ms: f64,
+    },
+}
+
+fn default_outlier_iqr_k() -> f64 {
+    3.0
+}
+
+// Upload `bytes` to the target over SSH every `interval_secs`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThroughputTest {
+    pub bytes: u64,
+    pub interval_secs: u64,
+}
+
+impl Default for ThroughputTest {
+    fn default() -> Self {
+        Self {
+            bytes: 1_000_000,
+            interval_secs: 300,
+        }
+    }
+}
+
+// A target that is still answering is Degraded once any of these is exceeded over its history
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthThresholds {
+    pub latency_ms: f64,
+    pub jitter_ms: f64,
+    pub loss_percent: f64,
+    // Consecutive failed checks before a target counts as Down; fewer leave it Degraded
+    pub down_after: usize,
+}
+
+impl Default for HealthThresholds {
+    fn default() -> Self {
+        Self {
+            latency_ms: 150.0,
+            jitter_ms: 30.0,
+            loss_percent: 5.0,
+            down_after: 3,
+        }
+    }
+}
+
+// Read one character per ping cycle, starting over at the end: 'd' fails and anything else
+// succeeds, so "d" is always down and "ud" alternates. Checks that failures are detected,
+// alerted, logged and sent to hooks without waiting for a real outage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyntheticCheck {
+    pub pattern: String,
+    // Reported for the successes
+    pub latency_ms: f64,
+}
+
+impl Default for SyntheticCheck {
+    fn default() -> Self {
+        Self {
+            pattern: "d".to_string(),
+            latency_ms: 1.0,
+        }
+    }
+}
+
+impl SyntheticCheck {
+    // An empty pattern is taken as always down, the usual canary
+    pub fn succeeds(&self, cycle: u64) -> bool {
+        let pattern = self.pattern.as_bytes();
+        if pattern.is_empty() {
+            return false;
+        }
+        !pattern[(cycle % pattern.len() as u64) as usize].eq_ignore_ascii_case(&b'd')
+    }
+}
+
+// A multicast or broadcast probe succeeds when at least `expected_responders` distinct hosts
+// answer one echo request within `listen_ms`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MulticastCheck {
+    pub expected_responders: usize,
+    pub listen_ms: u64,
+}
+
+impl Default for MulticastCheck {
+    fn default() -> Self {
+        Self {
+            expected_responders: 1,
+            listen_ms: 500,
+        }
+    }
+}
+
+// `count` echo requests `spacing_ms` apart, sent after every `every_cycles` ping cycles
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BurstProfile {
+    pub count: usize,
+    pub every_cycles: u64,
+    pub spacing_ms: u64,
+}
+
+impl Default for BurstProfile {
+    fn default() -> Self {
+        Self {
+            count: 20,
+            every_cycles: 10,
+            spacing_ms: 10,
+        }
+    }
+}
+
+// Binning for the latency histogram view: `bins` equal bins across the observed range, or
+// fixed `bin_ms` wide bins when that is set
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistogramSettings {
+    pub bins: usize,
+    pub bin_ms: Option<f64>,
+}
+
+impl Default for HistogramSettings {
+    fn default() -> Self {
+        Self {
+            bins: 20,
+            bin_ms: None,
+        }
+    }
+}
+
+// Local-time range during which alerts are silenced, e.g. {"start": "22:00", "end": "07:00"}.
+// A range may wrap past midnight. With `days`, it only applies on ranges starting on those days
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: chrono::NaiveTime,
+    pub end: chrono::NaiveTime,
+    #[serde(default)]
+    pub days: Vec<chrono::Weekday>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarkerKind {
+    Braille,
+    Dot,
+    Block,
+    HalfBlock,
+    Bar,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphKind {
+    Line,
+    Scatter,
+    Bar,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SeriesStyle {
+    pub marker: MarkerKind,
+    pub graph: GraphKind,
+}
+
+impl Default for SeriesStyle {
+    fn default() -> Self {
+        Self {
+            marker: MarkerKind::Braille,
+            graph: GraphKind::Line,
+        }
+    }
+}
+
+// The system resolver, or a chosen server over plain DNS (udp), DNS over TLS (dot) or DNS over
+// HTTPS (doh)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DnsProtocol {
+    #[default]
+    System,
+    Udp,
+    Dot,
+    Doh,
+}
+
+// `server` is "1.1.1.1" or "dns.example:853" for udp and dot, and the full URL such as
+// "https://cloudflare-dns.com/dns-query" for doh. DoT checks the certificate against
+// `tls_name`, or else the server's host
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DnsCheck {
+    pub protocol: DnsProtocol,
+    pub server: Option<String>,
+    pub tls_name: Option<String>,
+}
+
+// How latencies are shown; auto picks µs, ms or s by magnitude
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LatencyUnit {
+    #[default]
+    Auto,
+    Us,
+    Ms,
+    S,
+}
+
+// RFC3339 in UTC unless set otherwise, so exported times are never ambiguous local ones
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportTimestamps {
+    pub format: TimestampFormat,
+    pub timezone: ExportTimezone,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampFormat {
+    #[default]
+    Rfc3339,
+    // Milliseconds since the Unix epoch, which have no timezone
+    UnixMs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportTimezone {
+    #[default]
+    Utc,
+    // This machine's offset, written into each timestamp
+    Local,
+}
+
+// How each check type is drawn in the latency charts
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ChartStyles {
+    #[serde(default)]
+    pub ping: SeriesStyle,
+    #[serde(default)]
+    pub ssh: SeriesStyle,
+    // A latency axis grows at once for a new peak but shrinks back over about this long once
+    // it has passed; 0 rescales every frame
+    #[serde(default = "default_axis_settle_secs")]
+    pub axis_settle_secs: f64,
+}
+
+impl Default for ChartStyles {
+    fn default() -> Self {
+        Self {
+            ping: SeriesStyle::default(),
+            ssh: SeriesStyle::default(),
+            axis_settle_secs: default_axis_settle_secs(),
+        }
+    }
+}
+
+fn default_axis_settle_secs() -> f64 {
+    10.0
+}
+
+// Added to the mean for every percentage point of loss: 2ms at 20% loss ranks as 22ms
+fn default_loss_penalty_ms() -> f64 {
+    1.0
+}
+
+// Alert when the latest samples average 3x the preceding baseline; null disables the alert
+fn default_latency_jump_percent() -> Option<f64> {
+    Some(200.0)
+}
+
+fn default_latency_jump_samples() -> usize {
+    5
+}
+
+fn default_latency_baseline_samples() -> usize {
+    20
+}
+
+fn default_on_result_timeout_ms() -> u64 {
+    5000
+}
+
+// High enough that ordinary target lists all probe at once
+fn default_max_concurrent_probes() -> usize {
+    512
+}
+
+fn default_dns_refresh_secs() -> u64 {
+    30
+}
+
+fn default_dns_stable_resolutions() -> usize {
+    3
+}
+
+fn default_restart_on_panic() -> bool {
+    true
+}
+
+fn default_snapshot_interval_secs() -> u64 {
+    60
+}
+
+fn default_snapshot_retention() -> usize {
+    // One snapshot a minute for an hour
+    60
+}
+
 pub fn get_config_dir() -> Result<PathBuf> {
     let home =
         dirs::home_dir().ok_or_else(|| color_eyre::eyre::eyre!("Could not find home directory"))?;
@@ -50,13 +657,36 @@ pub fn get_config_dir() -> Result<PathBuf> {
     Ok(config_dir)
 }
 
-pub fn load_config() -> Result<Config> {
+// Profiles live next to the default list as `<profile>.iplist`
+pub fn get_config_file(profile: Option<&str>) -> Result<PathBuf> {
     let config_dir = get_config_dir()?;
-    let config_file = config_dir.join(".iplist");
+
+    match profile {
+        None => Ok(config_dir.join(".iplist")),
+        Some(name) => {
+            if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+                return Err(color_eyre::eyre::eyre!("Invalid profile name: {}", name));
+            }
+            Ok(config_dir.join(format!("{}.iplist", name)))
+        }
+    }
+}
+
+pub fn load_config(profile: Option<&str>) -> Result<Config> {
+    let config_file = get_config_file(profile)?;
 
     if !config_file.exists() {
+        // Only the default list is created on demand; a missing profile is most likely a typo
+        if let Some(name) = profile {
+            return Err(color_eyre::eyre::eyre!(
+                "Profile '{}' not found at {}",
+                name,
+                config_file.display()
+            ));
+        }
+
         let default_config = Config::default();
-        save_config(&default_config)?;
+        save_config(&default_config, profile)?;
         return Ok(default_config);
     }
 
@@ -65,21 +695,153 @@ pub fn load_config() -> Result<Config> {
     Ok(config)
 }
 
-pub fn save_config(config: &Config) -> Result<()> {
+// Layers JSON config files in order. Objects merge key by key with later files winning,
+// `targets` concatenate with a later target replacing an earlier one probed the same way (see
+// probe_identity), and any other value (including arrays such as quiet_hours) is replaced
+// outright. Defaults apply only to settings no file sets
+pub fn load_config_files(paths: &[PathBuf]) -> Result<Config> {
+    let mut merged = serde_json::Value::Object(serde_json::Map::new());
+
+    for path in paths {
+        let content = fs::read_to_string(path)
+            .map_err(|e| color_eyre::eyre::eyre!("Could not read {}: {}", path.display(), e))?;
+        let layer: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| color_eyre::eyre::eyre!("Invalid config {}: {}", path.display(), e))?;
+        if !layer.is_object() {
+            return Err(color_eyre::eyre::eyre!(
+                "Config {} must be a JSON object",
+                path.display()
+            ));
+        }
+        merge_config_layer(&mut merged, layer, true);
+    }
+
+    // Settings no file mentions fall back to the defaults
+    let mut config = serde_json::to_value(Config::default())?;
+    if let Some(config) = config.as_object_mut() {
+        config.insert("targets".to_string(), serde_json::Value::Array(Vec::new()));
+    }
+    merge_config_layer(&mut config, merged, true);
+
+    Ok(serde_json::from_value(config)?)
+}
+
+fn merge_config_layer(base: &mut serde_json::Value, layer: serde_json::Value, top_level: bool) {
+    use serde_json::Value;
+
+    match (base, layer) {
+        (Value::Object(base), Value::Object(layer)) => {
+            for (key, value) in layer {
+                match base.get_mut(&key) {
+                    Some(Value::Array(existing)) if top_level && key == "targets" => {
+                        if let Value::Array(targets) = value {
+                            merge_targets(existing, targets);
+                        }
+                    }
+                    Some(existing) => merge_config_layer(existing, value, false),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, layer) => *base = layer,
+    }
+}
+
+// What makes two targets the same probe: one host checked on another SSH port, or from
+// another namespace or interface, is a separate target
+fn probe_identity(target: &serde_json::Value) -> Option<[Option<&serde_json::Value>; 4]> {
+    let ip = target.get("ip")?;
+    let field = |key| target.get(key).filter(|value| !value.is_null());
+    Some([
+        Some(ip),
+        field("ssh_port"),
+        field("netns"),
+        field("interface"),
+    ])
+}
+
+fn merge_targets(existing: &mut Vec<serde_json::Value>, targets: Vec<serde_json::Value>) {
+    for target in targets {
+        let identity = probe_identity(&target);
+        match existing
+            .iter()
+            .position(|t| identity.is_some() && probe_identity(t) == identity)
+        {
+            Some(slot) => existing[slot] = target,
+            None => existing.push(target),
+        }
+    }
+}
+
+// Like load_config, but a file that no longer parses doesn't stop the monitor: it is copied
+// to `<file>.bak` and the last config that loaded cleanly (or the defaults) is used instead,
+// with a warning describing what happened
+pub fn load_config_or_recover(profile: Option<&str>) -> Result<(Config, Option<String>)> {
+    let config_file = get_config_file(profile)?;
+    let good_file = with_suffix(&config_file, ".last-good");
+
+    let parse_error = match load_config(profile) {
+        Ok(config) => {
+            // Best effort: a missing known-good copy only matters after a later corruption
+            if let Ok(content) = serde_json::to_string_pretty(&config) {
+                let _ = fs::write(&good_file, content);
+            }
+            return Ok((config, None));
+        }
+        Err(e) if e.downcast_ref::<serde_json::Error>().is_some() => e,
+        Err(e) => return Err(e),
+    };
+
+    let backup_file = with_suffix(&config_file, ".bak");
+    fs::copy(&config_file, &backup_file)?;
+
+    let (config, source) = match fs::read_to_string(&good_file)
+        .ok()
+        .and_then(|content| serde_json::from_str::<Config>(&content).ok())
+    {
+        Some(config) => (config, "the last good config"),
+        None => (Config::default(), "defaults"),
+    };
+
+    let warning = format!(
+        "{} is invalid ({}); backed up to {} and using {}",
+        config_file.display(),
+        parse_error,
+        backup_file.display(),
+        source
+    );
+    Ok((config, Some(warning)))
+}
+
+fn with_suffix(path: &std::path::Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+pub fn save_config(config: &Config, profile: Option<&str>) -> Result<()> {
     let config_dir = get_config_dir()?;
     fs::create_dir_all(&config_dir)?;
 
-    let config_file = config_dir.join(".iplist");
+    let config_file = get_config_file(profile)?;
     let content = serde_json::to_string_pretty(config)?;
     fs::write(config_file, content)?;
     Ok(())
 }
 
-pub fn load_targets_from_simple_list() -> Result<Vec<Target>> {
-    let config_dir = get_config_dir()?;
-    let iplist_file = config_dir.join(".iplist");
+pub fn load_targets_from_simple_list(profile: Option<&str>) -> Result<Vec<Target>> {
+    let iplist_file = get_config_file(profile)?;
 
     if !iplist_file.exists() {
+        if let Some(name) = profile {
+            return Err(color_eyre::eyre::eyre!(
+                "Profile '{}' not found at {}",
+                name,
+                iplist_file.display()
+            ));
+        }
         return Ok(Config::default().targets);
     }
 
@@ -105,8 +867,7 @@ pub fn load_targets_from_simple_list() -> Result<Vec<Target>> {
             Target {
                 ip,
                 name,
-                ssh_port: None,
-                ssh_user: None,
+                ..Default::default()
             }
         })
         .collect();
@@ -114,9 +875,247 @@ pub fn load_targets_from_simple_list() -> Result<Vec<Target>> {
     Ok(targets)
 }
 
+// An /etc/hosts-style file: an address, then its canonical name and any aliases. Each address
+// becomes one target named by its canonical name; an address listed again keeps its first
+// name. Lines without an address and a name are skipped, and returned as one warning
+pub fn load_targets_from_hosts_file(path: &Path) -> Result<(Vec<Target>, Option<String>)> {
+    let content = fs::read_to_string(path).map_err(|e| {
+        color_eyre::eyre::eyre!("Failed to read hosts file {}: {}", path.display(), e)
+    })?;
+
+    let mut targets: Vec<Target> = Vec::new();
+    let mut skipped = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut fields = line.split_whitespace();
+        let Some(ip) = fields.next() else {
+            continue;
+        };
+        let (Ok(addr), Some(name)) = (ip.parse::<std::net::IpAddr>(), fields.next()) else {
+            skipped.push((number + 1).to_string());
+            continue;
+        };
+
+        // The usual ff02::1 ip6-allnodes and friends; multicast needs its own target settings
+        let ip = addr.to_string();
+        if addr.is_multicast() || targets.iter().any(|t| t.ip == ip) {
+            continue;
+        }
+        targets.push(Target {
+            ip,
+            name: Some(name.to_string()),
+            ..Default::default()
+        });
+    }
+
+    let warning = (!skipped.is_empty()).then(|| {
+        format!(
+            "Skipped malformed lines in {}: {}",
+            path.display(),
+            skipped.join(", ")
+        )
+    });
+    Ok((targets, warning))
+}
+
+// Ranges beyond this many addresses are rejected outright; past the warning size they load,
+// but with a note since every address costs a probe per cycle
+const MAX_EXPANDED_TARGETS: u64 = 4096;
+const LARGE_RANGE_WARNING: u64 = 256;
+
+// Order of the tabs and the overview: highest priority first, then by name. A config that sets
+// no priorities keeps its own order
+pub fn sort_by_priority(targets: &mut [Target]) {
+    if targets.iter().all(|t| t.priority.is_none()) {
+        return;
+    }
+    let name = |t: &Target| t.name.as_deref().unwrap_or(&t.ip).to_lowercase();
+    targets.sort_by(|a, b| {
+        b.priority
+            .unwrap_or(0)
+            .cmp(&a.priority.unwrap_or(0))
+            .then_with(|| name(a).cmp(&name(b)))
+    });
+}
+
+// Expands IPv4 CIDR blocks (192.168.1.0/24) and ranges (10.0.0.1-10.0.0.50, or 10.0.0.1-50)
+// into one target per address. Expanded targets are named by IP; a name containing `{ip}` is
+// used as a template, and any other name becomes a prefix. Also returns a warning for each
+// range past LARGE_RANGE_WARNING
+pub fn expand_targets(targets: Vec<Target>) -> Result<(Vec<Target>, Vec<String>)> {
+    let mut expanded = Vec::with_capacity(targets.len());
+    let mut warnings = Vec::new();
+
+    for target in targets {
+        let Some((first, last)) = parse_ipv4_range(&target.ip)? else {
+            expanded.push(target);
+            continue;
+        };
+
+        let count = u64::from(last - first) + 1;
+        if count > MAX_EXPANDED_TARGETS {
+            return Err(color_eyre::eyre::eyre!(
+                "Range {} has {} addresses; at most {} are supported",
+                target.ip,
+                count,
+                MAX_EXPANDED_TARGETS
+            ));
+        }
+        if count > LARGE_RANGE_WARNING {
+            warnings.push(format!(
+                "{} expands to {} targets, each probed every cycle",
+                target.ip, count
+            ));
+        }
+
+        for raw in first..=last {
+            let ip = std::net::Ipv4Addr::from(raw).to_string();
+            let name = match &target.name {
+                Some(template) if template.contains("{ip}") => template.replace("{ip}", &ip),
+                Some(prefix) => format!("{} {}", prefix, ip),
+                None => ip.clone(),
+            };
+            expanded.push(Target {
+                ip,
+                name: Some(name),
+                ..target.clone()
+            });
+        }
+    }
+
+    Ok((expanded, warnings))
+}
+
+// A target's `ip`: an address, a range that expand_targets understands, or a hostname
+pub fn validate_address(spec: &str) -> Result<()> {
+    if spec.parse::<std::net::IpAddr>().is_ok() || parse_ipv4_range(spec)?.is_some() {
+        return Ok(());
+    }
+    // The interface is only looked up when probing; it may not exist yet
+    if let Some(scoped) = crate::zone::split_zone(spec) {
+        return scoped.map(|_| ()).map_err(|e| color_eyre::eyre::eyre!(e));
+    }
+    let valid_label = |label: &str| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    };
+    if spec.len() <= 253 && spec.split('.').all(valid_label) {
+        Ok(())
+    } else {
+        Err(color_eyre::eyre::eyre!(
+            "Not an address, range or hostname: {}",
+            spec
+        ))
+    }
+}
+
+// Inclusive (first, last) addresses, or None when `spec` is a single address or hostname
+fn parse_ipv4_range(spec: &str) -> Result<Option<(u32, u32)>> {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    if let Some((addr, prefix)) = spec.split_once('/') {
+        let addr = match addr.parse::<IpAddr>() {
+            Ok(IpAddr::V4(addr)) => addr,
+            Ok(IpAddr::V6(_)) => {
+                return Err(color_eyre::eyre::eyre!(
+                    "IPv6 ranges are not supported: {}",
+                    spec
+                ));
+            }
+            Err(_) => return Err(color_eyre::eyre::eyre!("Invalid CIDR block: {}", spec)),
+        };
+        let prefix = prefix
+            .parse::<u32>()
+            .ok()
+            .filter(|p| *p <= 32)
+            .ok_or_else(|| color_eyre::eyre::eyre!("Invalid CIDR prefix: {}", spec))?;
+
+        let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+        let network = u32::from(addr) & mask;
+        let broadcast = network | !mask;
+        // Skip the network and broadcast addresses, which don't belong to a host
+        return Ok(Some(if prefix <= 30 {
+            (network + 1, broadcast - 1)
+        } else {
+            (network, broadcast)
+        }));
+    }
+
+    // Hostnames may contain '-', so only treat it as a range when both ends are addresses
+    let Some((start, end)) = spec.split_once('-') else {
+        return Ok(None);
+    };
+    let Ok(start) = start.trim().parse::<Ipv4Addr>() else {
+        return Ok(None);
+    };
+    let end = match end.trim().parse::<Ipv4Addr>() {
+        Ok(end) => end,
+        Err(_) => match end.trim().parse::<u8>() {
+            Ok(last_octet) => {
+                let [a, b, c, _] = start.octets();
+                Ipv4Addr::new(a, b, c, last_octet)
+            }
+            Err(_) => return Ok(None),
+        },
+    };
+
+    let (first, last) = (u32::from(start), u32::from(end));
+    if first > last {
+        return Err(color_eyre::eyre::eyre!(
+            "Range ends before it starts: {}",
+            spec
+        ));
+    }
+    Ok(Some((first, last)))
+}
+
+// Default IPv4 routes from the kernel routing table, one target per route. Addresses in
+// /proc/net/route are the raw network-order bytes printed as a host-order hex word
+pub fn detect_default_gateways() -> Result<Vec<Target>> {
+    let content = fs::read_to_string("/proc/net/route")
+        .map_err(|e| color_eyre::eyre::eyre!("Could not read routing table: {}", e))?;
+
+    let mut targets: Vec<Target> = Vec::new();
+    for line in content.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 8 {
+            continue;
+        }
+
+        let (iface, destination, gateway, flags, mask) =
+            (fields[0], fields[1], fields[2], fields[3], fields[7]);
+        let is_gateway_route = u16::from_str_radix(flags, 16).is_ok_and(|f| f & RTF_GATEWAY != 0);
+        if destination != "00000000" || mask != "00000000" || !is_gateway_route {
+            continue;
+        }
+
+        let Ok(raw) = u32::from_str_radix(gateway, 16) else {
+            continue;
+        };
+        let ip = std::net::Ipv4Addr::from(raw.to_ne_bytes()).to_string();
+        if targets.iter().any(|t| t.ip == ip) {
+            continue;
+        }
+
+        targets.push(Target {
+            ip,
+            name: Some(format!("Gateway ({})", iface)),
+            ..Default::default()
+        });
+    }
+
+    Ok(targets)
+}
+
+const RTF_GATEWAY: u16 = 0x0002;
+
 pub fn parse_targets_from_args(
     ip_list: Option<String>,
     ssh_list: Option<String>,
+    default_ssh_user: Option<&str>,
 ) -> Result<Vec<Target>> {
     let mut targets = Vec::new();
 
@@ -126,9 +1125,7 @@ pub fn parse_targets_from_args(
             if !ip.is_empty() {
                 targets.push(Target {
                     ip,
-                    name: None,
-                    ssh_port: None,
-                    ssh_user: None,
+                    ..Default::default()
                 });
             }
         }
@@ -138,11 +1135,14 @@ pub fn parse_targets_from_args(
         for ssh_target in ssh_targets.split(',') {
             let ssh_target = ssh_target.trim();
             if !ssh_target.is_empty() {
+                // An explicit USER@ always wins over the --ssh-user default
                 let (user, ip_port) = if let Some(pos) = ssh_target.find('@') {
                     (&ssh_target[..pos], &ssh_target[pos + 1..])
+                } else if let Some(user) = default_ssh_user {
+                    (user, ssh_target)
                 } else {
                     return Err(color_eyre::eyre::eyre!(
-                        "Invalid SSH format: {}. Expected USER@ip[:port]",
+                        "Invalid SSH format: {}. Expected USER@ip[:port] or a --ssh-user default",
                         ssh_target
                     ));
                 };
@@ -160,9 +1160,10 @@ pub fn parse_targets_from_args(
 
                 targets.push(Target {
                     ip,
-                    name: Some(format!("{}@{}", user, ssh_target)),
+                    name: Some(format!("{}@{}", user, ip_port)),
                     ssh_port: port,
                     ssh_user: Some(user.to_string()),
+                    ..Default::default()
                 });
             }
         }
@@ -170,3 +1171,112 @@ pub fn parse_targets_from_args(
 
     Ok(targets)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Each call gets its own directory, so tests running in parallel don't share files
+    fn write_layers(name: &str, layers: &[&str]) -> Vec<PathBuf> {
+        let dir = std::env::temp_dir().join(format!(
+            "boxmonitor-{}-{}-{}",
+            name,
+            std::process::id(),
+            rand::random::<u32>()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        layers
+            .iter()
+            .enumerate()
+            .map(|(i, layer)| {
+                let path = dir.join(format!("{}.json", i));
+                fs::write(&path, layer).unwrap();
+                path
+            })
+            .collect()
+    }
+
+    #[test]
+    fn later_layers_override_and_nested_sections_merge() {
+        let paths = write_layers(
+            "override",
+            &[
+                r#"{"ping_interval_ms": 500, "health": {"loss_percent": 10, "latency_ms": 300},
+                    "quiet_hours": [{"start": "22:00:00", "end": "06:00:00"}]}"#,
+                r#"{"ping_interval_ms": 250, "health": {"latency_ms": 50}, "quiet_hours": []}"#,
+            ],
+        );
+        let config = load_config_files(&paths).unwrap();
+        assert_eq!(config.ping_interval_ms, 250);
+        assert_eq!(config.health.latency_ms, 50.0);
+        assert_eq!(config.health.loss_percent, 10.0);
+        assert!(config.quiet_hours.is_empty());
+        // Untouched settings keep their defaults, and no default targets sneak in
+        assert_eq!(config.history_size, Config::default().history_size);
+        assert!(config.targets.is_empty());
+    }
+
+    #[test]
+    fn targets_concatenate_and_replace_by_probe_identity() {
+        let paths = write_layers(
+            "targets",
+            &[
+                r#"{"targets": [{"ip": "10.0.0.1", "name": "old"},
+                                {"ip": "10.0.0.2", "ssh_port": 22, "ssh_user": "a"}]}"#,
+                r#"{"targets": [{"ip": "10.0.0.1", "name": "new"},
+                                {"ip": "10.0.0.2", "ssh_port": 2222, "ssh_user": "b"},
+                                {"ip": "10.0.0.3"}]}"#,
+            ],
+        );
+        let config = load_config_files(&paths).unwrap();
+        let summary: Vec<(&str, Option<&str>, Option<u16>)> = config
+            .targets
+            .iter()
+            .map(|t| (t.ip.as_str(), t.name.as_deref(), t.ssh_port))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("10.0.0.1", Some("new"), None),
+                ("10.0.0.2", None, Some(22)),
+                ("10.0.0.2", None, Some(2222)),
+                ("10.0.0.3", None, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn non_object_layer_is_rejected() {
+        let paths = write_layers("array", &["[1, 2]"]);
+        assert!(load_config_files(&paths).is_err());
+    }
+
+    #[test]
+    fn default_ssh_user_applies_to_bare_hosts() {
+        let targets =
+            parse_targets_from_args(None, Some("10.0.0.1,10.0.0.2:2222".into()), Some("ops"))
+                .unwrap();
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0].ssh_user.as_deref(), Some("ops"));
+        assert_eq!(targets[0].ssh_port, Some(22));
+        assert_eq!(targets[1].ssh_user.as_deref(), Some("ops"));
+        assert_eq!(targets[1].ssh_port, Some(2222));
+    }
+
+    #[test]
+    fn explicit_ssh_user_wins_over_default() {
+        let targets =
+            parse_targets_from_args(None, Some("root@10.0.0.1".into()), Some("ops")).unwrap();
+        assert_eq!(targets[0].ssh_user.as_deref(), Some("root"));
+        assert_eq!(targets[0].ip, "10.0.0.1");
+    }
+
+    #[test]
+    fn bare_ssh_host_without_default_is_an_error() {
+        let result = parse_targets_from_args(None, Some("10.0.0.1".into()), None);
+        assert!(result.is_err());
+        // Unaffected when every host names its user
+        let targets = parse_targets_from_args(None, Some("root@10.0.0.1".into()), None).unwrap();
+        assert_eq!(targets[0].ssh_user.as_deref(), Some("root"));
+    }
+}
@@ -0,0 +1,159 @@
+use crate::clock;
+use crate::config::{DnsCheck, DnsProtocol};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(2);
+const LOOKUP_HISTORY_SIZE: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lookup {
+    pub timestamp: DateTime<Utc>,
+    pub addresses: Vec<IpAddr>,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub protocol: DnsProtocol,
+    #[serde(default)]
+    pub tls_ms: Option<f64>,
+    #[serde(default)]
+    pub query_ms: Option<f64>,
+}
+
+// How a lookup went out and how long its parts took; a part that never finished is None
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LookupTiming {
+    pub protocol: DnsProtocol,
+    // TCP connect and TLS handshake, for dot and doh
+    pub tls_ms: Option<f64>,
+    pub query_ms: Option<f64>,
+}
+
+// Where a hostname target is being probed, and the lookups that got it there. A name that
+// answers with a different address now and then (round-robin, split-horizon, a flaky
+// resolver) keeps its address until a new one has come back several lookups in a row
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Resolution {
+    pub current: Option<IpAddr>,
+    // An address other than `current`, with how many consecutive lookups returned it
+    pub candidate: Option<(IpAddr, usize)>,
+    pub history: VecDeque<Lookup>,
+}
+
+impl Resolution {
+    // Hostnames get one; literal addresses, scoped ones included, are probed as they are
+    pub fn for_target(ip: &str) -> Option<Self> {
+        (ip.parse::<IpAddr>().is_err() && !ip.contains('%')).then(Self::default)
+    }
+
+    pub fn due(&self, refresh: chrono::Duration) -> bool {
+        self.history
+            .back()
+            .is_none_or(|lookup| clock::now() - lookup.timestamp >= refresh)
+    }
+
+    pub fn last_lookup(&self) -> Option<&Lookup> {
+        self.history.back()
+    }
+
+    pub fn last_error(&self) -> Option<&str> {
+        self.history
+            .back()
+            .and_then(|lookup| lookup.error.as_deref())
+    }
+
+    // Records a lookup, returning "old -> new" when it moved the target to a new address.
+    // A failed lookup keeps the current address
+    pub fn record(
+        &mut self,
+        outcome: Result<Vec<IpAddr>, String>,
+        timing: LookupTiming,
+        stable_resolutions: usize,
+    ) -> Option<String> {
+        if self.history.len() >= LOOKUP_HISTORY_SIZE {
+            self.history.pop_front();
+        }
+        let (addresses, error) = match outcome {
+            Ok(addresses) => (addresses, None),
+            Err(error) => (Vec::new(), Some(error)),
+        };
+        self.history.push_back(Lookup {
+            timestamp: clock::now(),
+            addresses: addresses.clone(),
+            error,
+            protocol: timing.protocol,
+            tls_ms: timing.tls_ms,
+            query_ms: timing.query_ms,
+        });
+
+        let first = *addresses.first()?;
+        let Some(current) = self.current else {
+            self.current = Some(first);
+            return None;
+        };
+        // Any answer that still includes the current address is no reason to move
+        if addresses.contains(&current) {
+            self.candidate = None;
+            return None;
+        }
+
+        let streak = match self.candidate {
+            Some((addr, streak)) if addr == first => streak + 1,
+            _ => 1,
+        };
+        if streak < stable_resolutions {
+            self.candidate = Some((first, streak));
+            return None;
+        }
+        self.current = Some(first);
+        self.candidate = None;
+        Some(format!("{} -> {}", current, first))
+    }
+}
+
+pub async fn lookup(host: &str, check: &DnsCheck) -> (Result<Vec<IpAddr>, String>, LookupTiming) {
+    let mut timing = LookupTiming {
+        protocol: check.protocol,
+        ..Default::default()
+    };
+    let outcome = tokio::time::timeout(LOOKUP_TIMEOUT, resolve(host, check, &mut timing))
+        .await
+        .unwrap_or_else(|_| {
+            Err(format!(
+                "DNS lookup timed out after {}s",
+                LOOKUP_TIMEOUT.as_secs()
+            ))
+        });
+    (outcome.and_then(deduplicate), timing)
+}
+
+async fn resolve(
+    host: &str,
+    check: &DnsCheck,
+    timing: &mut LookupTiming,
+) -> Result<Vec<IpAddr>, String> {
+    if check.protocol != DnsProtocol::System {
+        return crate::resolver::resolve(host, check, timing).await;
+    }
+    let start = Instant::now();
+    let addresses = tokio::net::lookup_host((host, 0))
+        .await
+        .map_err(|e| format!("DNS lookup failed: {}", e))?;
+    timing.query_ms = Some(start.elapsed().as_secs_f64() * 1000.0);
+    Ok(addresses.map(|a| a.ip()).collect())
+}
+
+fn deduplicate(addresses: Vec<IpAddr>) -> Result<Vec<IpAddr>, String> {
+    let mut unique: Vec<IpAddr> = Vec::new();
+    for addr in addresses {
+        if !unique.contains(&addr) {
+            unique.push(addr);
+        }
+    }
+    if unique.is_empty() {
+        return Err("DNS lookup returned no addresses".to_string());
+    }
+    Ok(unique)
+}
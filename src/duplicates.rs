@@ -0,0 +1,114 @@
+use crate::monitor::icmp_config;
+use crate::multicast::{ICMPV4_ECHO_REPLY, ICMPV6_ECHO_REPLY};
+use socket2::Type as SockType;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use surge_ping::{AsyncSocket, Client, PingIdentifier, PingSequence};
+
+// Replies are remembered this long, far longer than any probe waits for its first one
+const RETENTION: Duration = Duration::from_secs(60);
+const PRUNE_EVERY: Duration = Duration::from_secs(10);
+
+// (source, identifier on the wire, sequence)
+type ReplyKey = (IpAddr, u16, u16);
+
+// Counts every echo reply that reaches the host. The ping client stops listening for a
+// sequence once its first reply is in, so anything after that ("DUP!" in ping) would otherwise
+// go unseen. A raw socket gets its own copy of each ICMP message, so this takes nothing away
+// from the client. Without the privileges for one, nothing is counted
+#[derive(Default)]
+pub struct DuplicateWatch {
+    seen: Arc<Mutex<HashMap<ReplyKey, (usize, Instant)>>>,
+}
+
+impl DuplicateWatch {
+    pub fn start() -> Self {
+        let watch = Self::default();
+        let families = [
+            IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        ];
+        for family in families {
+            let mut config = icmp_config(&family, None);
+            config.sock_type_hint = SockType::RAW;
+            if let Ok(socket) = AsyncSocket::new(&config)
+                && socket.get_type() == SockType::RAW
+            {
+                tokio::spawn(listen(socket, family.is_ipv4(), Arc::clone(&watch.seen)));
+            }
+        }
+        watch
+    }
+
+    // Replies seen so far to one probe sent on `client`
+    pub fn replies(
+        &self,
+        client: &Client,
+        addr: IpAddr,
+        identifier: PingIdentifier,
+        sequence: PingSequence,
+    ) -> usize {
+        let key = (addr, wire_identifier(client, identifier), sequence.0);
+        self.seen
+            .lock()
+            .unwrap()
+            .get(&key)
+            .map_or(0, |(count, _)| *count)
+    }
+}
+
+// Unprivileged ICMP sockets put their own port on the wire in place of our identifier
+fn wire_identifier(client: &Client, identifier: PingIdentifier) -> u16 {
+    let socket = client.get_socket();
+    match socket.get_type() {
+        SockType::RAW => identifier.0,
+        _ => socket.local_addr().map_or(identifier.0, |addr| addr.port()),
+    }
+}
+
+async fn listen(
+    socket: AsyncSocket,
+    ipv4: bool,
+    seen: Arc<Mutex<HashMap<ReplyKey, (usize, Instant)>>>,
+) {
+    let mut buf = [0u8; 2048];
+    let mut pruned_at = Instant::now();
+    loop {
+        // A socket that has started failing stays broken; counting just stops
+        let Ok((len, from)) = socket.recv_from(&mut buf).await else {
+            return;
+        };
+        let Some((identifier, sequence)) = echo_reply(&buf[..len], ipv4) else {
+            continue;
+        };
+        let now = Instant::now();
+        let mut seen = seen.lock().unwrap();
+        if now - pruned_at > PRUNE_EVERY {
+            seen.retain(|_, (_, at)| now - *at < RETENTION);
+            pruned_at = now;
+        }
+        let entry = seen
+            .entry((from.ip(), identifier, sequence))
+            .or_insert((0, now));
+        entry.0 += 1;
+    }
+}
+
+// Identifier and sequence of an echo reply; raw IPv4 sockets deliver the IP header too
+fn echo_reply(buf: &[u8], ipv4: bool) -> Option<(u16, u16)> {
+    let (icmp, reply_kind) = if ipv4 {
+        let header_len = buf.first().map_or(0, |b| (b & 0x0f) as usize * 4);
+        (buf.get(header_len..)?, ICMPV4_ECHO_REPLY)
+    } else {
+        (buf, ICMPV6_ECHO_REPLY)
+    };
+    if icmp.len() < 8 || icmp[0] != reply_kind {
+        return None;
+    }
+    Some((
+        u16::from_be_bytes([icmp[4], icmp[5]]),
+        u16::from_be_bytes([icmp[6], icmp[7]]),
+    ))
+}
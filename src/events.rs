@@ -0,0 +1,88 @@
+use crate::clock;
+use crate::config::Target;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+const EVENT_FEED_SIZE: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum EventKind {
+    Down,
+    Degraded,
+    Recovered,
+    DnsChanged,
+    MacChanged,
+    UpstreamDown,
+    Escalated,
+    LocalNetworkLost,
+    LocalNetworkRestored,
+    LatencyJump,
+    ReplyAnomaly,
+    DuplicateReplies,
+    ProbeSkipped,
+    AlertsMuted,
+    ConfigSaved,
+    MonitorRestarted,
+    Acknowledged,
+    HistoryCleared,
+    LikelyLocalOutage,
+}
+
+impl EventKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            EventKind::Down => "down",
+            EventKind::Degraded => "degraded",
+            EventKind::Recovered => "recovered",
+            EventKind::DnsChanged => "dns",
+            EventKind::MacChanged => "mac",
+            EventKind::UpstreamDown => "masked",
+            EventKind::Escalated => "escalated",
+            EventKind::LocalNetworkLost => "offline",
+            EventKind::LocalNetworkRestored => "online",
+            EventKind::LatencyJump => "latency",
+            EventKind::ReplyAnomaly => "anomaly",
+            EventKind::DuplicateReplies => "dup",
+            EventKind::ProbeSkipped => "skipped",
+            EventKind::AlertsMuted => "muted",
+            EventKind::ConfigSaved => "config",
+            EventKind::MonitorRestarted => "restart",
+            EventKind::Acknowledged => "ack",
+            EventKind::HistoryCleared => "cleared",
+            EventKind::LikelyLocalOutage => "mass down",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    pub timestamp: DateTime<Utc>,
+    // None for events about this machine rather than one target
+    pub ip: Option<String>,
+    pub target_name: Option<String>,
+    pub kind: EventKind,
+    pub message: String,
+}
+
+// Every notable transition across all targets, oldest first. Unlike the failure log it also
+// records recoveries and other changes that aren't failures
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventFeed {
+    pub events: VecDeque<Event>,
+}
+
+impl EventFeed {
+    pub fn push(&mut self, target: Option<&Target>, kind: EventKind, message: String) {
+        if self.events.len() >= EVENT_FEED_SIZE {
+            self.events.pop_front();
+        }
+        self.events.push_back(Event {
+            timestamp: clock::now(),
+            ip: target.map(|t| t.ip.clone()),
+            target_name: target.map(|t| t.name.clone().unwrap_or_else(|| t.ip.clone())),
+            kind,
+            message,
+        });
+    }
+}
@@ -0,0 +1,138 @@
+use crate::config::get_config_dir;
+use crate::monitor::TargetStats;
+use chrono::Local;
+use color_eyre::Result;
+use plotters::prelude::*;
+use std::fs;
+use std::path::PathBuf;
+
+const EXPORT_WIDTH: u32 = 1024;
+const EXPORT_HEIGHT: u32 = 600;
+
+const SERIES_COLORS: [RGBColor; 6] = [
+    RGBColor(46, 160, 67),
+    RGBColor(31, 111, 235),
+    RGBColor(210, 153, 34),
+    RGBColor(191, 57, 137),
+    RGBColor(27, 161, 179),
+    RGBColor(207, 34, 46),
+];
+
+#[derive(Clone, Copy)]
+pub enum ExportSeries {
+    Ping,
+    Ssh,
+}
+
+impl ExportSeries {
+    fn title(self) -> &'static str {
+        match self {
+            ExportSeries::Ping => "Ping Latency (ms)",
+            ExportSeries::Ssh => "SSH Connection Time (ms)",
+        }
+    }
+
+    // Samples as (index, value), with None for failures so they break the line
+    fn samples(self, target: &TargetStats) -> Vec<(usize, Option<f64>)> {
+        match self {
+            ExportSeries::Ping => target
+                .ping_history
+                .iter()
+                .map(|r| r.latency_ms)
+                .enumerate()
+                .collect(),
+            ExportSeries::Ssh => target
+                .ssh_history
+                .iter()
+                .map(|r| r.connection_time_ms)
+                .enumerate()
+                .collect(),
+        }
+    }
+}
+
+// Re-renders the given targets' history with plotters, independent of the terminal chart, and
+// writes it under the config dir as a timestamped SVG
+pub fn export_chart_svg(
+    targets: &[TargetStats],
+    series: ExportSeries,
+    label: &str,
+) -> Result<PathBuf> {
+    let export_dir = get_config_dir()?.join("exports");
+    fs::create_dir_all(&export_dir)?;
+
+    let slug: String = label
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let path = export_dir.join(format!(
+        "{}-{}.svg",
+        slug,
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+
+    let all_samples: Vec<Vec<(usize, Option<f64>)>> =
+        targets.iter().map(|t| series.samples(t)).collect();
+    let x_max = all_samples.iter().map(Vec::len).max().unwrap_or(0).max(1);
+    let y_max = all_samples
+        .iter()
+        .flatten()
+        .filter_map(|(_, value)| *value)
+        .fold(0.0, f64::max)
+        .max(1.0)
+        * 1.1;
+
+    // The backend borrows the path until the drawing is dropped
+    {
+        let root = SVGBackend::new(&path, (EXPORT_WIDTH, EXPORT_HEIGHT)).into_drawing_area();
+        root.fill(&WHITE)?;
+
+        let mut chart = ChartBuilder::on(&root)
+            .caption(
+                format!("{} - {}", label, series.title()),
+                ("sans-serif", 24),
+            )
+            .margin(16)
+            .x_label_area_size(40)
+            .y_label_area_size(60)
+            .build_cartesian_2d(0..x_max, 0.0..y_max)?;
+
+        chart
+            .configure_mesh()
+            .x_desc("Time (samples)")
+            .y_desc(series.title())
+            .draw()?;
+
+        for (idx, (target, samples)) in targets.iter().zip(&all_samples).enumerate() {
+            let color = SERIES_COLORS[idx % SERIES_COLORS.len()];
+            let name = target.target.name.as_ref().unwrap_or(&target.target.ip);
+
+            let mut labeled = false;
+            for segment in samples.split(|(_, value)| value.is_none()) {
+                if segment.is_empty() {
+                    continue;
+                }
+                let points = segment
+                    .iter()
+                    .filter_map(|(i, value)| value.map(|v| (*i, v)));
+                let drawn = chart.draw_series(LineSeries::new(points, color.stroke_width(2)))?;
+                // One legend entry per target, not per segment
+                if !labeled {
+                    drawn.label(name.clone()).legend(move |(x, y)| {
+                        PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2))
+                    });
+                    labeled = true;
+                }
+            }
+        }
+
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+
+        root.present()?;
+    }
+    Ok(path)
+}
@@ -0,0 +1,74 @@
+use crate::monitor::MonitorStatus;
+use color_eyre::Result;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+
+// Enough for a load balancer's request line and headers; anything longer is cut off
+const MAX_REQUEST: usize = 8 * 1024;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const ACCEPT_RETRY: Duration = Duration::from_millis(100);
+
+// Bound up front, so a port that's taken fails startup instead of going unnoticed
+pub async fn bind(addr: &str) -> Result<TcpListener> {
+    TcpListener::bind(addr).await.map_err(|e| {
+        color_eyre::eyre::eyre!("Could not listen for health checks on {}: {}", addr, e)
+    })
+}
+
+// `GET /healthz` answers 200 while monitoring cycles keep completing and 503 once the loop has
+// stalled, going by the same last-cycle time as the stall warning and the systemd watchdog
+pub async fn serve(listener: TcpListener, status: Arc<Mutex<MonitorStatus>>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let status = Arc::clone(&status);
+                tokio::spawn(async move {
+                    let _ = tokio::time::timeout(REQUEST_TIMEOUT, respond(stream, status)).await;
+                });
+            }
+            // Out of file descriptors, say; not worth spinning on
+            Err(_) => tokio::time::sleep(ACCEPT_RETRY).await,
+        }
+    }
+}
+
+async fn respond(mut stream: TcpStream, status: Arc<Mutex<MonitorStatus>>) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&chunk[..read]);
+    }
+
+    // Only the request line matters
+    let request = String::from_utf8_lossy(&request);
+    let mut request_line = request.split_whitespace();
+    let (method, path) = (request_line.next(), request_line.next());
+    let (code, body) = match (method, path) {
+        (Some("GET" | "HEAD"), Some("/healthz")) => match status.lock().await.stalled_for() {
+            None => ("200 OK", "ok\n".to_string()),
+            Some(stalled) => (
+                "503 Service Unavailable",
+                format!("stalled, no cycle for {}s\n", stalled.num_seconds()),
+            ),
+        },
+        _ => ("404 Not Found", "not found\n".to_string()),
+    };
+
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        code,
+        body.len()
+    );
+    if method != Some("HEAD") {
+        response.push_str(&body);
+    }
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
@@ -0,0 +1,227 @@
+use crate::alerts::Alert;
+use crate::config::{Config, ExportTimestamps, Target};
+use crate::monitor::{PingResult, SshResult};
+use crate::units::ExportedTimestamp;
+use serde::Serialize;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+
+// Past this many hooks still running, further results are dropped rather than queued
+const MAX_RUNNING_HOOKS: usize = 8;
+
+// What the hook receives on stdin, one JSON object per run
+#[derive(Debug, Serialize)]
+pub struct ResultEvent {
+    pub ip: String,
+    pub name: Option<String>,
+    pub check: &'static str,
+    pub timestamp: ExportedTimestamp,
+    pub success: bool,
+    pub latency_ms: Option<f64>,
+    pub failure_reason: Option<String>,
+    pub warmup: bool,
+    pub local_outage: bool,
+}
+
+impl ResultEvent {
+    pub fn ping(target: &Target, result: &PingResult, timestamps: ExportTimestamps) -> Self {
+        Self {
+            ip: target.ip.clone(),
+            name: target.name.clone(),
+            check: "ping",
+            timestamp: timestamps.stamp(result.timestamp),
+            success: result.success,
+            latency_ms: result.latency_ms,
+            failure_reason: result.failure_reason.clone(),
+            warmup: result.warmup,
+            local_outage: result.local_outage,
+        }
+    }
+
+    pub fn ssh(target: &Target, result: &SshResult, timestamps: ExportTimestamps) -> Self {
+        Self {
+            ip: target.ip.clone(),
+            name: target.name.clone(),
+            check: "ssh",
+            timestamp: timestamps.stamp(result.timestamp),
+            success: result.success,
+            latency_ms: result.connection_time_ms,
+            failure_reason: result.failure_reason.clone(),
+            warmup: false,
+            local_outage: result.local_outage,
+        }
+    }
+}
+
+// What an escalation tier's command receives on stdin
+#[derive(Debug, Serialize)]
+pub struct EscalationEvent {
+    pub ip: String,
+    pub name: Option<String>,
+    pub tier: usize,
+    pub timestamp: ExportedTimestamp,
+    pub down_secs: i64,
+    pub message: String,
+    pub reason: Option<String>,
+}
+
+impl EscalationEvent {
+    pub fn new(
+        target: &Target,
+        tier: usize,
+        down_secs: i64,
+        alert: &Alert,
+        reason: Option<String>,
+        timestamps: ExportTimestamps,
+    ) -> Self {
+        Self {
+            ip: target.ip.clone(),
+            name: target.name.clone(),
+            tier,
+            timestamp: timestamps.stamp(alert.timestamp),
+            down_secs,
+            message: alert.message.clone(),
+            reason,
+        }
+    }
+}
+
+// The user's `on_result_command`, run detached for each probe result. The command is run
+// directly rather than through a shell, and a slow or failing hook never holds up probing
+pub struct ResultHook {
+    command: Vec<String>,
+    timeout: Duration,
+    running: Arc<Semaphore>,
+    // Latest failure, cleared by the next run that succeeds
+    last_error: Arc<Mutex<Option<String>>>,
+    pub timestamps: ExportTimestamps,
+}
+
+impl ResultHook {
+    pub fn from_config(config: &Config) -> Option<Self> {
+        let command = config.on_result_command.clone().filter(|c| !c.is_empty())?;
+        Some(Self {
+            command,
+            timeout: Duration::from_millis(config.on_result_timeout_ms),
+            running: Arc::new(Semaphore::new(MAX_RUNNING_HOOKS)),
+            last_error: Arc::new(Mutex::new(None)),
+            timestamps: config.export_timestamps,
+        })
+    }
+
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.lock().unwrap().clone()
+    }
+
+    pub fn fire(&self, event: ResultEvent) {
+        spawn_hook(
+            &self.command,
+            self.timeout,
+            &self.running,
+            &self.last_error,
+            event,
+        );
+    }
+}
+
+// The `escalation` tiers' commands, run like `on_result_command` with its timeout
+pub struct EscalationHook {
+    timeout: Duration,
+    running: Arc<Semaphore>,
+    last_error: Arc<Mutex<Option<String>>>,
+    pub timestamps: ExportTimestamps,
+}
+
+impl EscalationHook {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            timeout: Duration::from_millis(config.on_result_timeout_ms),
+            running: Arc::new(Semaphore::new(MAX_RUNNING_HOOKS)),
+            last_error: Arc::new(Mutex::new(None)),
+            timestamps: config.export_timestamps,
+        }
+    }
+
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.lock().unwrap().clone()
+    }
+
+    pub fn fire(&self, command: &[String], event: EscalationEvent) {
+        if command.is_empty() {
+            return;
+        }
+        spawn_hook(
+            command,
+            self.timeout,
+            &self.running,
+            &self.last_error,
+            event,
+        );
+    }
+}
+
+fn spawn_hook(
+    command: &[String],
+    timeout: Duration,
+    running: &Arc<Semaphore>,
+    last_error: &Arc<Mutex<Option<String>>>,
+    event: impl Serialize + Send + Sync + 'static,
+) {
+    let Ok(permit) = Arc::clone(running).try_acquire_owned() else {
+        *last_error.lock().unwrap() = Some(format!(
+            "{} hooks still running, results dropped",
+            MAX_RUNNING_HOOKS
+        ));
+        return;
+    };
+
+    let command = command.to_vec();
+    let last_error = Arc::clone(last_error);
+    tokio::spawn(async move {
+        let outcome = run_hook(&command, timeout, &event).await;
+        *last_error.lock().unwrap() = outcome.err();
+        drop(permit);
+    });
+}
+
+async fn run_hook(
+    command: &[String],
+    timeout: Duration,
+    event: &impl Serialize,
+) -> Result<(), String> {
+    let mut payload = serde_json::to_vec(event).map_err(|e| e.to_string())?;
+    payload.push(b'\n');
+
+    // Dropping the child on timeout kills it
+    let mut child = Command::new(&command[0])
+        .args(&command[1..])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("{}: {}", command[0], e))?;
+
+    // A hook that ignores stdin closes the pipe early; that isn't a failure
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(&payload).await;
+    }
+
+    let output = tokio::time::timeout(timeout, child.wait_with_output())
+        .await
+        .map_err(|_| format!("{} timed out after {}ms", command[0], timeout.as_millis()))?
+        .map_err(|e| format!("{}: {}", command[0], e))?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    match stderr.lines().next().filter(|line| !line.trim().is_empty()) {
+        Some(line) => Err(format!("{} {}: {}", command[0], output.status, line.trim())),
+        None => Err(format!("{} {}", command[0], output.status)),
+    }
+}
@@ -0,0 +1,345 @@
+use crate::config::OutboundHttp;
+use crate::resolver::connect_tls;
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+// Only the status line is wanted back
+const MAX_RESPONSE: usize = 16 * 1024;
+// However many retries are configured, one push never waits longer than this between tries
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+// An http:// or https:// URL, split up for a plain HTTP/1.1 request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpUrl {
+    pub tls: bool,
+    pub host: String,
+    pub port: u16,
+    // As written, for the Host header
+    authority: String,
+    path: String,
+}
+
+impl HttpUrl {
+    pub fn parse(url: &str) -> Result<Self, String> {
+        let (tls, rest) = if let Some(rest) = url.strip_prefix("https://") {
+            (true, rest)
+        } else if let Some(rest) = url.strip_prefix("http://") {
+            (false, rest)
+        } else {
+            return Err(format!("URL must start with http:// or https://: {}", url));
+        };
+        let (authority, path) = match rest.find(['/', '?']) {
+            Some(end) if rest[end..].starts_with('?') => {
+                (&rest[..end], format!("/{}", &rest[end..]))
+            }
+            Some(end) => (&rest[..end], rest[end..].to_string()),
+            None => (rest, "/".to_string()),
+        };
+        if authority.contains('@') {
+            return Err(format!("URL credentials aren't supported: {}", url));
+        }
+
+        let default_port = if tls { 443 } else { 80 };
+        let parse_port = |port: &str| {
+            port.parse::<u16>()
+                .map_err(|_| format!("Invalid port \"{}\" in URL {}", port, url))
+        };
+        // An IPv6 address is bracketed, with or without a port after it
+        let (host, port) = if let Some(bracketed) = authority.strip_prefix('[') {
+            let Some((host, after)) = bracketed.split_once(']') else {
+                return Err(format!("Unclosed [ in URL {}", url));
+            };
+            let port = match after {
+                "" => default_port,
+                _ => match after.strip_prefix(':') {
+                    Some(port) => parse_port(port)?,
+                    None => return Err(format!("Unexpected \"{}\" in URL {}", after, url)),
+                },
+            };
+            (host, port)
+        } else {
+            match authority.split_once(':') {
+                Some((_, port)) if port.contains(':') => {
+                    return Err(format!("IPv6 addresses need [brackets] in URL {}", url));
+                }
+                Some((host, port)) => (host, parse_port(port)?),
+                None => (authority, default_port),
+            }
+        };
+        if host.is_empty() {
+            return Err(format!("URL has no host: {}", url));
+        }
+
+        Ok(Self {
+            tls,
+            host: host.to_string(),
+            port,
+            authority: authority.to_string(),
+            path,
+        })
+    }
+}
+
+// One POST, giving up after `timeout`. Any 2xx answer counts as delivered
+pub async fn post_json(url: &HttpUrl, body: &[u8], timeout: Duration) -> Result<(), String> {
+    match tokio::time::timeout(timeout, post_once(url, body)).await {
+        Ok(outcome) => outcome,
+        Err(_) => Err(format!(
+            "no answer from {} within {}ms",
+            url.authority,
+            timeout.as_millis()
+        )),
+    }
+}
+
+// Tries again `retries` times, waiting `backoff_ms` and then twice as long each time
+pub async fn post_with_retry(
+    url: &HttpUrl,
+    body: &[u8],
+    settings: &OutboundHttp,
+) -> Result<(), String> {
+    let timeout = Duration::from_millis(settings.timeout_ms);
+    let mut backoff = Duration::from_millis(settings.backoff_ms);
+    let mut attempt = 0;
+    loop {
+        match post_json(url, body, timeout).await {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt >= settings.retries => {
+                return Err(match attempt {
+                    0 => e,
+                    _ => format!("{} (after {} attempts)", e, attempt + 1),
+                });
+            }
+            Err(_) => {
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+                attempt += 1;
+            }
+        }
+    }
+}
+
+// Payloads waiting to go out, oldest first. A push that fails stays queued for the next
+// attempt instead of being lost; past `queue_size` the oldest are dropped
+pub struct Outbox {
+    url: HttpUrl,
+    settings: OutboundHttp,
+    queue: VecDeque<Vec<u8>>,
+}
+
+impl Outbox {
+    pub fn new(url: HttpUrl, settings: OutboundHttp) -> Self {
+        Self {
+            url,
+            settings,
+            queue: VecDeque::new(),
+        }
+    }
+
+    pub fn queued(&self) -> usize {
+        self.queue.len()
+    }
+
+    // Queues `body` and sends everything queued, in order, stopping at the first failure
+    pub async fn send(&mut self, body: Vec<u8>) -> Result<(), String> {
+        self.queue.push_back(body);
+        while self.queue.len() > self.settings.queue_size.max(1) {
+            self.queue.pop_front();
+        }
+        while let Some(body) = self.queue.front() {
+            post_with_retry(&self.url, body, &self.settings).await?;
+            self.queue.pop_front();
+        }
+        Ok(())
+    }
+}
+
+async fn post_once(url: &HttpUrl, body: &[u8]) -> Result<(), String> {
+    let mut request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n",
+        url.path,
+        url.authority,
+        body.len()
+    )
+    .into_bytes();
+    request.extend_from_slice(body);
+
+    if url.tls {
+        let mut stream = connect_tls(&url.host, url.port, &url.host).await?;
+        exchange(&mut stream, &request).await
+    } else {
+        let mut stream = TcpStream::connect((url.host.as_str(), url.port))
+            .await
+            .map_err(|e| format!("Failed to connect to {}: {}", url.authority, e))?;
+        exchange(&mut stream, &request).await
+    }
+}
+
+// Sends the request and checks the status line
+async fn exchange<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    request: &[u8],
+) -> Result<(), String> {
+    stream
+        .write_all(request)
+        .await
+        .map_err(|e| format!("Failed to send: {}", e))?;
+
+    let mut response = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !response.contains(&b'\n') && response.len() < MAX_RESPONSE {
+        let read = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|e| format!("Failed to read the answer: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        response.extend_from_slice(&chunk[..read]);
+    }
+
+    let response = String::from_utf8_lossy(&response);
+    let status = response
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .unwrap_or("");
+    if status.starts_with('2') {
+        Ok(())
+    } else if status.is_empty() {
+        Err("connection closed without an answer".to_string())
+    } else {
+        Err(format!("answered HTTP {}", status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use tokio::net::TcpListener;
+
+    // Answers each connection with the next status in `statuses` (the last one repeating),
+    // recording the request bodies it accepted with a 2xx
+    async fn server(statuses: &'static [u16]) -> (HttpUrl, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url =
+            HttpUrl::parse(&format!("http://{}/ingest", listener.local_addr().unwrap())).unwrap();
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+        let received = Arc::clone(&delivered);
+        let served = Arc::new(AtomicUsize::new(0));
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let status = statuses[served
+                    .fetch_add(1, Ordering::SeqCst)
+                    .min(statuses.len() - 1)];
+                let mut request = vec![0u8; 4096];
+                let read = stream.read(&mut request).await.unwrap();
+                let request = String::from_utf8_lossy(&request[..read]).into_owned();
+                if (200..300).contains(&status) {
+                    let body = request.split("\r\n\r\n").nth(1).unwrap_or("").to_string();
+                    received.lock().unwrap().push(body);
+                }
+                let answer = format!("HTTP/1.1 {} X\r\nContent-Length: 0\r\n\r\n", status);
+                let _ = stream.write_all(answer.as_bytes()).await;
+            }
+        });
+        (url, delivered)
+    }
+
+    fn settings(retries: u32, queue_size: usize) -> OutboundHttp {
+        OutboundHttp {
+            timeout_ms: 1000,
+            retries,
+            backoff_ms: 1,
+            queue_size,
+        }
+    }
+
+    #[test]
+    fn parses_hosts_ports_and_paths() {
+        let url = HttpUrl::parse("https://collector.example.net/ingest?k=1").unwrap();
+        assert!(url.tls);
+        assert_eq!(
+            (url.host.as_str(), url.port),
+            ("collector.example.net", 443)
+        );
+        assert_eq!(url.path, "/ingest?k=1");
+
+        let url = HttpUrl::parse("http://10.0.0.5:8080").unwrap();
+        assert!(!url.tls);
+        assert_eq!((url.host.as_str(), url.port), ("10.0.0.5", 8080));
+        assert_eq!(url.path, "/");
+    }
+
+    #[test]
+    fn bracketed_ipv6_takes_the_default_port_or_its_own() {
+        let url = HttpUrl::parse("https://[::1]/ingest").unwrap();
+        assert_eq!((url.host.as_str(), url.port), ("::1", 443));
+        assert_eq!(url.authority, "[::1]");
+
+        let url = HttpUrl::parse("http://[2001:db8::1]:8080/ingest").unwrap();
+        assert_eq!((url.host.as_str(), url.port), ("2001:db8::1", 8080));
+    }
+
+    #[test]
+    fn bad_urls_say_what_is_wrong_with_the_url() {
+        for (url, expected) in [
+            ("ftp://host/x", "must start with http:// or https://"),
+            ("https://host:99999/x", "Invalid port \"99999\""),
+            ("https://[::1/x", "Unclosed ["),
+            ("https://::1/x", "need [brackets]"),
+            ("https://user:pw@host/x", "credentials"),
+            ("https:///x", "no host"),
+        ] {
+            let error = HttpUrl::parse(url).unwrap_err();
+            assert!(error.contains(expected), "{}: {}", url, error);
+            assert!(!error.contains("DNS"), "{}: {}", url, error);
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_until_delivered() {
+        let (url, delivered) = server(&[503, 503, 200]).await;
+        post_with_retry(&url, b"one", &settings(2, 10))
+            .await
+            .unwrap();
+        assert_eq!(*delivered.lock().unwrap(), vec!["one"]);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_the_configured_retries() {
+        let (url, delivered) = server(&[503, 503, 200]).await;
+        let error = post_with_retry(&url, b"one", &settings(1, 10))
+            .await
+            .unwrap_err();
+        assert!(error.contains("503"), "{}", error);
+        assert!(delivered.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_pushes_stay_queued_in_order() {
+        let (url, delivered) = server(&[500, 200]).await;
+        let mut outbox = Outbox::new(url, settings(0, 10));
+        assert!(outbox.send(b"first".to_vec()).await.is_err());
+        assert_eq!(outbox.queued(), 1);
+        outbox.send(b"second".to_vec()).await.unwrap();
+        assert_eq!(outbox.queued(), 0);
+        assert_eq!(*delivered.lock().unwrap(), vec!["first", "second"]);
+    }
+
+    #[tokio::test]
+    async fn a_full_queue_drops_the_oldest() {
+        let (url, delivered) = server(&[500, 500, 200]).await;
+        let mut outbox = Outbox::new(url, settings(0, 2));
+        assert!(outbox.send(b"a".to_vec()).await.is_err());
+        assert!(outbox.send(b"b".to_vec()).await.is_err());
+        outbox.send(b"c".to_vec()).await.unwrap();
+        assert_eq!(*delivered.lock().unwrap(), vec!["b", "c"]);
+    }
+}
@@ -0,0 +1,345 @@
+use crate::config::{ChartStyles, ExportTimestamps, LatencyUnit};
+use crate::monitor::{MonitorCommand, MonitorStatus, TargetStats};
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{Mutex, mpsc};
+
+// How often an attached client is sent fresh state; well under the usual ping interval
+const PUSH_INTERVAL: Duration = Duration::from_millis(500);
+const DAEMON_START_TIMEOUT: Duration = Duration::from_secs(5);
+
+// The daemon writes one of these per line to each client; clients write one MonitorCommand
+// per line back
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub targets: Vec<TargetStats>,
+    pub status: MonitorStatus,
+    pub chart_styles: ChartStyles,
+    #[serde(default)]
+    pub latency_unit: LatencyUnit,
+    #[serde(default)]
+    pub export_timestamps: ExportTimestamps,
+}
+
+// Under /var/run so a daemon started with sudo can be found whatever HOME the client has
+pub fn default_socket_path(profile: Option<&str>) -> PathBuf {
+    let name = match profile {
+        Some(profile) => format!("boxmonitor-{}.sock", profile),
+        None => "boxmonitor.sock".to_string(),
+    };
+    Path::new("/var/run").join(name)
+}
+
+pub fn daemon_log_path() -> Result<PathBuf> {
+    Ok(crate::config::get_config_dir()?.join("daemon.log"))
+}
+
+// Re-runs this command line with --foreground in a new session, detached from the terminal,
+// and returns once the child is accepting connections
+pub fn spawn_daemon(socket: &Path) -> Result<()> {
+    use std::os::unix::process::CommandExt;
+    use std::process::Stdio;
+
+    if std::os::unix::net::UnixStream::connect(socket).is_ok() {
+        return Err(color_eyre::eyre::eyre!(
+            "A daemon is already listening on {}",
+            socket.display()
+        ));
+    }
+
+    let log_path = daemon_log_path()?;
+    if let Some(dir) = log_path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let log = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)?;
+
+    let mut command = std::process::Command::new(std::env::current_exe()?);
+    command
+        .args(std::env::args_os().skip(1))
+        .arg("--foreground")
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log);
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    let mut child = command.spawn()?;
+
+    let deadline = std::time::Instant::now() + DAEMON_START_TIMEOUT;
+    while std::time::Instant::now() < deadline {
+        if let Some(status) = child.try_wait()? {
+            return Err(color_eyre::eyre::eyre!(
+                "Daemon exited during startup ({}); see {}",
+                status,
+                log_path.display()
+            ));
+        }
+        if std::os::unix::net::UnixStream::connect(socket).is_ok() {
+            println!(
+                "boxmonitor daemon started (pid {}), listening on {}",
+                child.id(),
+                socket.display()
+            );
+            println!("Attach with: boxmonitor attach");
+            println!("Log: {}", log_path.display());
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    println!(
+        "boxmonitor daemon started (pid {}) but isn't listening on {} yet; see {}",
+        child.id(),
+        socket.display(),
+        log_path.display()
+    );
+    Ok(())
+}
+
+// Serves the shared state until SIGTERM or Ctrl+C, then removes the socket
+pub async fn serve(
+    socket: &Path,
+    targets: Arc<Mutex<Vec<TargetStats>>>,
+    status: Arc<Mutex<MonitorStatus>>,
+    chart_styles: ChartStyles,
+    latency_unit: LatencyUnit,
+    export_timestamps: ExportTimestamps,
+    commands: mpsc::UnboundedSender<MonitorCommand>,
+) -> Result<()> {
+    let listener = bind(socket)?;
+    eprintln!("Listening on {}", socket.display());
+
+    let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    tokio::spawn(serve_client(
+                        stream,
+                        Arc::clone(&targets),
+                        Arc::clone(&status),
+                        chart_styles,
+                        latency_unit,
+                        export_timestamps,
+                        commands.clone(),
+                    ));
+                }
+                Err(e) => eprintln!("Attach connection failed: {}", e),
+            },
+            _ = terminate.recv() => break,
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    let _ = std::fs::remove_file(socket);
+    Ok(())
+}
+
+// A socket whose daemon died is left behind; only a live one blocks a new daemon
+fn bind(socket: &Path) -> Result<UnixListener> {
+    use std::os::unix::fs::PermissionsExt;
+
+    if socket.exists() {
+        if std::os::unix::net::UnixStream::connect(socket).is_ok() {
+            return Err(color_eyre::eyre::eyre!(
+                "A daemon is already listening on {}",
+                socket.display()
+            ));
+        }
+        std::fs::remove_file(socket)?;
+    }
+
+    let listener = UnixListener::bind(socket)
+        .map_err(|e| color_eyre::eyre::eyre!("Could not listen on {}: {}", socket.display(), e))?;
+
+    // Clients can send commands, so only the owner may connect: the sudo user when started
+    // through sudo, otherwise root
+    std::fs::set_permissions(socket, std::fs::Permissions::from_mode(0o600))?;
+    if let (Some(uid), Some(gid)) = (sudo_id("SUDO_UID"), sudo_id("SUDO_GID")) {
+        std::os::unix::fs::chown(socket, Some(uid), Some(gid))?;
+    }
+    Ok(listener)
+}
+
+fn sudo_id(var: &str) -> Option<u32> {
+    std::env::var(var).ok()?.parse().ok()
+}
+
+async fn serve_client(
+    stream: UnixStream,
+    targets: Arc<Mutex<Vec<TargetStats>>>,
+    status: Arc<Mutex<MonitorStatus>>,
+    chart_styles: ChartStyles,
+    latency_unit: LatencyUnit,
+    export_timestamps: ExportTimestamps,
+    commands: mpsc::UnboundedSender<MonitorCommand>,
+) {
+    let (reader, mut writer) = stream.into_split();
+
+    let command_reader = tokio::spawn(async move {
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            match serde_json::from_str::<MonitorCommand>(&line) {
+                Ok(command) => {
+                    if commands.send(command).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => eprintln!("Ignoring malformed command from client: {}", e),
+            }
+        }
+    });
+
+    let mut interval = tokio::time::interval(PUSH_INTERVAL);
+    loop {
+        interval.tick().await;
+        let snapshot = Snapshot {
+            targets: targets.lock().await.clone(),
+            status: status.lock().await.clone(),
+            chart_styles,
+            latency_unit,
+            export_timestamps,
+        };
+        let Ok(mut line) = serde_json::to_vec(&snapshot) else {
+            break;
+        };
+        line.push(b'\n');
+        // The client went away
+        if writer.write_all(&line).await.is_err() {
+            break;
+        }
+    }
+
+    command_reader.abort();
+}
+
+// Shared state mirrored from a daemon, in the same shape the frontends use locally. Once the
+// daemon goes away the state stops updating and the frontends report monitoring as stalled
+pub struct Attached {
+    pub targets: Arc<Mutex<Vec<TargetStats>>>,
+    pub status: Arc<Mutex<MonitorStatus>>,
+    pub chart_styles: ChartStyles,
+    pub latency_unit: LatencyUnit,
+    pub export_timestamps: ExportTimestamps,
+    pub commands: mpsc::UnboundedSender<MonitorCommand>,
+}
+
+type SnapshotLines = tokio::io::Lines<BufReader<tokio::net::unix::OwnedReadHalf>>;
+
+// Connects and waits for the daemon's first snapshot
+async fn connect(
+    socket: &Path,
+) -> Result<(SnapshotLines, tokio::net::unix::OwnedWriteHalf, Snapshot)> {
+    let stream = UnixStream::connect(socket).await.map_err(|e| {
+        color_eyre::eyre::eyre!(
+            "Could not connect to {}: {} (is `boxmonitor daemon` running?)",
+            socket.display(),
+            e
+        )
+    })?;
+    let (reader, writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    let first = lines
+        .next_line()
+        .await?
+        .ok_or_else(|| color_eyre::eyre::eyre!("Daemon closed the connection"))?;
+    let snapshot: Snapshot = serde_json::from_str(&first)?;
+    Ok((lines, writer, snapshot))
+}
+
+// The daemon's current state, once
+pub async fn fetch_snapshot(socket: &Path) -> Result<Snapshot> {
+    let (_, _, snapshot) = connect(socket).await?;
+    Ok(snapshot)
+}
+
+// One target's full current state, found by name or address; a name that isn't an exact match
+// may still match ignoring case
+pub async fn query_target(socket: &Path, query: &str) -> Result<TargetStats> {
+    let snapshot = fetch_snapshot(socket).await?;
+    let name = |t: &TargetStats| t.target.name.clone().unwrap_or_else(|| t.target.ip.clone());
+    let found = snapshot
+        .targets
+        .iter()
+        .position(|t| t.target.ip == query || t.target.name.as_deref() == Some(query))
+        .or_else(|| {
+            snapshot
+                .targets
+                .iter()
+                .position(|t| name(t).eq_ignore_ascii_case(query))
+        });
+    match found {
+        Some(index) => Ok(snapshot.targets.into_iter().nth(index).unwrap()),
+        None => Err(color_eyre::eyre::eyre!(
+            "No target named {}; the daemon monitors: {}",
+            query,
+            snapshot
+                .targets
+                .iter()
+                .map(name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+pub async fn attach(socket: &Path) -> Result<Attached> {
+    let (mut lines, mut writer, snapshot) = connect(socket).await?;
+
+    let targets = Arc::new(Mutex::new(snapshot.targets));
+    let status = Arc::new(Mutex::new(snapshot.status));
+
+    let shared_targets = Arc::clone(&targets);
+    let shared_status = Arc::clone(&status);
+    tokio::spawn(async move {
+        while let Ok(Some(line)) = lines.next_line().await {
+            let Ok(snapshot) = serde_json::from_str::<Snapshot>(&line) else {
+                continue;
+            };
+            *shared_targets.lock().await = snapshot.targets;
+            let mut status = shared_status.lock().await;
+            // A warning dismissed here stays dismissed
+            let dismissed = status.config_warning.is_none();
+            *status = snapshot.status;
+            if dismissed {
+                status.config_warning = None;
+            }
+        }
+    });
+
+    let (commands, mut command_rx) = mpsc::unbounded_channel::<MonitorCommand>();
+    tokio::spawn(async move {
+        while let Some(command) = command_rx.recv().await {
+            let Ok(mut line) = serde_json::to_vec(&command) else {
+                continue;
+            };
+            line.push(b'\n');
+            if writer.write_all(&line).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok(Attached {
+        targets,
+        status,
+        chart_styles: snapshot.chart_styles,
+        latency_unit: snapshot.latency_unit,
+        export_timestamps: snapshot.export_timestamps,
+        commands,
+    })
+}
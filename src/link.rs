@@ -0,0 +1,85 @@
+// Whether this machine itself is on a network, so that a local outage (laptop asleep, cable
+// out, VPN dropped) isn't blamed on every target at once. Only Linux is checked; elsewhere
+// the local network is always assumed available
+#[derive(Debug, Default)]
+pub struct LinkMonitor {
+    // A host that never had a default route (LAN-only) isn't down for lacking one
+    seen_default_route: bool,
+}
+
+impl LinkMonitor {
+    // Why the local network is unavailable, or None when it looks usable
+    #[cfg(target_os = "linux")]
+    pub fn check(&mut self) -> Option<String> {
+        if no_interface_up() {
+            return Some("no network interface is up".to_string());
+        }
+
+        match has_default_route() {
+            Some(true) => {
+                self.seen_default_route = true;
+                None
+            }
+            Some(false) if self.seen_default_route => Some("default route lost".to_string()),
+            _ => None,
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn check(&mut self) -> Option<String> {
+        None
+    }
+}
+
+// Tunnel devices report "unknown" while passing traffic, so only "down" and friends count
+#[cfg(target_os = "linux")]
+fn no_interface_up() -> bool {
+    let Ok(entries) = std::fs::read_dir("/sys/class/net") else {
+        return false;
+    };
+
+    let mut interfaces = 0;
+    for entry in entries.flatten() {
+        if entry.file_name() == "lo" {
+            continue;
+        }
+        interfaces += 1;
+        let state = std::fs::read_to_string(entry.path().join("operstate")).unwrap_or_default();
+        if matches!(state.trim(), "up" | "unknown") {
+            return false;
+        }
+    }
+    // Nothing but loopback is nothing to judge by
+    interfaces > 0
+}
+
+// Any IPv4 or IPv6 default route, with or without a gateway; None if neither table is readable
+#[cfg(target_os = "linux")]
+fn has_default_route() -> Option<bool> {
+    let v4 = std::fs::read_to_string("/proc/net/route")
+        .ok()
+        .map(|content| {
+            content.lines().skip(1).any(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                fields.len() >= 8 && fields[1] == "00000000" && fields[7] == "00000000"
+            })
+        });
+    // Destination and prefix length come first; the loopback and unreachable entries the kernel
+    // keeps for ::/0 name the lo device
+    let v6 = std::fs::read_to_string("/proc/net/ipv6_route")
+        .ok()
+        .map(|content| {
+            content.lines().any(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                fields.len() >= 10
+                    && fields[0].bytes().all(|b| b == b'0')
+                    && fields[1] == "00"
+                    && fields[9] != "lo"
+            })
+        });
+
+    match (v4, v6) {
+        (None, None) => None,
+        (v4, v6) => Some(v4.unwrap_or(false) || v6.unwrap_or(false)),
+    }
+}
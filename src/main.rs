@@ -1,25 +1,92 @@
+mod accessible;
+mod alerts;
+mod clock;
+mod collector;
 mod config;
+mod dns;
+mod duplicates;
+mod events;
+mod export;
+mod healthz;
+mod hooks;
+mod http;
+mod ipc;
+mod link;
 mod monitor;
+mod multicast;
+mod neighbor;
+mod netns;
+mod replay;
+mod report;
+mod resolver;
+mod snapshot;
+mod systemd;
+mod theme;
+mod throughput;
 mod ui;
+mod ui_diagnostics;
+mod ui_editor;
+mod ui_events;
 mod ui_failure_charts;
+mod ui_gauges;
+mod ui_histogram;
+mod ui_popup;
+mod ui_relative;
+mod ui_samples_table;
+mod ui_sampling;
+mod ui_throughput;
+mod units;
+mod watch;
+mod zone;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use color_eyre::Result;
-use config::{load_config, load_targets_from_simple_list, parse_targets_from_args};
-use monitor::Monitor;
+use config::{
+    load_config, load_targets_from_hosts_file, load_targets_from_simple_list,
+    parse_targets_from_args,
+};
+use monitor::{Monitor, MonitorCommand, MonitorStatus, TargetStats};
+use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use theme::{Palette, Theme};
+use tokio::sync::{Mutex, mpsc};
+
+// Wait before restarting a panicked monitor loop, doubling for each panic in quick succession
+const RESTART_BACKOFF_MIN: std::time::Duration = std::time::Duration::from_secs(1);
+const RESTART_BACKOFF_MAX: std::time::Duration = std::time::Duration::from_secs(60);
 
 #[derive(Parser)]
 #[command(name = "boxmonitor")]
 #[command(about = "A network monitoring tool with TUI interface")]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     #[arg(short, long, help = "Use simple IP list format instead of JSON config")]
     simple: bool,
 
-    #[arg(short, long, help = "Show configuration and exit")]
+    #[arg(
+        short,
+        long,
+        visible_alias = "show-config",
+        help = "Show configuration and exit"
+    )]
     config: bool,
 
+    #[arg(
+        long = "config-file",
+        value_name = "FILE",
+        help = "JSON config file to load; repeat to layer files, later ones winning"
+    )]
+    config_files: Vec<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Monitor the addresses in an /etc/hosts-style file, named by their hostnames"
+    )]
+    hosts_file: Option<PathBuf>,
+
     #[arg(long, help = "Comma-separated list of IP addresses to monitor")]
     ip: Option<String>,
 
@@ -28,12 +95,246 @@ struct Args {
         help = "Comma-separated list of SSH targets in USER@ip[:port] format"
     )]
     ssh: Option<String>,
+
+    #[arg(long, help = "Default SSH user for --ssh targets given without USER@")]
+    ssh_user: Option<String>,
+
+    #[arg(
+        long,
+        help = "Prepend the default gateway(s) from the routing table as targets"
+    )]
+    add_gateway: bool,
+
+    #[arg(
+        long,
+        help = "Named config profile to load from ~/.config/box/<PROFILE>.iplist"
+    )]
+    profile: Option<String>,
+
+    #[arg(
+        long,
+        help = "Print plain-text status updates instead of the TUI (screen reader friendly)"
+    )]
+    accessible: bool,
+
+    #[arg(
+        long,
+        help = "Disable colors (also honored via the NO_COLOR environment variable)"
+    )]
+    no_color: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Palette::Default,
+        help = "Chart series colors; the color-blind palettes also vary marker shapes"
+    )]
+    palette: Palette,
+
+    #[arg(
+        long,
+        help = "Run without the TUI and print each failure to stdout as a tab-separated line"
+    )]
+    watch_failures: bool,
+
+    #[arg(
+        long,
+        help = "Draw the TUI inline in the normal screen instead of the alternate screen"
+    )]
+    inline: bool,
+
+    #[arg(
+        long,
+        value_name = "URL",
+        help = "POST each target's statistics to this collector every minute"
+    )]
+    collector_url: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Compare two saved statistics snapshots (press 'S' in the TUI to save one)")]
+    Diff {
+        #[arg(help = "Baseline snapshot")]
+        before: PathBuf,
+
+        #[arg(help = "Snapshot to compare against the baseline")]
+        after: PathBuf,
+
+        #[arg(long, help = "Print the comparison as JSON")]
+        json: bool,
+    },
+    #[command(about = "Monitor in the background, serving live state to `boxmonitor attach`")]
+    Daemon {
+        #[arg(
+            long,
+            help = "Stay in the foreground instead of detaching, e.g. under a service manager"
+        )]
+        foreground: bool,
+
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Socket to listen on [default: /var/run/boxmonitor.sock, or boxmonitor-PROFILE.sock with --profile]"
+        )]
+        socket: Option<PathBuf>,
+    },
+    #[command(about = "View a running daemon's live state in the TUI, or with --accessible")]
+    Attach {
+        #[arg(long, value_name = "PATH", help = "Daemon socket to connect to")]
+        socket: Option<PathBuf>,
+    },
+    #[command(about = "Print an incident report from a running daemon's state")]
+    Report {
+        #[arg(long, value_enum, default_value_t = report::ReportFormat::Md)]
+        format: report::ReportFormat,
+
+        #[arg(
+            long,
+            value_parser = report::parse_window,
+            help = "Only cover the last DURATION, e.g. 15m or 2h [default: the whole session]"
+        )]
+        window: Option<chrono::Duration>,
+
+        #[arg(long, value_name = "PATH", help = "Daemon socket to read from")]
+        socket: Option<PathBuf>,
+    },
+    #[command(about = "Play back results recorded by an on_result_command in the TUI")]
+    Replay {
+        #[arg(help = "JSONL file of results, one on_result_command input per line")]
+        log: PathBuf,
+
+        #[arg(
+            long,
+            default_value_t = 1.0,
+            help = "Playback speed, e.g. 10 for ten times as fast"
+        )]
+        speed: f64,
+    },
+    #[command(about = "Print one target's full current state from a running daemon as JSON")]
+    Query {
+        #[arg(help = "Target name or address")]
+        target: String,
+
+        #[arg(long, value_name = "PATH", help = "Daemon socket to read from")]
+        socket: Option<PathBuf>,
+    },
+}
+
+// How the monitor's state is shown, whether it is collected in-process or by a daemon
+#[derive(Clone, Copy)]
+enum Frontend {
+    WatchFailures,
+    Accessible,
+    Tui { inline: bool },
+}
+
+impl Frontend {
+    fn from_args(args: &Args) -> Self {
+        if args.watch_failures {
+            Frontend::WatchFailures
+        } else if args.accessible {
+            Frontend::Accessible
+        } else {
+            Frontend::Tui {
+                inline: args.inline,
+            }
+        }
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     color_eyre::install()?;
 
+    let args = Args::parse();
+
+    // Offline tools work on saved files and don't need root
+    if let Some(Command::Diff {
+        before,
+        after,
+        json,
+    }) = &args.command
+    {
+        return snapshot::run_diff(before, after, *json);
+    }
+
+    if let Some(Command::Report {
+        format,
+        window,
+        socket,
+    }) = &args.command
+    {
+        let socket = socket
+            .clone()
+            .unwrap_or_else(|| ipc::default_socket_path(args.profile.as_deref()));
+        let snapshot = ipc::fetch_snapshot(&socket).await?;
+        match format {
+            report::ReportFormat::Md => {
+                print!(
+                    "{}",
+                    report::render_markdown(&snapshot.targets, *window, snapshot.latency_unit)
+                )
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(Command::Query { target, socket }) = &args.command {
+        let socket = socket
+            .clone()
+            .unwrap_or_else(|| ipc::default_socket_path(args.profile.as_deref()));
+        let target = ipc::query_target(&socket, target).await?;
+        println!("{}", serde_json::to_string_pretty(&target)?);
+        return Ok(());
+    }
+
+    let frontend = Frontend::from_args(&args);
+
+    // Attaching only needs access to the daemon's socket
+    if let Some(Command::Attach { socket }) = &args.command {
+        let socket = socket
+            .clone()
+            .unwrap_or_else(|| ipc::default_socket_path(args.profile.as_deref()));
+        let attached = ipc::attach(&socket).await?;
+        let theme = Theme::from_env(args.no_color)
+            .with_chart_styles(attached.chart_styles)
+            .with_latency_unit(attached.latency_unit)
+            .with_palette(args.palette);
+        return run_frontend(
+            frontend,
+            attached.targets,
+            attached.status,
+            attached.commands,
+            theme,
+            attached.export_timestamps,
+        )
+        .await;
+    }
+
+    // Replays only read a recording, so don't need root either
+    if let Some(Command::Replay { log, speed }) = &args.command {
+        let config = if args.config_files.is_empty() {
+            load_config(args.profile.as_deref())?
+        } else {
+            config::load_config_files(&args.config_files)?
+        };
+        let replay = replay::start(log, *speed, &config)?;
+        let theme = Theme::from_env(args.no_color)
+            .with_chart_styles(config.chart_styles)
+            .with_latency_unit(config.latency_unit)
+            .with_palette(args.palette);
+        return run_frontend(
+            frontend,
+            replay.targets,
+            replay.status,
+            replay.commands,
+            theme,
+            config.export_timestamps,
+        )
+        .await;
+    }
+
     // Check if running as root (required for ICMP ping)
     if !is_root() {
         eprintln!("Error: This program requires root privileges to send ICMP ping packets.");
@@ -41,75 +342,158 @@ async fn main() -> Result<()> {
         std::process::exit(1);
     }
 
-    let args = Args::parse();
+    let profile = args.profile.as_deref();
 
     if args.config {
-        show_config().await?;
+        show_config(profile, &args.config_files).await?;
         return Ok(());
     }
 
-    let config = if args.ip.is_some() || args.ssh.is_some() {
-        let targets = parse_targets_from_args(args.ip, args.ssh)?;
+    let daemon_socket = match &args.command {
+        Some(Command::Daemon { foreground, socket }) => {
+            let socket = socket
+                .clone()
+                .unwrap_or_else(|| ipc::default_socket_path(profile));
+            if !foreground {
+                return ipc::spawn_daemon(&socket);
+            }
+            Some(socket)
+        }
+        _ => None,
+    };
+
+    let mut config_warning = None;
+    let mut config = if args.ip.is_some() || args.ssh.is_some() {
+        let targets = parse_targets_from_args(args.ip, args.ssh, args.ssh_user.as_deref())?;
+        config::Config {
+            targets,
+            ..Default::default()
+        }
+    } else if let Some(hosts_file) = &args.hosts_file {
+        let (targets, warning) = load_targets_from_hosts_file(hosts_file)?;
+        config_warning = warning;
         config::Config {
             targets,
             ..Default::default()
         }
     } else if args.simple {
-        let targets = load_targets_from_simple_list()?;
+        let targets = load_targets_from_simple_list(profile)?;
         config::Config {
             targets,
             ..Default::default()
         }
+    } else if !args.config_files.is_empty() {
+        config::load_config_files(&args.config_files)?
     } else {
-        load_config()?
+        let (config, warning) = config::load_config_or_recover(profile)?;
+        config_warning = warning;
+        config
     };
 
+    let (targets, range_warnings) = config::expand_targets(config.targets)?;
+    config.targets = targets;
+
+    if args.add_gateway || config.add_gateway {
+        let gateways = config::detect_default_gateways()?;
+        if gateways.is_empty() {
+            eprintln!("No default gateway found in the routing table");
+        }
+        // Gateways go first, skipping any that are already configured
+        let mut targets: Vec<_> = gateways
+            .into_iter()
+            .filter(|gateway| !config.targets.iter().any(|t| t.ip == gateway.ip))
+            .collect();
+        targets.append(&mut config.targets);
+        config.targets = targets;
+    }
+
+    config::sort_by_priority(&mut config.targets);
+
     if config.targets.is_empty() {
-        eprintln!("No targets configured. Please add IPs to ~/.config/box/.iplist");
+        eprintln!(
+            "No targets configured. Please add IPs to {}",
+            config::get_config_file(profile)?.display()
+        );
         return Ok(());
     }
 
-    let mut monitor = Monitor::new(
-        config.targets.clone(),
-        config.ping_interval_ms,
-        config.ssh_timeout_ms,
-        config.history_size,
-    );
+    let healthz = match &config.healthz_listen {
+        Some(addr) => Some(healthz::bind(addr).await?),
+        None => None,
+    };
+
+    let monitor = Monitor::new(&config, profile);
 
     let targets = Arc::new(Mutex::new(monitor.get_targets().to_vec()));
     let targets_clone = Arc::clone(&targets);
+    // Shown together in the one warning banner
+    let warnings: Vec<String> = config_warning
+        .into_iter()
+        .chain(range_warnings)
+        .chain(monitor.warnings().iter().cloned())
+        .collect();
+    let mut initial_status = MonitorStatus::new(&config);
+    initial_status.config_warning = (!warnings.is_empty()).then(|| warnings.join("; "));
+    let status = Arc::new(Mutex::new(initial_status));
+    let status_clone = Arc::clone(&status);
+    let (command_tx, command_rx) = mpsc::unbounded_channel();
 
-    let monitoring_task = tokio::spawn(async move {
-        let mut interval =
-            tokio::time::interval(std::time::Duration::from_millis(config.ping_interval_ms));
-        let mut ssh_interval = tokio::time::interval(std::time::Duration::from_millis(
-            config.ping_interval_ms * 5,
-        ));
-
-        loop {
-            tokio::select! {
-                _ = interval.tick() => {
-                    if let Err(e) = monitor.run_ping_cycle().await {
-                        eprintln!("Ping cycle error: {}", e);
-                    }
+    let monitor = Arc::new(Mutex::new(monitor));
+    let command_rx = Arc::new(Mutex::new(command_rx));
+    let monitoring_task = tokio::spawn(supervise_monitoring(
+        monitor,
+        targets_clone,
+        status_clone,
+        command_rx,
+        config.ping_interval_ms,
+        config.restart_on_panic,
+    ));
 
-                    let mut targets_guard = targets_clone.lock().await;
-                    *targets_guard = monitor.get_targets().to_vec();
-                }
-                _ = ssh_interval.tick() => {
-                    if let Err(e) = monitor.run_ssh_cycle().await {
-                        eprintln!("SSH cycle error: {}", e);
-                    }
+    tokio::spawn(systemd::run_watchdog(Arc::clone(&status)));
+    if let Some(listener) = healthz {
+        tokio::spawn(healthz::serve(listener, Arc::clone(&status)));
+    }
+    if let Some(collector) = collector::Collector::from_config(&config, args.collector_url.clone())
+    {
+        tokio::spawn(collector.run(Arc::clone(&targets), Arc::clone(&status)));
+    }
 
-                    let mut targets_guard = targets_clone.lock().await;
-                    *targets_guard = monitor.get_targets().to_vec();
-                }
+    let chart_styles = config.chart_styles;
+    let latency_unit = config.latency_unit;
+    let export_timestamps = config.export_timestamps;
+    let theme = Theme::from_env(args.no_color)
+        .with_chart_styles(chart_styles)
+        .with_latency_unit(latency_unit)
+        .with_palette(args.palette);
+    let ui_task = tokio::spawn(async move {
+        // A daemon's only frontend is the socket that attached clients read from
+        let result = match daemon_socket {
+            Some(socket) => {
+                ipc::serve(
+                    &socket,
+                    targets,
+                    status,
+                    chart_styles,
+                    latency_unit,
+                    export_timestamps,
+                    command_tx,
+                )
+                .await
             }
-        }
-    });
+            None => {
+                run_frontend(
+                    frontend,
+                    targets,
+                    status,
+                    command_tx,
+                    theme,
+                    export_timestamps,
+                )
+                .await
+            }
+        };
 
-    let ui_task = tokio::spawn(async move {
-        if let Err(e) = ui::run_ui(targets).await {
+        if let Err(e) = result {
             eprintln!("UI error: {}", e);
         }
     });
@@ -122,19 +506,222 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+async fn run_frontend(
+    frontend: Frontend,
+    targets: Arc<Mutex<Vec<TargetStats>>>,
+    status: Arc<Mutex<MonitorStatus>>,
+    commands: mpsc::UnboundedSender<MonitorCommand>,
+    theme: Theme,
+    export_timestamps: config::ExportTimestamps,
+) -> Result<()> {
+    match frontend {
+        Frontend::WatchFailures => watch::run_watch_failures(targets).await,
+        Frontend::Accessible => accessible::run_accessible(targets, status).await,
+        Frontend::Tui { inline } => {
+            ui::run_ui(targets, status, commands, theme, export_timestamps, inline).await
+        }
+    }
+}
+
 fn is_root() -> bool {
     unsafe { libc::geteuid() == 0 }
 }
 
-async fn show_config() -> Result<()> {
-    let config = load_config()?;
+type SharedCommands = Arc<Mutex<mpsc::UnboundedReceiver<MonitorCommand>>>;
+
+// Runs the monitor loop and, with restart_on_panic, starts it again after a panic. The Monitor
+// and the command channel outlive each run, so history, stats and queued commands carry over
+async fn supervise_monitoring(
+    monitor: Arc<Mutex<Monitor>>,
+    targets: Arc<Mutex<Vec<TargetStats>>>,
+    status: Arc<Mutex<MonitorStatus>>,
+    commands: SharedCommands,
+    ping_interval_ms: u64,
+    restart_on_panic: bool,
+) {
+    let mut backoff = RESTART_BACKOFF_MIN;
+    let mut first_run = true;
+    loop {
+        let started = std::time::Instant::now();
+        let run = tokio::spawn(run_monitoring(
+            Arc::clone(&monitor),
+            Arc::clone(&targets),
+            Arc::clone(&status),
+            Arc::clone(&commands),
+            ping_interval_ms,
+            first_run,
+        ));
+        let Err(error) = run.await else {
+            return;
+        };
+        if !error.is_panic() || !restart_on_panic {
+            return;
+        }
+
+        // A run that lasted a while was a one-off, not a crash loop
+        if started.elapsed() > RESTART_BACKOFF_MAX {
+            backoff = RESTART_BACKOFF_MIN;
+        }
+        let message = format!(
+            "monitoring panicked ({}), restarting in {}s",
+            panic_message(error.into_panic()),
+            backoff.as_secs()
+        );
+        let mut monitor = monitor.lock().await;
+        monitor.note_restart(message);
+        status.lock().await.events = monitor.events().clone();
+        drop(monitor);
+
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(RESTART_BACKOFF_MAX);
+        first_run = false;
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload
+            .downcast_ref::<&str>()
+            .map_or("unknown cause".to_string(), |message| message.to_string()),
+    }
+}
+
+async fn run_monitoring(
+    monitor: Arc<Mutex<Monitor>>,
+    targets: Arc<Mutex<Vec<TargetStats>>>,
+    status: Arc<Mutex<MonitorStatus>>,
+    commands: SharedCommands,
+    ping_interval_ms: u64,
+    first_run: bool,
+) {
+    // Both stay locked for the whole run; a panic releases them for the next one
+    let mut monitor = monitor.lock().await;
+    let mut command_rx = commands.lock().await;
+    let mut interval = tokio::time::interval(std::time::Duration::from_millis(ping_interval_ms));
+    let mut ssh_interval =
+        tokio::time::interval(std::time::Duration::from_millis(ping_interval_ms * 5));
+
+    // The first tick is immediate; the service counts as ready once its results are in
+    interval.tick().await;
+    ping_cycle(&mut monitor, &targets, &status, None).await;
+    if first_run {
+        systemd::notify_ready();
+    }
+    // SSH too goes right away, so its panels fill without waiting on the loop
+    ssh_interval.tick().await;
+    ssh_cycle(&mut monitor, &targets, &status).await;
+
+    loop {
+        tokio::select! {
+            scheduled = interval.tick() => {
+                let lag = scheduled.elapsed();
+                ping_cycle(&mut monitor, &targets, &status, Some(lag)).await;
+            }
+            _ = ssh_interval.tick() => {
+                ssh_cycle(&mut monitor, &targets, &status).await;
+            }
+            Some(command) = command_rx.recv() => {
+                if monitor.apply_command(command) {
+                    ping_cycle(&mut monitor, &targets, &status, None).await;
+                    ssh_cycle(&mut monitor, &targets, &status).await;
+                    // The out-of-band cycle stands in for the next scheduled one, and probe
+                    // requests queued while it ran are already answered by it
+                    interval.reset();
+                    ssh_interval.reset();
+                    while let Ok(queued) = command_rx.try_recv() {
+                        monitor.apply_command(queued);
+                    }
+                }
+                publish(&mut monitor, &targets, &status).await;
+            }
+        }
+    }
+}
+
+async fn ping_cycle(
+    monitor: &mut Monitor,
+    targets: &Mutex<Vec<TargetStats>>,
+    status: &Mutex<MonitorStatus>,
+    lag: Option<std::time::Duration>,
+) {
+    let started = std::time::Instant::now();
+    let result = monitor.run_ping_cycle().await;
+    let took = started.elapsed();
+    if let Err(e) = &result {
+        eprintln!("Ping cycle error: {}", e);
+    }
+
+    publish(monitor, targets, status).await;
+    let mut status = status.lock().await;
+    status.record_cycle();
+    let diagnostics = &mut status.diagnostics;
+    diagnostics.record_ping_cycle(took, lag);
+    diagnostics.in_flight_probes = monitor.in_flight_probes();
+    diagnostics.history_bytes = monitor.history_bytes();
+    if let Err(e) = result {
+        diagnostics.record_error(format!("ping cycle: {}", e));
+    }
+}
+
+// Shares the current targets and re-runs alerting, so the frontends see changes at once
+async fn publish(
+    monitor: &mut Monitor,
+    targets: &Mutex<Vec<TargetStats>>,
+    status: &Mutex<MonitorStatus>,
+) {
+    let alert_update = monitor.evaluate_alerts();
+
+    *targets.lock().await = monitor.get_targets().to_vec();
+    let mut status = status.lock().await;
+    status.record_alerts(alert_update);
+    status.hook_error = monitor.hook_error();
+    status.local_outage = monitor.local_outage().cloned();
+    status.events = monitor.events().clone();
+}
+
+async fn ssh_cycle(
+    monitor: &mut Monitor,
+    targets: &Mutex<Vec<TargetStats>>,
+    status: &Mutex<MonitorStatus>,
+) {
+    let started = std::time::Instant::now();
+    let result = monitor.run_ssh_cycle().await;
+    let took = started.elapsed();
+    if let Err(e) = &result {
+        eprintln!("SSH cycle error: {}", e);
+    }
+
+    publish(monitor, targets, status).await;
+    let mut status = status.lock().await;
+    status.record_cycle();
+    status.diagnostics.record_ssh_cycle(took);
+    status.diagnostics.in_flight_probes = monitor.in_flight_probes();
+    if let Err(e) = result {
+        status.diagnostics.record_error(format!("SSH cycle: {}", e));
+    }
+}
+
+async fn show_config(profile: Option<&str>, config_files: &[PathBuf]) -> Result<()> {
+    if !config_files.is_empty() {
+        let config = config::load_config_files(config_files)?;
+        println!("Merged configuration:");
+        println!("{}", serde_json::to_string_pretty(&config)?);
+
+        println!("\nMerged from, in order:");
+        for path in config_files {
+            println!("  {}", path.display());
+        }
+        return Ok(());
+    }
+
+    let config = load_config(profile)?;
     println!("Current configuration:");
     println!("{}", serde_json::to_string_pretty(&config)?);
 
-    let config_dir = config::get_config_dir()?;
     println!(
         "\nConfig file location: {}",
-        config_dir.join(".iplist").display()
+        config::get_config_file(profile)?.display()
     );
 
     Ok(())
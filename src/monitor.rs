@@ -1,9 +1,28 @@
-use crate::config::Target;
+use crate::alerts::{
+    Acknowledgement, Alert, AlertEngine, AlertKind, AlertSettings, AlertUpdate, MassOutage,
+};
+use crate::clock;
+use crate::config::{
+    Config, DnsCheck, DownBackoff, HealthThresholds, HistogramSettings, LatencyUnit, OutlierFilter,
+    PrimaryCheck, RecencyWeighting, SyntheticCheck, Target,
+};
+use crate::dns::Resolution;
+use crate::duplicates::DuplicateWatch;
+use crate::events::{Event, EventFeed, EventKind};
+use crate::hooks::{EscalationEvent, EscalationHook, ResultEvent, ResultHook};
+use crate::link::LinkMonitor;
+use crate::multicast::{ICMPV4_ECHO_REPLY, ICMPV6_ECHO_REPLY, multicast_probe};
+use crate::neighbor::NeighborCheck;
+use crate::throughput::{ThroughputResult, upload_test};
+use crate::units::format_latency;
 use chrono::{DateTime, Utc};
 use color_eyre::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PingResult {
@@ -11,6 +30,21 @@ pub struct PingResult {
     pub latency_ms: Option<f64>,
     pub success: bool,
     pub failure_reason: Option<String>,
+    #[serde(default)]
+    pub warmup: bool,
+    // Failed while this machine had no network, so not held against the target
+    #[serde(default)]
+    pub local_outage: bool,
+    // A miss between replies from a target marked icmp_rate_limited, taken as throttling
+    #[serde(default)]
+    pub rate_limited: bool,
+}
+
+impl PingResult {
+    // Whether the result belongs in the target's statistics and health
+    pub fn counted(&self) -> bool {
+        !self.warmup && !self.local_outage && !self.rate_limited
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +53,20 @@ pub struct SshResult {
     pub connection_time_ms: Option<f64>,
     pub success: bool,
     pub failure_reason: Option<String>,
+    #[serde(default)]
+    pub local_outage: bool,
+    #[serde(default)]
+    pub kind: SshProbeKind,
+}
+
+// What an SSH sample timed. A channel round trip on a persistent session skips TCP and key
+// exchange, so the two aren't comparable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SshProbeKind {
+    #[default]
+    Connect,
+    Channel,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,14 +76,188 @@ pub struct FailureLog {
     pub reason: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsSnapshot {
+    pub timestamp: DateTime<Utc>,
+    pub ping_stats: Option<Statistics>,
+    pub ssh_stats: Option<Statistics>,
+}
+
+// chrono durations as whole milliseconds, for state shared with attached clients
+mod duration_ms {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &chrono::Duration, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_i64(duration.num_milliseconds())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<chrono::Duration, D::Error> {
+        i64::deserialize(d).map(chrono::Duration::milliseconds)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsSettings {
+    #[serde(with = "duration_ms")]
+    pub snapshot_interval: chrono::Duration,
+    pub snapshot_retention: usize,
+    pub warmup_samples: usize,
+    pub loss_penalty_ms: f64,
+    pub health: HealthThresholds,
+    pub histogram: HistogramSettings,
+    pub recency_weighting: Option<RecencyWeighting>,
+    #[serde(default)]
+    pub outlier_filter: Option<OutlierFilter>,
+    pub reference_target: Option<String>,
+}
+
+impl StatsSettings {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            snapshot_interval: chrono::Duration::seconds(config.snapshot_interval_secs as i64),
+            snapshot_retention: config.snapshot_retention,
+            warmup_samples: config.warmup_samples,
+            loss_penalty_ms: config.loss_penalty_ms,
+            health: config.health,
+            histogram: config.histogram,
+            recency_weighting: config.recency_weighting,
+            outlier_filter: config.outlier_filter,
+            reference_target: config.reference_target.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Health {
+    Unknown,
+    Healthy,
+    Degraded,
+    Down,
+}
+
+impl Health {
+    pub fn label(self) -> &'static str {
+        match self {
+            Health::Unknown => "unknown",
+            Health::Healthy => "healthy",
+            Health::Degraded => "degraded",
+            Health::Down => "down",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TargetStats {
     pub target: Target,
     pub ping_history: VecDeque<PingResult>,
     pub ssh_history: VecDeque<SshResult>,
     pub failure_log: VecDeque<FailureLog>,
+    // Entries ever added to failure_log, which keeps only the latest; tells a reader which are
+    // new even when several share a timestamp
+    #[serde(default)]
+    pub failures_logged: u64,
     pub ping_stats: Option<Statistics>,
     pub ssh_stats: Option<Statistics>,
+    pub stats_snapshots: VecDeque<StatsSnapshot>,
+    pub settings: StatsSettings,
+    pub ping_samples_seen: usize,
+    pub ping_sequence: u16,
+    pub health: Health,
+    // When the current run of Down started, for escalation
+    #[serde(default)]
+    pub down_since: Option<DateTime<Utc>>,
+    pub ack: Option<Acknowledgement>,
+    // Counted pings since startup, unaffected by the history size or stats window
+    pub session_started: DateTime<Utc>,
+    pub session_pings: usize,
+    pub session_ping_successes: usize,
+    // Results of the target's configured bursts, separate from the steady ping history
+    pub burst_history: VecDeque<BurstResult>,
+    // Set for hostname targets
+    pub resolution: Option<Resolution>,
+    // ARP check from the latest failed ping, for on-link targets; cleared by the next reply
+    #[serde(default)]
+    pub neighbor: Option<NeighborCheck>,
+    // From the neighbor table after answered pings, for on-link IPv4 targets
+    #[serde(default)]
+    pub mac: Option<String>,
+    // Replies beyond the first to the same ping this session, as `ping` flags with DUP!
+    #[serde(default)]
+    pub duplicate_replies: usize,
+    // Set for targets with a throughput test
+    #[serde(default)]
+    pub throughput_history: VecDeque<ThroughputResult>,
+    // Set while a consistently failing target is pinged less often
+    #[serde(default)]
+    pub backoff: Option<ProbeBackoff>,
+    // Name of an earlier target probes of this one go to the same address as, say two
+    // hostnames behind one CNAME
+    #[serde(default)]
+    pub duplicate_of: Option<String>,
+    // Name of the target down upstream of this one along its depends_on chain; while set,
+    // this one's failures are put down to that outage and it isn't alerted
+    #[serde(default)]
+    pub upstream_down: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProbeBackoff {
+    pub interval_ms: u64,
+    pub next_probe_at: DateTime<Utc>,
+}
+
+// Share of pings answered over some span, with the time that share of the span adds up to
+#[derive(Debug, Clone, Copy)]
+pub struct Availability {
+    pub ratio: f64,
+    pub downtime: chrono::Duration,
+}
+
+impl Availability {
+    fn new(successes: usize, total: usize, span: chrono::Duration) -> Option<Self> {
+        if total == 0 {
+            return None;
+        }
+        let ratio = successes as f64 / total as f64;
+        let downtime_ms = (1.0 - ratio) * span.num_milliseconds() as f64;
+        Some(Self {
+            ratio,
+            downtime: chrono::Duration::milliseconds(downtime_ms.round() as i64),
+        })
+    }
+
+    // Samples as (timestamp, success), spanning first to last
+    fn over(mut samples: impl Iterator<Item = (DateTime<Utc>, bool)>) -> Option<Self> {
+        let (first, success) = samples.next()?;
+        let (total, successes, last) = samples.fold(
+            (1, success as usize, first),
+            |(total, successes, _), (timestamp, success)| {
+                (total + 1, successes + success as usize, timestamp)
+            },
+        );
+        Self::new(successes, total, last - first)
+    }
+
+    // 99.9% is three nines; None for a perfect record, which has infinitely many
+    pub fn nines(&self) -> Option<f64> {
+        (self.ratio < 1.0).then(|| (1.0 - self.ratio).log10().abs())
+    }
+}
+
+// How far apart a target's pings actually landed, against the interval they were due at
+#[derive(Debug, Clone, Copy)]
+pub struct Sampling {
+    pub intervals: usize,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+    // Intervals more than half again as long as the configured one
+    pub late: usize,
+}
+
+impl Sampling {
+    pub fn is_late(interval_ms: f64, expected_ms: u64) -> bool {
+        interval_ms > expected_ms as f64 * 1.5
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,34 +273,289 @@ pub struct Statistics {
     pub p99: f64,
     pub success_rate: f64,
     pub total_count: usize,
+    // Mean inflated by a per-percentage-point loss penalty, for ranking lossy links fairly
+    #[serde(default)]
+    pub effective_latency: f64,
+    // Mean absolute difference between consecutive samples
+    #[serde(default)]
+    pub jitter: f64,
+    // 95% Wilson score interval around success_rate, in percent
+    #[serde(default)]
+    pub success_ci_low: f64,
+    #[serde(default)]
+    pub success_ci_high: f64,
+    // Replies left out by the outlier filter, and the latency above which they were
+    #[serde(default)]
+    pub outliers_excluded: usize,
+    #[serde(default)]
+    pub outlier_limit: Option<f64>,
 }
 
 impl TargetStats {
-    pub fn new(target: Target, history_size: usize) -> Self {
+    pub fn new(target: Target, history_size: usize, settings: StatsSettings) -> Self {
         Self {
+            // A synthetic target's address is only a label
+            resolution: match target.synthetic {
+                Some(_) => None,
+                None => Resolution::for_target(&target.ip),
+            },
             target,
             ping_history: VecDeque::with_capacity(history_size),
             ssh_history: VecDeque::with_capacity(history_size),
             failure_log: VecDeque::with_capacity(history_size),
+            failures_logged: 0,
             ping_stats: None,
             ssh_stats: None,
+            stats_snapshots: VecDeque::with_capacity(settings.snapshot_retention + 1),
+            settings,
+            ping_samples_seen: 0,
+            ping_sequence: 0,
+            health: Health::Unknown,
+            down_since: None,
+            ack: None,
+            session_started: clock::now(),
+            session_pings: 0,
+            session_ping_successes: 0,
+            burst_history: VecDeque::new(),
+            neighbor: None,
+            mac: None,
+            duplicate_replies: 0,
+            throughput_history: VecDeque::new(),
+            backoff: None,
+            duplicate_of: None,
+            upstream_down: None,
+        }
+    }
+
+    // Keeps the latest MAC address from the neighbor table, returning the one it replaced
+    pub fn record_mac(&mut self, mac: String) -> Option<String> {
+        match self.mac.replace(mac.clone()) {
+            Some(previous) if previous != mac => Some(previous),
+            _ => None,
+        }
+    }
+
+    // A fresh baseline, as if monitoring had just started, for after a problem was fixed. The
+    // warmup isn't repeated and the ping sequence carries on so late replies aren't mistaken
+    pub fn clear_history(&mut self) {
+        self.ping_history.clear();
+        self.ssh_history.clear();
+        self.failure_log.clear();
+        self.ping_stats = None;
+        self.ssh_stats = None;
+        self.stats_snapshots.clear();
+        self.health = Health::Unknown;
+        self.down_since = None;
+        self.session_started = clock::now();
+        self.session_pings = 0;
+        self.session_ping_successes = 0;
+        self.burst_history.clear();
+        self.neighbor = None;
+        self.duplicate_replies = 0;
+        self.throughput_history.clear();
+        self.backoff = None;
+    }
+
+    // Whether this cycle skips the target's ping because it is backing off
+    pub fn backing_off(&self) -> bool {
+        self.backoff
+            .as_ref()
+            .is_some_and(|backoff| clock::now() < backoff.next_probe_at)
+    }
+
+    // Each counted miss past the threshold doubles the gap, with equal jitter so long-dead
+    // targets don't all come due in the same cycle; a reply ends the backoff
+    fn update_backoff(&mut self, settings: &DownBackoff, ping_interval_ms: u64) {
+        let Some(latest) = self.ping_history.back().filter(|r| r.counted()) else {
+            return;
+        };
+        if latest.success {
+            self.backoff = None;
+            return;
+        }
+        let misses = self
+            .ping_history
+            .iter()
+            .rev()
+            .filter(|r| r.counted())
+            .take_while(|r| !r.success)
+            .count();
+        if misses < settings.after_failures.max(1) {
+            return;
+        }
+
+        let interval_ms = self
+            .backoff
+            .as_ref()
+            .map_or(ping_interval_ms, |backoff| backoff.interval_ms)
+            .saturating_mul(2)
+            .min(settings.max_interval_ms.max(ping_interval_ms));
+        let delay_ms = interval_ms / 2 + rand::random_range(0..=interval_ms / 2);
+        self.backoff = Some(ProbeBackoff {
+            interval_ms,
+            next_probe_at: clock::now() + chrono::Duration::milliseconds(delay_ms as i64),
+        });
+    }
+
+    // The address probes go to: the literal address, or what the hostname resolved to
+    pub fn probe_address(&self) -> std::result::Result<std::net::IpAddr, String> {
+        if let Some(interface) = &self.target.interface {
+            // Inside a namespace the interface is the namespace's, which only binding can check
+            if self.target.netns.is_none() {
+                crate::zone::check_interface(interface)?;
+            }
+            if let Some(zone) = self.zone().filter(|zone| &zone.interface != interface) {
+                return Err(format!(
+                    "Zone %{} doesn't match interface {}",
+                    zone.interface, interface
+                ));
+            }
+        }
+        match &self.resolution {
+            Some(resolution) => resolution.current.ok_or_else(|| {
+                resolution
+                    .last_error()
+                    .unwrap_or("Hostname not resolved yet")
+                    .to_string()
+            }),
+            None => match crate::zone::split_zone(&self.target.ip) {
+                Some(scoped) => {
+                    let (addr, zone) = scoped?;
+                    crate::zone::resolve(zone)?;
+                    Ok(std::net::IpAddr::V6(addr))
+                }
+                None => self
+                    .target
+                    .ip
+                    .parse()
+                    .map_err(|e| format!("Invalid IP address: {}", e)),
+            },
         }
     }
 
-    pub fn add_ping_result(&mut self, result: PingResult, max_history: usize) {
+    // A link-local target's zone, already checked by probe_address
+    fn zone(&self) -> Option<crate::zone::Zone> {
+        let (_, zone) = crate::zone::split_zone(&self.target.ip)?.ok()?;
+        crate::zone::resolve(zone).ok()
+    }
+
+    // The interface ICMP sockets for this target are bound to
+    pub fn probe_interface(&self) -> Option<String> {
+        self.target
+            .interface
+            .clone()
+            .or_else(|| self.zone().map(|zone| zone.interface))
+    }
+
+    // Where SSH connects, with the scope id a link-local address needs
+    pub fn ssh_address(&self, port: u16) -> std::result::Result<std::net::SocketAddr, String> {
+        let addr = self.probe_address()?;
+        Ok(match (addr, self.zone()) {
+            (std::net::IpAddr::V6(v6), Some(zone)) => {
+                std::net::SocketAddrV6::new(v6, port, 0, zone.scope_id).into()
+            }
+            _ => std::net::SocketAddr::new(addr, port),
+        })
+    }
+
+    pub fn add_ping_result(&mut self, mut result: PingResult, max_history: usize) {
         if self.ping_history.len() >= max_history {
             self.ping_history.pop_front();
         }
 
-        // Log failure if ping failed
-        if !result.success {
-            if let Some(failure_reason) = &result.failure_reason {
-                self.add_failure_log("Ping".to_string(), failure_reason.clone(), max_history);
-            }
+        // The first pings of a session pay for ARP/ND and setup; keep them out of the stats
+        result.warmup = self.ping_samples_seen < self.settings.warmup_samples;
+        self.ping_samples_seen += 1;
+        if self.target.icmp_rate_limited && !result.success && !result.local_outage {
+            self.classify_rate_limited(&mut result, max_history);
+        }
+        if result.counted() {
+            self.session_pings += 1;
+            self.session_ping_successes += result.success as usize;
+        }
+
+        // Log failure if ping failed; a local outage is reported once, not per target
+        if !result.success
+            && !result.local_outage
+            && !result.rate_limited
+            && let Some(failure_reason) = &result.failure_reason
+        {
+            self.add_failure_log("Ping".to_string(), failure_reason.clone(), max_history);
         }
 
         self.ping_history.push_back(result);
         self.update_ping_stats();
+        self.update_health();
+        if self.health == Health::Healthy {
+            self.ack = None;
+        }
+        self.maybe_snapshot_stats();
+    }
+
+    // A miss shortly after a reply is put down to the target throttling ICMP. Once
+    // RATE_LIMIT_MAX_MISSES come in a row it is an outage after all, and the misses leading up
+    // to it are counted again. A target that has never answered is simply down
+    fn classify_rate_limited(&mut self, result: &mut PingResult, max_history: usize) {
+        let streak = self
+            .ping_history
+            .iter()
+            .rev()
+            .take_while(|r| !r.success && !r.local_outage)
+            .count();
+        if streak + 1 < RATE_LIMIT_MAX_MISSES {
+            result.rate_limited = self.ping_history.iter().any(|r| r.success);
+        } else if streak + 1 == RATE_LIMIT_MAX_MISSES {
+            for earlier in self.ping_history.iter_mut().rev().take(streak) {
+                if earlier.rate_limited {
+                    earlier.rate_limited = false;
+                    self.session_pings += earlier.counted() as usize;
+                }
+            }
+            // Logged late, so the failure view still shows how the outage began
+            let reasons: Vec<(DateTime<Utc>, String)> = self
+                .ping_history
+                .iter()
+                .rev()
+                .take(streak)
+                .filter_map(|r| r.failure_reason.clone().map(|reason| (r.timestamp, reason)))
+                .collect();
+            for (timestamp, reason) in reasons.into_iter().rev() {
+                self.push_failure(
+                    FailureLog {
+                        timestamp,
+                        failure_type: "Ping".to_string(),
+                        reason,
+                    },
+                    max_history,
+                );
+            }
+        }
+    }
+
+    // Replies coming back on a steady beat, say every third ping, with short gaps between
+    // them: the mark of ICMP rate limiting rather than of a lossy link. Gives the N of
+    // "1 in N answered"
+    pub fn rate_limit_pattern(&self) -> Option<usize> {
+        if self.target.icmp_rate_limited {
+            return None;
+        }
+        let recent: Vec<bool> = self
+            .ping_history
+            .iter()
+            .filter(|r| r.counted())
+            .map(|r| r.success)
+            .collect();
+        let recent = &recent[recent.len().saturating_sub(RATE_LIMIT_SAMPLES)..];
+        let replies: Vec<usize> = (0..recent.len()).filter(|&i| recent[i]).collect();
+        if recent.len() < RATE_LIMIT_SAMPLES || replies.len() < 3 {
+            return None;
+        }
+
+        let gaps: Vec<usize> = replies.windows(2).map(|w| w[1] - w[0]).collect();
+        let shortest = *gaps.iter().min()?;
+        let longest = *gaps.iter().max()?;
+        (shortest >= 2 && longest - shortest <= 1 && longest <= RATE_LIMIT_MAX_MISSES)
+            .then(|| (recent.len() as f64 / replies.len() as f64).round() as usize)
     }
 
     pub fn add_ssh_result(&mut self, result: SshResult, max_history: usize) {
@@ -87,274 +564,2237 @@ impl TargetStats {
         }
 
         // Log failure if SSH failed
-        if !result.success {
-            if let Some(failure_reason) = &result.failure_reason {
-                self.add_failure_log("ssh".to_string(), failure_reason.clone(), max_history);
-            }
+        if !result.success
+            && !result.local_outage
+            && let Some(failure_reason) = &result.failure_reason
+        {
+            self.add_failure_log("ssh".to_string(), failure_reason.clone(), max_history);
         }
 
         self.ssh_history.push_back(result);
         self.update_ssh_stats();
+        self.update_health();
+        if self.health == Health::Healthy {
+            self.ack = None;
+        }
     }
 
-    pub fn add_failure_log(&mut self, failure_type: String, reason: String, max_history: usize) {
-        if self.failure_log.len() >= max_history {
-            self.failure_log.pop_front();
+    pub fn add_burst_result(&mut self, result: BurstResult, max_history: usize) {
+        if self.burst_history.len() >= max_history {
+            self.burst_history.pop_front();
         }
-
-        let failure_entry = FailureLog {
-            timestamp: Utc::now(),
-            failure_type,
-            reason,
-        };
-
-        self.failure_log.push_back(failure_entry);
+        self.burst_history.push_back(result);
     }
 
-    fn update_ping_stats(&mut self) {
-        let successful_pings: Vec<f64> = self
-            .ping_history
-            .iter()
-            .filter_map(|r| r.latency_ms)
-            .collect();
-
-        if !successful_pings.is_empty() {
-            self.ping_stats = Some(calculate_statistics(
-                &successful_pings,
-                self.ping_history.len(),
-            ));
+    pub fn add_throughput_result(&mut self, result: ThroughputResult, max_history: usize) {
+        if self.throughput_history.len() >= max_history {
+            self.throughput_history.pop_front();
         }
+        self.throughput_history.push_back(result);
     }
 
-    fn update_ssh_stats(&mut self) {
-        let successful_ssh: Vec<f64> = self
-            .ssh_history
+    // Due when the interval has passed since the last test, or straight away without one
+    fn throughput_due(&self) -> bool {
+        let Some(test) = self.target.throughput else {
+            return false;
+        };
+        self.throughput_history.back().is_none_or(|last| {
+            clock::now() - last.timestamp >= chrono::Duration::seconds(test.interval_secs as i64)
+        })
+    }
+
+    // Time from each ping to the next, as (index in the history of the later one, ms)
+    pub fn sampling_intervals(&self) -> Vec<(usize, f64)> {
+        self.ping_history
             .iter()
-            .filter_map(|r| r.connection_time_ms)
-            .collect();
+            .zip(self.ping_history.iter().skip(1))
+            .enumerate()
+            .map(|(i, (earlier, later))| {
+                let gap = later.timestamp - earlier.timestamp;
+                (
+                    i + 1,
+                    gap.num_microseconds().unwrap_or(i64::MAX) as f64 / 1000.0,
+                )
+            })
+            .collect()
+    }
 
-        if !successful_ssh.is_empty() {
-            self.ssh_stats = Some(calculate_statistics(
-                &successful_ssh,
-                self.ssh_history.len(),
-            ));
+    pub fn sampling(&self, expected_ms: u64) -> Option<Sampling> {
+        let intervals = self.sampling_intervals();
+        if intervals.is_empty() {
+            return None;
         }
+        let values = intervals.iter().map(|(_, ms)| *ms);
+        Some(Sampling {
+            intervals: intervals.len(),
+            mean_ms: values.clone().sum::<f64>() / intervals.len() as f64,
+            max_ms: values.clone().fold(0.0, f64::max),
+            late: values
+                .filter(|ms| Sampling::is_late(*ms, expected_ms))
+                .count(),
+        })
     }
-}
 
-pub struct Monitor {
-    targets: Vec<TargetStats>,
-    _ping_interval: Duration,
-    ssh_timeout: Duration,
-    history_size: usize,
-}
+    // Loss across every recorded burst that got sent, with how many bursts that covers
+    pub fn burst_loss(&self) -> Option<(f64, usize)> {
+        let sent_bursts = self.burst_history.iter().filter(|b| b.sent > 0);
+        let (bursts, sent, received) = sent_bursts.fold((0, 0, 0), |(n, sent, received), b| {
+            (n + 1, sent + b.sent, received + b.received)
+        });
+        (sent > 0).then(|| ((sent - received) as f64 / sent as f64 * 100.0, bursts))
+    }
 
-impl Monitor {
-    pub fn new(
-        targets: Vec<Target>,
-        ping_interval_ms: u64,
-        ssh_timeout_ms: u64,
-        history_size: usize,
-    ) -> Self {
-        let target_stats = targets
-            .into_iter()
-            .map(|target| TargetStats::new(target, history_size))
-            .collect();
+    pub fn add_failure_log(&mut self, failure_type: String, reason: String, max_history: usize) {
+        let failure_entry = FailureLog {
+            timestamp: clock::now(),
+            failure_type,
+            reason,
+        };
 
-        Self {
-            targets: target_stats,
-            _ping_interval: Duration::from_millis(ping_interval_ms),
-            ssh_timeout: Duration::from_millis(ssh_timeout_ms),
-            history_size,
-        }
+        self.push_failure(failure_entry, max_history);
     }
 
-    pub fn get_targets(&self) -> &[TargetStats] {
-        &self.targets
+    fn push_failure(&mut self, failure: FailureLog, max_history: usize) {
+        if self.failure_log.len() >= max_history {
+            self.failure_log.pop_front();
+        }
+        self.failure_log.push_back(failure);
+        self.failures_logged += 1;
     }
 
-    pub async fn run_ping_cycle(&mut self) -> Result<()> {
-        let mut handles = Vec::new();
+    // Snapshots are driven by the ping cadence; they only need minute-level precision
+    fn maybe_snapshot_stats(&mut self) {
+        if self.ping_stats.is_none() && self.ssh_stats.is_none() {
+            return;
+        }
 
-        for (index, target_stats) in self.targets.iter().enumerate() {
-            let ip = target_stats.target.ip.clone();
-            let handle = tokio::spawn(async move { (index, ping_target(&ip).await) });
-            handles.push(handle);
+        let now = clock::now();
+        if let Some(last) = self.stats_snapshots.back()
+            && now - last.timestamp < self.settings.snapshot_interval
+        {
+            return;
         }
 
-        for handle in handles {
-            if let Ok((index, result)) = handle.await {
-                if let Some(target_stats) = self.targets.get_mut(index) {
-                    target_stats.add_ping_result(result, self.history_size);
-                }
-            }
+        // One more than the retention, so the snapshot from a full retention ago (an hour with
+        // the defaults) is still there to compare against
+        if self.stats_snapshots.len() > self.settings.snapshot_retention {
+            self.stats_snapshots.pop_front();
         }
 
-        Ok(())
+        self.stats_snapshots.push_back(StatsSnapshot {
+            timestamp: now,
+            ping_stats: self.ping_stats.clone(),
+            ssh_stats: self.ssh_stats.clone(),
+        });
     }
 
-    pub async fn run_ssh_cycle(&mut self) -> Result<()> {
-        let mut handles = Vec::new();
+    // Most recent snapshot taken at least `age` ago
+    pub fn snapshot_from(&self, age: chrono::Duration) -> Option<&StatsSnapshot> {
+        let cutoff = clock::now() - age;
+        self.stats_snapshots
+            .iter()
+            .rev()
+            .find(|snapshot| snapshot.timestamp <= cutoff)
+    }
 
-        for (index, target_stats) in self.targets.iter().enumerate() {
-            if target_stats.target.ssh_port.is_some() && target_stats.target.ssh_user.is_some() {
-                let ip = target_stats.target.ip.clone();
-                let port = target_stats.target.ssh_port.unwrap_or(22);
-                let user = target_stats.target.ssh_user.clone().unwrap();
-                let timeout = self.ssh_timeout;
+    // Copy restricted to samples newer than `since`, with statistics recomputed over just that span
+    pub fn windowed(&self, since: chrono::Duration) -> TargetStats {
+        self.between(clock::now() - since, None)
+    }
 
-                let handle =
-                    tokio::spawn(async move { (index, ssh_test(&ip, port, &user, timeout).await) });
-                handles.push(handle);
-            }
+    // Copy keeping only the latest `count` pings and SSH checks, for drawing; statistics and
+    // health stay those of the whole history
+    pub fn latest_samples(&self, count: usize) -> TargetStats {
+        let latest = |len: usize| len.saturating_sub(count);
+        self.with_samples(
+            self.ping_history
+                .range(latest(self.ping_history.len())..)
+                .cloned()
+                .collect(),
+            self.ssh_history
+                .range(latest(self.ssh_history.len())..)
+                .cloned()
+                .collect(),
+            self.failure_log.clone(),
+        )
+    }
+
+    // Copy with these samples in place of the histories, which are only ever copied in part.
+    // Views built every frame go through here, so a long history isn't cloned just to be cut
+    fn with_samples(
+        &self,
+        ping_history: VecDeque<PingResult>,
+        ssh_history: VecDeque<SshResult>,
+        failure_log: VecDeque<FailureLog>,
+    ) -> TargetStats {
+        // Listed out, so a new field can't be forgotten here
+        let TargetStats {
+            target,
+            failures_logged,
+            ping_stats,
+            ssh_stats,
+            stats_snapshots,
+            settings,
+            ping_samples_seen,
+            ping_sequence,
+            health,
+            down_since,
+            ack,
+            session_started,
+            session_pings,
+            session_ping_successes,
+            burst_history,
+            resolution,
+            neighbor,
+            mac,
+            duplicate_replies,
+            throughput_history,
+            backoff,
+            duplicate_of,
+            upstream_down,
+            ping_history: _,
+            ssh_history: _,
+            failure_log: _,
+        } = self;
+        TargetStats {
+            ping_history,
+            ssh_history,
+            failure_log,
+            target: target.clone(),
+            failures_logged: *failures_logged,
+            ping_stats: ping_stats.clone(),
+            ssh_stats: ssh_stats.clone(),
+            stats_snapshots: stats_snapshots.clone(),
+            settings: settings.clone(),
+            ping_samples_seen: *ping_samples_seen,
+            ping_sequence: *ping_sequence,
+            health: *health,
+            down_since: *down_since,
+            ack: ack.clone(),
+            session_started: *session_started,
+            session_pings: *session_pings,
+            session_ping_successes: *session_ping_successes,
+            burst_history: burst_history.clone(),
+            resolution: resolution.clone(),
+            neighbor: neighbor.clone(),
+            mac: mac.clone(),
+            duplicate_replies: *duplicate_replies,
+            throughput_history: throughput_history.clone(),
+            backoff: backoff.clone(),
+            duplicate_of: duplicate_of.clone(),
+            upstream_down: upstream_down.clone(),
         }
+    }
 
-        for handle in handles {
-            if let Ok((index, result)) = handle.await {
-                if let Some(target_stats) = self.targets.get_mut(index) {
-                    target_stats.add_ssh_result(result, self.history_size);
-                }
-            }
+    // Rough heap use of the histories: their buffers plus the text of failure reasons
+    pub fn history_bytes(&self) -> usize {
+        fn buffer<T>(history: &VecDeque<T>) -> usize {
+            history.capacity() * std::mem::size_of::<T>()
         }
+        let text = |reason: &Option<String>| reason.as_ref().map_or(0, String::capacity);
+        buffer(&self.ping_history)
+            + self
+                .ping_history
+                .iter()
+                .map(|r| text(&r.failure_reason))
+                .sum::<usize>()
+            + buffer(&self.ssh_history)
+            + self
+                .ssh_history
+                .iter()
+                .map(|r| text(&r.failure_reason))
+                .sum::<usize>()
+            + buffer(&self.failure_log)
+            + self
+                .failure_log
+                .iter()
+                .map(|f| f.failure_type.capacity() + f.reason.capacity())
+                .sum::<usize>()
+            + buffer(&self.stats_snapshots)
+            + buffer(&self.burst_history)
+            + buffer(&self.throughput_history)
+    }
 
-        Ok(())
+    // Only what happened from `from` until `to` (or now), with stats and health over just that
+    pub fn between(&self, from: DateTime<Utc>, to: Option<DateTime<Utc>>) -> TargetStats {
+        let within =
+            |timestamp: DateTime<Utc>| timestamp >= from && to.is_none_or(|to| timestamp <= to);
+        let mut windowed = self.with_samples(
+            self.ping_history
+                .iter()
+                .filter(|r| within(r.timestamp))
+                .cloned()
+                .collect(),
+            self.ssh_history
+                .iter()
+                .filter(|r| within(r.timestamp))
+                .cloned()
+                .collect(),
+            self.failure_log
+                .iter()
+                .filter(|f| within(f.timestamp))
+                .cloned()
+                .collect(),
+        );
+        windowed.ping_stats = None;
+        windowed.ssh_stats = None;
+        windowed.update_ping_stats();
+        windowed.update_ssh_stats();
+        windowed.update_health();
+        windowed
     }
-}
 
-async fn ping_target(ip: &str) -> PingResult {
-    let timestamp = Utc::now();
+    // Over the pings in the history (or the stats window), spanning first to last sample
+    pub fn window_availability(&self) -> Option<Availability> {
+        let counted = self.ping_history.iter().filter(|r| r.counted());
+        Availability::over(counted.map(|r| (r.timestamp, r.success)))
+    }
 
-    let addr = match ip.parse::<std::net::IpAddr>() {
-        Ok(addr) => addr,
-        Err(e) => {
-            return PingResult {
-                timestamp,
-                latency_ms: None,
-                success: false,
-                failure_reason: Some(format!("Invalid IP address: {}", e)),
-            };
-        }
-    };
+    // Same, over the SSH checks
+    pub fn ssh_availability(&self) -> Option<Availability> {
+        let counted = self.ssh_history.iter().filter(|r| !r.local_outage);
+        Availability::over(counted.map(|r| (r.timestamp, r.success)))
+    }
 
-    let config = surge_ping::Config::default();
-    let client = match surge_ping::Client::new(&config) {
-        Ok(client) => client,
-        Err(e) => {
-            return PingResult {
-                timestamp,
-                latency_ms: None,
-                success: false,
-                failure_reason: Some(format!("Failed to create ping client: {}", e)),
-            };
+    // Each check's availability over the history, averaged by the target's check_weights.
+    // With a single check this is just that check's
+    pub fn composite_availability(&self) -> Option<Availability> {
+        let parts = self.composite_parts();
+        let total: f64 = parts.iter().map(|(_, weight, _)| weight).sum();
+        if total <= 0.0 {
+            return None;
         }
-    };
+        let mean = |value: fn(&Availability) -> f64| {
+            parts.iter().map(|(_, w, a)| w * value(a)).sum::<f64>() / total
+        };
+        Some(Availability {
+            ratio: mean(|a| a.ratio),
+            downtime: chrono::Duration::milliseconds(
+                mean(|a| a.downtime.num_milliseconds() as f64).round() as i64,
+            ),
+        })
+    }
 
-    let mut pinger = client.pinger(addr, surge_ping::PingIdentifier(0)).await;
+    // The checks that go into the composite, with their weights: those with samples and a
+    // weight above zero
+    pub fn composite_parts(&self) -> Vec<(&'static str, f64, Availability)> {
+        let weights = &self.target.check_weights;
+        [
+            ("ping", weights.ping, self.window_availability()),
+            ("SSH", weights.ssh, self.ssh_availability()),
+        ]
+        .into_iter()
+        .filter(|(_, weight, _)| *weight > 0.0)
+        .filter_map(|(check, weight, availability)| Some((check, weight, availability?)))
+        .collect()
+    }
 
-    match pinger.ping(surge_ping::PingSequence(0), &[]).await {
-        Ok((_, duration)) => {
-            let latency = duration.as_millis() as f64;
-            PingResult {
-                timestamp,
-                latency_ms: Some(latency),
-                success: true,
-                failure_reason: None,
-            }
-        }
-        Err(e) => PingResult {
-            timestamp,
-            latency_ms: None,
-            success: false,
-            failure_reason: Some(format!("Ping failed: {}", e)),
-        },
+    pub fn session_availability(&self) -> Option<Availability> {
+        Availability::new(
+            self.session_ping_successes,
+            self.session_pings,
+            clock::now() - self.session_started,
+        )
     }
-}
 
-async fn ssh_test(ip: &str, port: u16, _user: &str, timeout: Duration) -> SshResult {
-    let start = Instant::now();
-    let timestamp = Utc::now();
-
-    let result = tokio::time::timeout(timeout, async {
-        let tcp = std::net::TcpStream::connect(format!("{}:{}", ip, port));
-        match tcp {
-            Ok(stream) => {
-                let mut session = ssh2::Session::new().unwrap();
-                session.set_tcp_stream(stream);
-                match session.handshake() {
-                    Ok(_) => Ok("Success".to_string()),
-                    Err(e) => Err(format!("SSH handshake failed: {}", e)),
-                }
+    // Which degradation thresholds the current primary check's statistics exceed, as display
+    // text. SSH connect times include the handshake, so only loss applies to them
+    pub fn degraded_reasons(&self) -> Vec<String> {
+        let thresholds = &self.settings.health;
+        let mut reasons = Vec::new();
+        match self.failure_streak() {
+            0 => {}
+            1 => reasons.push("latest check failed".to_string()),
+            streak => reasons.push(format!("last {} checks failed", streak)),
+        }
+        if self.primary_check() == PrimaryCheck::Ssh {
+            if let Some(loss) = self
+                .ssh_stats
+                .as_ref()
+                .map(|stats| 100.0 - stats.success_rate)
+                .filter(|loss| *loss > thresholds.loss_percent)
+            {
+                reasons.push(format!("{:.1}% SSH loss", loss));
             }
-            Err(e) => Err(format!("TCP connection failed: {}", e)),
+            return reasons;
         }
-    })
-    .await;
+        let Some(stats) = &self.ping_stats else {
+            return reasons;
+        };
 
-    match result {
-        Ok(Ok(_)) => {
-            let connection_time = start.elapsed().as_millis() as f64;
-            SshResult {
-                timestamp,
-                connection_time_ms: Some(connection_time),
-                success: true,
-                failure_reason: None,
-            }
+        let loss = 100.0 - stats.success_rate;
+        if loss > thresholds.loss_percent {
+            reasons.push(match self.rate_limit_pattern() {
+                Some(_) => format!("{:.1}% loss, looks rate-limited", loss),
+                None => format!("{:.1}% loss", loss),
+            });
         }
-        Ok(Err(error_msg)) => SshResult {
-            timestamp,
-            connection_time_ms: None,
-            success: false,
-            failure_reason: Some(error_msg),
-        },
-        Err(_) => SshResult {
-            timestamp,
-            connection_time_ms: None,
-            success: false,
-            failure_reason: Some(format!(
-                "SSH connection timeout after {}ms",
-                timeout.as_millis()
-            )),
-        },
+        if stats.mean > thresholds.latency_ms {
+            reasons.push(format!(
+                "{} mean latency",
+                format_latency(stats.mean, LatencyUnit::Auto, 1)
+            ));
+        }
+        if stats.jitter > thresholds.jitter_ms {
+            reasons.push(format!(
+                "{} jitter",
+                format_latency(stats.jitter, LatencyUnit::Auto, 1)
+            ));
+        }
+        reasons
     }
-}
 
-fn calculate_statistics(values: &[f64], total_count: usize) -> Statistics {
-    let mut sorted_values = values.to_vec();
-    sorted_values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    // Latest counted primary checks, newest first, as (timestamp, success)
+    fn recent_primary_checks(&self) -> Vec<(DateTime<Utc>, bool)> {
+        let limit = self.settings.health.down_after.max(1);
+        match self.primary_check() {
+            PrimaryCheck::Ping => self
+                .ping_history
+                .iter()
+                .rev()
+                .filter(|r| r.counted())
+                .take(limit)
+                .map(|r| (r.timestamp, r.success))
+                .collect(),
+            PrimaryCheck::Ssh => self
+                .ssh_history
+                .iter()
+                .rev()
+                .filter(|r| !r.local_outage)
+                .take(limit)
+                .map(|r| (r.timestamp, r.success))
+                .collect(),
+        }
+    }
 
-    let mean = values.iter().sum::<f64>() / values.len() as f64;
-    let median = percentile(&sorted_values, 50.0);
-    let min = *sorted_values.first().unwrap_or(&0.0);
-    let max = *sorted_values.last().unwrap_or(&0.0);
-    let success_rate = (values.len() as f64 / total_count as f64) * 100.0;
+    // How many of the latest counted primary checks failed in a row
+    fn failure_streak(&self) -> usize {
+        self.recent_primary_checks()
+            .iter()
+            .take_while(|(_, success)| !success)
+            .count()
+    }
 
-    Statistics {
-        mean,
-        median,
-        min,
-        max,
-        p25: percentile(&sorted_values, 25.0),
-        p75: percentile(&sorted_values, 75.0),
-        p90: percentile(&sorted_values, 90.0),
-        p95: percentile(&sorted_values, 95.0),
-        p99: percentile(&sorted_values, 99.0),
-        success_rate,
-        total_count,
+    // Down once `down_after` counted primary checks in a row have failed, so one lost ping
+    // doesn't raise a critical alert; a shorter streak leaves the target Degraded. Otherwise
+    // the statistics decide between Healthy and Degraded
+    fn update_health(&mut self) {
+        let recent = self.recent_primary_checks();
+        let streak = recent.iter().take_while(|(_, success)| !success).count();
+        self.health = match recent.first() {
+            None => Health::Unknown,
+            Some(_) if streak >= self.settings.health.down_after.max(1) => Health::Down,
+            Some(_) if streak > 0 || !self.degraded_reasons().is_empty() => Health::Degraded,
+            Some(_) => Health::Healthy,
+        };
+        // From the first failure of the streak, not from when it got long enough
+        self.down_since = match self.health {
+            Health::Down => self.down_since.or(recent.last().map(|(at, _)| *at)),
+            _ => None,
+        };
     }
-}
 
-fn percentile(sorted_values: &[f64], p: f64) -> f64 {
-    if sorted_values.is_empty() {
-        return 0.0;
+    // Latencies above these are outliers the charts don't scale to
+    pub fn ping_scale_limit(&self) -> f64 {
+        scale_limit(self.ping_stats.as_ref())
     }
 
-    if sorted_values.len() == 1 {
-        return sorted_values[0];
+    pub fn ssh_scale_limit(&self) -> f64 {
+        scale_limit(self.ssh_stats.as_ref())
     }
 
-    let index = (p / 100.0) * (sorted_values.len() - 1) as f64;
-    let lower = index.floor() as usize;
-    let upper = index.ceil() as usize;
+    // What health follows: SSH only for targets that have an SSH check
+    pub fn primary_check(&self) -> PrimaryCheck {
+        match self.target.primary_check {
+            PrimaryCheck::Ssh
+                if self.target.ssh_user.is_some() && self.target.ssh_port.is_some() =>
+            {
+                PrimaryCheck::Ssh
+            }
+            _ => PrimaryCheck::Ping,
+        }
+    }
+
+    // Why the latest primary check failed, for events and alerts
+    pub fn down_reason(&self) -> Option<&str> {
+        match self.primary_check() {
+            PrimaryCheck::Ping => self.ping_history.back()?.failure_reason.as_deref(),
+            PrimaryCheck::Ssh => self.ssh_history.back()?.failure_reason.as_deref(),
+        }
+    }
+
+    fn update_ping_stats(&mut self) {
+        let counted = self.ping_history.iter().filter(|r| r.counted());
+        let total_count = counted.clone().count();
+        let successful_pings: Vec<f64> = counted.filter_map(|r| r.latency_ms).collect();
+
+        if !successful_pings.is_empty() {
+            self.ping_stats = Some(calculate_statistics(
+                &successful_pings,
+                total_count,
+                self.settings.loss_penalty_ms,
+                self.settings.recency_weighting,
+                self.settings.outlier_filter,
+            ));
+        }
+    }
+
+    // With persistent sessions only the channel round trips are timed; the occasional successful
+    // reconnect is left out, since it would read as a spike. Failures of either kind count
+    fn update_ssh_stats(&mut self) {
+        let timed = match self
+            .ssh_history
+            .iter()
+            .any(|r| r.kind == SshProbeKind::Channel)
+        {
+            true => SshProbeKind::Channel,
+            false => SshProbeKind::Connect,
+        };
+        let counted = self
+            .ssh_history
+            .iter()
+            .filter(|r| !r.local_outage && (!r.success || r.kind == timed));
+        let total_count = counted.clone().count();
+        let successful_ssh: Vec<f64> = counted.filter_map(|r| r.connection_time_ms).collect();
+
+        if !successful_ssh.is_empty() {
+            self.ssh_stats = Some(calculate_statistics(
+                &successful_ssh,
+                total_count,
+                self.settings.loss_penalty_ms,
+                self.settings.recency_weighting,
+                self.settings.outlier_filter,
+            ));
+        }
+    }
+}
+
+fn scale_limit(stats: Option<&Statistics>) -> f64 {
+    stats
+        .and_then(|stats| stats.outlier_limit)
+        .unwrap_or(f64::INFINITY)
+}
+
+// Target indices by descending priority; the sort is stable, so equal priorities (including
+// the default of none at all) keep config order
+fn dispatch_order(targets: &[Target]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..targets.len()).collect();
+    order.sort_by_key(|&index| std::cmp::Reverse(targets[index].priority.unwrap_or(0)));
+    order
+}
+
+// ICMP identifiers are 16 bits, so one shared socket can tell apart replies for at most
+// this many targets; beyond it identifiers wrap and replies may be misattributed. The last
+// identifier is kept back for bursts
+pub const MAX_PING_TARGETS: usize = u16::MAX as usize;
+pub const BURST_PING_IDENTIFIER: surge_ping::PingIdentifier = surge_ping::PingIdentifier(u16::MAX);
+
+// Deterministic per-target identifier: the target's index, which is unique up to
+// MAX_PING_TARGETS (hashing would only introduce collisions within that space)
+pub fn ping_identifier(index: usize) -> surge_ping::PingIdentifier {
+    surge_ping::PingIdentifier((index % MAX_PING_TARGETS) as u16)
+}
+
+// A ping as sent: the client it went out on, where to, and the identifier and sequence it bore
+type ProbeKey = (
+    surge_ping::Client,
+    std::net::IpAddr,
+    surge_ping::PingIdentifier,
+    surge_ping::PingSequence,
+);
+
+// One client per address family, network namespace and bound interface, keyed by
+// (namespace, is IPv6, interface)
+#[derive(Default)]
+struct PingClients {
+    clients: HashMap<(Option<String>, bool, Option<String>), surge_ping::Client>,
+}
+
+impl PingClients {
+    // Created lazily and retried on the next cycle if socket creation fails
+    fn get(
+        &mut self,
+        addr: &std::net::IpAddr,
+        netns: Option<&str>,
+        interface: Option<&str>,
+    ) -> std::result::Result<surge_ping::Client, String> {
+        let key = (
+            netns.map(str::to_string),
+            addr.is_ipv6(),
+            interface.map(str::to_string),
+        );
+        if let Some(client) = self.clients.get(&key) {
+            return Ok(client.clone());
+        }
+
+        let config = icmp_config(addr, interface);
+        let client = match netns {
+            Some(name) => crate::netns::in_netns(name, move || surge_ping::Client::new(&config))?,
+            None => surge_ping::Client::new(&config).map_err(|e| match interface {
+                Some(interface) => format!("Failed to bind a ping socket to {}: {}", interface, e),
+                None => format!("Failed to create ping client: {}", e),
+            })?,
+        };
+        self.clients.insert(key, client.clone());
+        Ok(client)
+    }
+}
+
+const ALERT_LOG_SIZE: usize = 100;
+// With icmp_rate_limited, this many misses in a row is an outage rather than throttling
+const RATE_LIMIT_MAX_MISSES: usize = 10;
+// Counted pings looked at for a rate-limiting pattern
+const RATE_LIMIT_SAMPLES: usize = 30;
+
+// This machine's own network has been unavailable since `since`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalOutage {
+    pub since: DateTime<Utc>,
+    pub reason: String,
+}
+
+// Requests from the frontends to the monitoring loop
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MonitorCommand {
+    // Run ping and SSH cycles now rather than at the next tick
+    ProbeNow,
+    Acknowledge(usize, Acknowledgement),
+    ClearAcknowledgement(usize),
+    // Hold back all alerts until then; None unmutes
+    MuteAlerts(Option<DateTime<Utc>>),
+    // Write the targets being monitored, with the global settings, to the profile's config file
+    SaveConfig,
+    // Drop one target's samples, failures and statistics so they start again from now
+    ClearHistory(usize),
+    // Write targets and a ping interval from the config editor to the profile's config file.
+    // Monitoring carries on as it is; they apply from the next start
+    SaveEditedConfig {
+        targets: Vec<Target>,
+        ping_interval_ms: u64,
+    },
+}
+
+// The monitor's own counters, for telling an odd reading caused by the tool from one caused by
+// the network. Durations are in milliseconds
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Diagnostics {
+    pub ping_cycles: u64,
+    pub ssh_cycles: u64,
+    total_ping_cycle_ms: f64,
+    total_ssh_cycle_ms: f64,
+    pub last_ping_cycle_ms: f64,
+    // How late the latest scheduled ping cycle started, and the worst so far
+    pub loop_lag_ms: f64,
+    pub max_loop_lag_ms: f64,
+    // SSH checks, bursts and throughput tests still running when the latest cycle ended
+    pub in_flight_probes: usize,
+    // Estimated size of every target's histories
+    pub history_bytes: usize,
+    pub last_error: Option<(DateTime<Utc>, String)>,
+}
+
+impl Diagnostics {
+    // `lag` is None for a cycle run out of turn, such as one asked for with 'r'
+    pub fn record_ping_cycle(&mut self, took: Duration, lag: Option<Duration>) {
+        self.ping_cycles += 1;
+        self.last_ping_cycle_ms = took.as_secs_f64() * 1000.0;
+        self.total_ping_cycle_ms += self.last_ping_cycle_ms;
+        if let Some(lag) = lag {
+            self.loop_lag_ms = lag.as_secs_f64() * 1000.0;
+            self.max_loop_lag_ms = self.max_loop_lag_ms.max(self.loop_lag_ms);
+        }
+    }
+
+    pub fn record_ssh_cycle(&mut self, took: Duration) {
+        self.ssh_cycles += 1;
+        self.total_ssh_cycle_ms += took.as_secs_f64() * 1000.0;
+    }
+
+    pub fn record_error(&mut self, error: String) {
+        self.last_error = Some((clock::now(), error));
+    }
+
+    pub fn mean_ping_cycle_ms(&self) -> Option<f64> {
+        (self.ping_cycles > 0).then(|| self.total_ping_cycle_ms / self.ping_cycles as f64)
+    }
+
+    pub fn mean_ssh_cycle_ms(&self) -> Option<f64> {
+        (self.ssh_cycles > 0).then(|| self.total_ssh_cycle_ms / self.ssh_cycles as f64)
+    }
+}
+
+// Liveness of the monitoring loop, shared with the frontends so a stalled loop isn't mistaken
+// for a quiet network
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitorStatus {
+    pub last_cycle_at: DateTime<Utc>,
+    #[serde(with = "duration_ms")]
+    stall_threshold: chrono::Duration,
+    pub active_alerts: Vec<Alert>,
+    pub alert_log: VecDeque<Alert>,
+    pub quiet_hours: bool,
+    pub alerts_muted_until: Option<DateTime<Utc>>,
+    // Set when the config file was unreadable and a fallback config was loaded instead
+    pub config_warning: Option<String>,
+    // Latest on_result_command failure, until a later run succeeds
+    pub hook_error: Option<String>,
+    // Latest failure to reach the collector, until a later send gets through
+    #[serde(default)]
+    pub collector_error: Option<String>,
+    pub local_outage: Option<LocalOutage>,
+    // Enough targets down together that this machine's network is the likelier cause
+    #[serde(default)]
+    pub mass_outage: Option<MassOutage>,
+    pub events: VecDeque<Event>,
+    // What the config editor starts from
+    #[serde(default)]
+    pub ping_interval_ms: u64,
+    #[serde(default)]
+    pub diagnostics: Diagnostics,
+}
+
+impl MonitorStatus {
+    pub fn new(config: &Config) -> Self {
+        // An SSH cycle may legitimately take the full SSH timeout before pings resume
+        let stall_ms = config.ping_interval_ms * 3 + config.ssh_timeout_ms;
+        Self {
+            last_cycle_at: clock::now(),
+            stall_threshold: chrono::Duration::milliseconds(stall_ms as i64),
+            active_alerts: Vec::new(),
+            alert_log: VecDeque::with_capacity(ALERT_LOG_SIZE),
+            quiet_hours: false,
+            alerts_muted_until: None,
+            config_warning: None,
+            hook_error: None,
+            collector_error: None,
+            local_outage: None,
+            mass_outage: None,
+            events: VecDeque::new(),
+            ping_interval_ms: config.ping_interval_ms,
+            diagnostics: Diagnostics::default(),
+        }
+    }
+
+    pub fn record_alerts(&mut self, update: AlertUpdate) {
+        self.active_alerts = update.active;
+        self.quiet_hours = update.quiet_hours;
+        self.alerts_muted_until = update.muted_until;
+        self.mass_outage = update.mass_outage;
+        for alert in update.fired {
+            if self.alert_log.len() >= ALERT_LOG_SIZE {
+                self.alert_log.pop_front();
+            }
+            self.alert_log.push_back(alert);
+        }
+    }
+
+    pub fn record_cycle(&mut self) {
+        self.last_cycle_at = clock::now();
+    }
+
+    pub fn stalled_for(&self) -> Option<chrono::Duration> {
+        let elapsed = clock::now() - self.last_cycle_at;
+        (elapsed > self.stall_threshold).then_some(elapsed)
+    }
+}
+
+pub struct Monitor {
+    targets: Vec<TargetStats>,
+    ping_interval: Duration,
+    ssh_timeout: Duration,
+    history_size: usize,
+    ping_clients: PingClients,
+    ssh_persistent: bool,
+    ssh_key_file: Option<PathBuf>,
+    probe_jitter: Duration,
+    // Shared by ping and SSH probes; each holds a permit while it runs
+    probe_limit: Arc<Semaphore>,
+    ssh_sessions: HashMap<usize, ssh2::Session>,
+    alerts: AlertEngine,
+    // As of the latest analysis, for logging when it starts and ends
+    mass_outage: Option<MassOutage>,
+    dispatch_order: Vec<usize>,
+    ping_cycles: u64,
+    // Configured bursts still in flight, by target index; collected at the next ping cycle
+    pending_bursts: HashMap<usize, tokio::task::JoinHandle<BurstResult>>,
+    // SSH probes that outlived their cycle, by target index. Until one finishes its target is
+    // skipped, so a struggling host never has more than one connection from us at a time
+    pending_ssh: HashMap<usize, tokio::task::JoinHandle<SshOutcome>>,
+    // Throughput tests in flight, by target index; like bursts, collected at the next ping cycle
+    pending_throughput: HashMap<usize, tokio::task::JoinHandle<ThroughputOutcome>>,
+    // Answered pings to look for duplicate replies to, by target index; checked at the next ping
+    // cycle, by when any duplicates have long arrived
+    pending_replies: HashMap<usize, ProbeKey>,
+    duplicate_watch: DuplicateWatch,
+    result_hook: Option<ResultHook>,
+    escalation_hook: EscalationHook,
+    link: LinkMonitor,
+    local_outage: Option<LocalOutage>,
+    dns_refresh: chrono::Duration,
+    dns_stable_resolutions: usize,
+    dns_check: DnsCheck,
+    down_backoff: Option<DownBackoff>,
+    coalesce_duplicates: bool,
+    events: EventFeed,
+    // What monitoring started from, and the profile it is saved back to
+    config: Config,
+    profile: Option<String>,
+    warnings: Vec<String>,
+}
+
+impl Monitor {
+    pub fn new(config: &Config, profile: Option<&str>) -> Self {
+        let settings = StatsSettings::from_config(config);
+        let target_stats = config
+            .targets
+            .iter()
+            .map(|target| TargetStats::new(target.clone(), config.history_size, settings.clone()))
+            .collect::<Vec<_>>();
+
+        let mut warnings = Vec::new();
+        if target_stats.len() > MAX_PING_TARGETS {
+            warnings.push(format!(
+                "{} targets exceeds the {} distinct ICMP identifiers; replies may be misattributed",
+                target_stats.len(),
+                MAX_PING_TARGETS
+            ));
+        }
+
+        let mut monitor = Self {
+            targets: target_stats,
+            ping_interval: Duration::from_millis(config.ping_interval_ms),
+            ssh_timeout: Duration::from_millis(config.ssh_timeout_ms),
+            history_size: config.history_size,
+            ping_clients: PingClients::default(),
+            ssh_persistent: config.ssh_persistent,
+            ssh_key_file: config.ssh_key_file.clone(),
+            // At most half the interval, so jittered cycles still finish before the next tick
+            probe_jitter: Duration::from_millis(
+                config.probe_jitter_ms.min(config.ping_interval_ms / 2),
+            ),
+            probe_limit: Arc::new(Semaphore::new(config.max_concurrent_probes.max(1))),
+            ssh_sessions: HashMap::new(),
+            alerts: AlertEngine::new(AlertSettings::from_config(config)),
+            mass_outage: None,
+            dispatch_order: dispatch_order(&config.targets),
+            ping_cycles: 0,
+            pending_bursts: HashMap::new(),
+            pending_ssh: HashMap::new(),
+            pending_throughput: HashMap::new(),
+            pending_replies: HashMap::new(),
+            duplicate_watch: DuplicateWatch::start(),
+            result_hook: ResultHook::from_config(config),
+            escalation_hook: EscalationHook::from_config(config),
+            link: LinkMonitor::default(),
+            local_outage: None,
+            dns_refresh: chrono::Duration::seconds(config.dns_refresh_secs as i64),
+            dns_stable_resolutions: config.dns_stable_resolutions,
+            dns_check: config.dns_check.clone(),
+            down_backoff: config.down_backoff,
+            coalesce_duplicates: config.coalesce_duplicate_addresses,
+            events: EventFeed::default(),
+            config: config.clone(),
+            profile: profile.map(str::to_string),
+            warnings: Vec::new(),
+        };
+        for target in &config.targets {
+            if let Some(upstream) = &target.depends_on
+                && monitor.find_target(upstream).is_none()
+            {
+                warnings.push(format!(
+                    "{} depends on {}, which isn't a monitored target",
+                    target.name.as_ref().unwrap_or(&target.ip),
+                    upstream
+                ));
+            }
+        }
+        monitor.warnings = warnings;
+        monitor
+    }
+
+    // Problems with the configuration found while setting up, for the config warning banner
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    pub fn get_targets(&self) -> &[TargetStats] {
+        &self.targets
+    }
+
+    // Applies a frontend request; true when it asks for an immediate probe cycle
+    pub fn apply_command(&mut self, command: MonitorCommand) -> bool {
+        match command {
+            MonitorCommand::ProbeNow => {
+                // Asked for by hand, so targets that are backing off are probed too
+                for target_stats in &mut self.targets {
+                    if let Some(backoff) = &mut target_stats.backoff {
+                        backoff.next_probe_at = clock::now();
+                    }
+                }
+                return true;
+            }
+            MonitorCommand::Acknowledge(index, ack) => {
+                if let Some(target_stats) = self.targets.get_mut(index) {
+                    self.events.push(
+                        Some(&target_stats.target),
+                        EventKind::Acknowledged,
+                        format!("by {}: {}", ack.by, ack.note),
+                    );
+                    target_stats.ack = Some(ack);
+                }
+            }
+            MonitorCommand::ClearAcknowledgement(index) => {
+                if let Some(target_stats) = self.targets.get_mut(index) {
+                    self.events.push(
+                        Some(&target_stats.target),
+                        EventKind::Acknowledged,
+                        "cleared".to_string(),
+                    );
+                    target_stats.ack = None;
+                }
+            }
+            MonitorCommand::MuteAlerts(until) => {
+                let message = match until {
+                    Some(until) => format!(
+                        "all alerts muted until {}",
+                        until.with_timezone(&chrono::Local).format("%H:%M:%S")
+                    ),
+                    None => "alerts unmuted".to_string(),
+                };
+                self.events.push(None, EventKind::AlertsMuted, message);
+                self.alerts.mute(until);
+            }
+            MonitorCommand::SaveConfig => {
+                let config = Config {
+                    targets: self.targets.iter().map(|t| t.target.clone()).collect(),
+                    ..self.config.clone()
+                };
+                let message = match self.save_config(&config) {
+                    Ok(path) => format!("saved to {}", path.display()),
+                    Err(e) => format!("save failed: {}", e),
+                };
+                self.events.push(None, EventKind::ConfigSaved, message);
+            }
+            MonitorCommand::SaveEditedConfig {
+                targets,
+                ping_interval_ms,
+            } => {
+                let config = Config {
+                    targets,
+                    ping_interval_ms,
+                    ..self.config.clone()
+                };
+                let message = match self.save_config(&config) {
+                    Ok(path) => format!("saved to {}, applies on restart", path.display()),
+                    Err(e) => format!("save failed: {}", e),
+                };
+                self.events.push(None, EventKind::ConfigSaved, message);
+            }
+            MonitorCommand::ClearHistory(index) => {
+                if let Some(target_stats) = self.targets.get_mut(index) {
+                    let message = format!(
+                        "dropped {} pings and {} failures",
+                        target_stats.ping_history.len(),
+                        target_stats.failure_log.len()
+                    );
+                    target_stats.clear_history();
+                    self.events.push(
+                        Some(&target_stats.target),
+                        EventKind::HistoryCleared,
+                        message,
+                    );
+                }
+            }
+        }
+        false
+    }
+
+    // SaveConfig writes the targets as they are now, including expanded ranges and added
+    // gateways
+    fn save_config(&self, config: &Config) -> Result<std::path::PathBuf> {
+        crate::config::save_config(config, self.profile.as_deref())?;
+        crate::config::get_config_file(self.profile.as_deref())
+    }
+
+    pub fn events(&self) -> &VecDeque<Event> {
+        &self.events.events
+    }
+
+    pub fn note_restart(&mut self, message: String) {
+        self.events.push(None, EventKind::MonitorRestarted, message);
+    }
+
+    pub fn local_outage(&self) -> Option<&LocalOutage> {
+        self.local_outage.as_ref()
+    }
+
+    // Re-checks the local network, keeping the outage's start time while it lasts
+    fn check_local_network(&mut self) -> bool {
+        self.local_outage = match (self.link.check(), self.local_outage.take()) {
+            (None, None) => None,
+            (None, Some(_)) => {
+                self.events.push(
+                    None,
+                    EventKind::LocalNetworkRestored,
+                    "restored".to_string(),
+                );
+                None
+            }
+            (Some(reason), Some(outage)) => Some(LocalOutage { reason, ..outage }),
+            (Some(reason), None) => {
+                self.events
+                    .push(None, EventKind::LocalNetworkLost, reason.clone());
+                Some(LocalOutage {
+                    since: clock::now(),
+                    reason,
+                })
+            }
+        };
+        self.local_outage.is_some()
+    }
+
+    pub fn in_flight_probes(&self) -> usize {
+        self.pending_ssh.len() + self.pending_bursts.len() + self.pending_throughput.len()
+    }
+
+    pub fn history_bytes(&self) -> usize {
+        self.targets.iter().map(TargetStats::history_bytes).sum()
+    }
+
+    pub fn hook_error(&self) -> Option<String> {
+        self.result_hook
+            .as_ref()
+            .and_then(ResultHook::last_error)
+            .or_else(|| self.escalation_hook.last_error())
+    }
+
+    // Post-cycle analysis over all targets
+    pub fn evaluate_alerts(&mut self) -> AlertUpdate {
+        self.update_upstream_outages();
+        let was_muted = self.alerts.muted_until().is_some();
+        let update = self.alerts.evaluate(&self.targets);
+        if was_muted && self.alerts.muted_until().is_none() {
+            self.events.push(
+                None,
+                EventKind::AlertsMuted,
+                "mute expired, alerting resumed".to_string(),
+            );
+        }
+        if update.mass_outage.is_some() != self.mass_outage.is_some() {
+            let message = match update.mass_outage {
+                Some(outage) => format!(
+                    "{} of {} targets down at once, likely a local network issue",
+                    outage.down, outage.total
+                ),
+                None => "targets recovering, no longer looks like a local issue".to_string(),
+            };
+            self.events
+                .push(None, EventKind::LikelyLocalOutage, message);
+        }
+        self.mass_outage = update.mass_outage;
+        // Degraded and Down alerts are already in the feed as health changes
+        for alert in update
+            .fired
+            .iter()
+            .filter(|a| a.kind == AlertKind::LatencyJump)
+        {
+            let target = self.targets.iter().find(|t| t.target.ip == alert.ip);
+            self.events.push(
+                target.map(|t| &t.target),
+                EventKind::LatencyJump,
+                alert.message.clone(),
+            );
+        }
+        for escalation in &update.escalations {
+            let Some(target) = self
+                .targets
+                .iter()
+                .find(|t| t.target.ip == escalation.alert.ip)
+            else {
+                continue;
+            };
+            self.events.push(
+                Some(&target.target),
+                EventKind::Escalated,
+                escalation.alert.message.clone(),
+            );
+            if let Some(command) = &escalation.command {
+                self.escalation_hook.fire(
+                    command,
+                    EscalationEvent::new(
+                        &target.target,
+                        escalation.tier,
+                        escalation.down_secs,
+                        &escalation.alert,
+                        target.down_reason().map(str::to_string),
+                        self.escalation_hook.timestamps,
+                    ),
+                );
+            }
+        }
+        update
+    }
+
+    fn jitter_delay(&self) -> Duration {
+        if self.probe_jitter.is_zero() {
+            return Duration::ZERO;
+        }
+        Duration::from_millis(rand::random_range(0..=self.probe_jitter.as_millis() as u64))
+    }
+
+    // Looks up every hostname target that is due, all at once; a lookup that moves a target
+    // to a new address goes in its failure log
+    async fn refresh_dns(&mut self) {
+        let mut handles = Vec::new();
+        for (index, target_stats) in self.targets.iter().enumerate() {
+            if let Some(resolution) = &target_stats.resolution
+                && resolution.due(self.dns_refresh)
+            {
+                let host = target_stats.target.ip.clone();
+                let check = self.dns_check.clone();
+                handles.push(tokio::spawn(async move {
+                    (index, crate::dns::lookup(&host, &check).await)
+                }));
+            }
+        }
+
+        for handle in handles {
+            let Ok((index, (outcome, timing))) = handle.await else {
+                continue;
+            };
+            let target_stats = &mut self.targets[index];
+            let Some(resolution) = target_stats.resolution.as_mut() else {
+                continue;
+            };
+            if let Some(change) = resolution.record(outcome, timing, self.dns_stable_resolutions) {
+                target_stats.add_failure_log(
+                    "DNS".to_string(),
+                    format!("DNS changed: {}", change),
+                    self.history_size,
+                );
+                self.events
+                    .push(Some(&target_stats.target), EventKind::DnsChanged, change);
+            }
+        }
+    }
+
+    // Marks targets with a down target somewhere up their depends_on chain, naming the one
+    // furthest up, which is the outage the rest follow from
+    fn update_upstream_outages(&mut self) {
+        let upstream: Vec<Option<usize>> = (0..self.targets.len())
+            .map(|index| self.down_upstream(index))
+            .collect();
+        for (index, upstream) in upstream.into_iter().enumerate() {
+            let upstream = upstream.map(|i| {
+                let target = &self.targets[i].target;
+                target.name.clone().unwrap_or_else(|| target.ip.clone())
+            });
+            let target_stats = &mut self.targets[index];
+            if upstream.is_some() && upstream != target_stats.upstream_down {
+                let message = format!(
+                    "failures masked while {} is down upstream",
+                    upstream.as_deref().unwrap_or_default()
+                );
+                self.events
+                    .push(Some(&target_stats.target), EventKind::UpstreamDown, message);
+            }
+            target_stats.upstream_down = upstream;
+        }
+    }
+
+    fn down_upstream(&self, index: usize) -> Option<usize> {
+        let mut seen = vec![index];
+        let mut down = None;
+        let mut current = index;
+        while let Some(parent) = self.targets[current]
+            .target
+            .depends_on
+            .as_deref()
+            .and_then(|reference| self.find_target(reference))
+        {
+            // A loop in the config would otherwise go round forever
+            if seen.contains(&parent) {
+                break;
+            }
+            seen.push(parent);
+            if self.targets[parent].health == Health::Down {
+                down = Some(parent);
+            }
+            current = parent;
+        }
+        down
+    }
+
+    // By address or name, like `boxmonitor query`
+    fn find_target(&self, reference: &str) -> Option<usize> {
+        self.targets
+            .iter()
+            .position(|t| t.target.ip == reference || t.target.name.as_deref() == Some(reference))
+            .or_else(|| {
+                self.targets.iter().position(|t| {
+                    t.target
+                        .name
+                        .as_deref()
+                        .is_some_and(|name| name.eq_ignore_ascii_case(reference))
+                })
+            })
+    }
+
+    // Targets whose probes go to the same place as an earlier target's, as (duplicate, first)
+    // pairs. A target that starts or stops sharing an address gets an event
+    fn find_duplicates(&mut self) -> Vec<(usize, usize)> {
+        let mut first_by_address = HashMap::new();
+        let mut duplicates = Vec::new();
+        for index in 0..self.targets.len() {
+            let target_stats = &self.targets[index];
+            let first = target_stats.probe_address().ok().and_then(|addr| {
+                let key = (
+                    addr,
+                    target_stats.target.netns.clone(),
+                    target_stats.probe_interface(),
+                    target_stats.target.multicast.is_some(),
+                );
+                match first_by_address.get(&key) {
+                    Some(&first) => Some(first),
+                    None => {
+                        first_by_address.insert(key, index);
+                        None
+                    }
+                }
+            });
+            if let Some(first) = first {
+                duplicates.push((index, first));
+            }
+
+            let first_name = first.map(|first| {
+                let target = &self.targets[first].target;
+                target.name.clone().unwrap_or_else(|| target.ip.clone())
+            });
+            let target_stats = &mut self.targets[index];
+            if target_stats.duplicate_of == first_name {
+                continue;
+            }
+            let message = match (&first_name, &target_stats.duplicate_of) {
+                (Some(name), _) => {
+                    let addr = target_stats
+                        .probe_address()
+                        .map(|addr| addr.to_string())
+                        .unwrap_or_default();
+                    let sharing = if self.coalesce_duplicates {
+                        "pinged once for both"
+                    } else {
+                        "both are pinged"
+                    };
+                    format!("{} is the same address as {}; {}", addr, name, sharing)
+                }
+                (None, previous) => format!(
+                    "No longer the same address as {}",
+                    previous.as_deref().unwrap_or_default()
+                ),
+            };
+            self.events
+                .push(Some(&target_stats.target), EventKind::DnsChanged, message);
+            target_stats.duplicate_of = first_name;
+        }
+        duplicates
+    }
+
+    pub async fn run_ping_cycle(&mut self) -> Result<()> {
+        self.collect_duplicate_replies();
+        self.collect_bursts().await;
+        self.collect_throughput_tests().await;
+        self.refresh_dns().await;
+        let duplicates = self.find_duplicates();
+        let coalesced: Vec<(usize, usize)> = if self.coalesce_duplicates {
+            duplicates
+        } else {
+            Vec::new()
+        };
+        let mut handles = Vec::new();
+        let mut probes = Vec::new();
+        let mut synthesized = Vec::new();
+
+        for &index in &self.dispatch_order {
+            let target_stats = &mut self.targets[index];
+            // A coalesced duplicate takes a copy of the first target's result instead
+            if target_stats.backing_off() || coalesced.iter().any(|(dup, _)| *dup == index) {
+                continue;
+            }
+            if let Some(synthetic) = &target_stats.target.synthetic {
+                let result = synthetic_result(synthetic, self.ping_cycles);
+                synthesized.push((index, (result, None), None));
+                continue;
+            }
+            let addr = target_stats.probe_address();
+            let multicast = target_stats.target.multicast;
+            let netns = target_stats.target.netns.clone();
+            let interface = target_stats.probe_interface();
+            let identifier = ping_identifier(index);
+            let sequence = surge_ping::PingSequence(target_stats.ping_sequence);
+            target_stats.ping_sequence = target_stats.ping_sequence.wrapping_add(1);
+
+            let client = addr.and_then(|addr| {
+                self.ping_clients
+                    .get(&addr, netns.as_deref(), interface.as_deref())
+                    .map(|client| (client, addr))
+            });
+            // The duplicate watch listens in this namespace only, and a group or broadcast
+            // address is meant to answer more than once
+            if let Ok((client, addr)) = &client
+                && netns.is_none()
+                && multicast.is_none()
+            {
+                probes.push((index, (client.clone(), *addr, identifier, sequence)));
+            }
+
+            let delay = self.jitter_delay();
+            let probe_limit = Arc::clone(&self.probe_limit);
+            let handle = tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                let _permit = probe_limit.acquire_owned().await;
+                let (result, neighbor) = match client {
+                    Ok((client, addr)) => match multicast {
+                        Some(check) => {
+                            let result = multicast_probe(
+                                addr,
+                                netns.as_deref(),
+                                interface.as_deref(),
+                                identifier,
+                                sequence,
+                                check,
+                            )
+                            .await;
+                            ((result, None), None)
+                        }
+                        None => {
+                            let result = ping_target(&client, addr, identifier, sequence).await;
+                            // Asked from this namespace only, so netns targets go without
+                            let neighbor = if result.0.success || netns.is_some() {
+                                None
+                            } else {
+                                crate::neighbor::probe(addr).await
+                            };
+                            (result, neighbor)
+                        }
+                    },
+                    Err(reason) => (
+                        (
+                            PingResult {
+                                timestamp: clock::now(),
+                                latency_ms: None,
+                                success: false,
+                                failure_reason: Some(reason),
+                                warmup: false,
+                                local_outage: false,
+                                rate_limited: false,
+                            },
+                            None,
+                        ),
+                        None,
+                    ),
+                };
+                (index, result, neighbor)
+            });
+            handles.push(handle);
+        }
+
+        let mut results = synthesized;
+        for handle in handles {
+            if let Ok(result) = handle.await {
+                results.push(result);
+            }
+        }
+        for &(duplicate, first) in &coalesced {
+            if let Some((_, (result, _), neighbor)) = results.iter().find(|(i, ..)| *i == first) {
+                let copy = (duplicate, (result.clone(), None), neighbor.clone());
+                results.push(copy);
+            }
+        }
+
+        // Targets sharing an address and socket can send the same sequence, and then their
+        // replies can't be told apart from duplicates
+        let answered: Vec<usize> = results
+            .iter()
+            .filter(|(_, (result, _), _)| result.success)
+            .map(|(index, ..)| *index)
+            .collect();
+        for (index, probe) in &probes {
+            let shared = probes.iter().any(|(other, (_, addr, _, sequence))| {
+                other != index && *addr == probe.1 && *sequence == probe.3
+            });
+            if answered.contains(index) && !shared {
+                self.pending_replies.insert(*index, probe.clone());
+            }
+        }
+
+        // Checked once the pings are back, so a link that dropped mid-cycle still counts;
+        // answered pings prove the network was there and always count
+        let local_outage = self.check_local_network();
+        for (index, (mut result, anomaly), neighbor) in results {
+            if let Some(target_stats) = self.targets.get_mut(index) {
+                target_stats.neighbor = neighbor;
+                if let Some(anomaly) = anomaly {
+                    self.events
+                        .push(Some(&target_stats.target), EventKind::ReplyAnomaly, anomaly);
+                }
+                result.local_outage = local_outage && !result.success;
+                let answered = result.success;
+                let previous_health = target_stats.health;
+                target_stats.add_ping_result(result, self.history_size);
+                if let Some(settings) = &self.down_backoff {
+                    target_stats.update_backoff(settings, self.ping_interval.as_millis() as u64);
+                }
+                if let Some((kind, message)) = health_change(previous_health, target_stats) {
+                    self.events.push(Some(&target_stats.target), kind, message);
+                }
+                // A reply leaves a fresh neighbor entry; a new MAC for the same address means a
+                // different device, an address handed out again, or ARP spoofing
+                if answered
+                    && target_stats.target.netns.is_none()
+                    && target_stats.target.multicast.is_none()
+                    && target_stats.target.synthetic.is_none()
+                    && let Some(mac) = target_stats
+                        .probe_address()
+                        .ok()
+                        .and_then(crate::neighbor::mac_address)
+                    && let Some(previous) = target_stats.record_mac(mac.clone())
+                {
+                    let change = format!("MAC changed from {} to {}", previous, mac);
+                    target_stats.add_failure_log(
+                        "MAC".to_string(),
+                        change.clone(),
+                        self.history_size,
+                    );
+                    self.events
+                        .push(Some(&target_stats.target), EventKind::MacChanged, change);
+                }
+                // Fired after recording, so the event carries the warmup flag
+                if let Some(hook) = &self.result_hook
+                    && let Some(result) = target_stats.ping_history.back()
+                {
+                    hook.fire(ResultEvent::ping(
+                        &target_stats.target,
+                        result,
+                        hook.timestamps,
+                    ));
+                }
+            }
+        }
+
+        self.ping_cycles += 1;
+        self.start_bursts();
+        self.start_throughput_tests();
+        Ok(())
+    }
+
+    fn collect_duplicate_replies(&mut self) {
+        for (index, (client, addr, identifier, sequence)) in self.pending_replies.drain() {
+            let replies = self
+                .duplicate_watch
+                .replies(&client, addr, identifier, sequence);
+            let Some(target_stats) = self.targets.get_mut(index) else {
+                continue;
+            };
+            if replies > 1 {
+                let extra = replies - 1;
+                target_stats.duplicate_replies += extra;
+                let message = match extra {
+                    1 => format!("duplicate reply (DUP!) to ping {}", sequence.0),
+                    n => format!("{} duplicate replies (DUP!) to ping {}", n, sequence.0),
+                };
+                self.events.push(
+                    Some(&target_stats.target),
+                    EventKind::DuplicateReplies,
+                    message,
+                );
+            }
+        }
+    }
+
+    // Bursts start once the cycle's steady pings are in, so they never share a cycle's loss;
+    // a burst still running when the next one is due is not doubled up
+    fn start_bursts(&mut self) {
+        for &index in &self.dispatch_order {
+            let target_stats = &self.targets[index];
+            let target = &target_stats.target;
+            // Multicast targets are counted by responders, which a burst doesn't measure
+            let Some(profile) = target.burst.filter(|_| target.multicast.is_none()) else {
+                continue;
+            };
+            if !self.ping_cycles.is_multiple_of(profile.every_cycles.max(1))
+                || self.pending_bursts.contains_key(&index)
+                || target_stats.backing_off()
+            {
+                continue;
+            }
+
+            let addr = target_stats.probe_address();
+            let netns = target.netns.clone();
+            let interface = target_stats.probe_interface();
+            let loss_penalty_ms = target_stats.settings.loss_penalty_ms;
+            let handle = tokio::spawn(async move {
+                run_burst(
+                    addr,
+                    netns.as_deref(),
+                    interface.as_deref(),
+                    profile.count,
+                    Duration::from_millis(profile.spacing_ms),
+                    loss_penalty_ms,
+                )
+                .await
+            });
+            self.pending_bursts.insert(index, handle);
+        }
+    }
+
+    async fn collect_bursts(&mut self) {
+        let finished: Vec<usize> = self
+            .pending_bursts
+            .iter()
+            .filter(|(_, handle)| handle.is_finished())
+            .map(|(&index, _)| index)
+            .collect();
+
+        for index in finished {
+            if let Some(handle) = self.pending_bursts.remove(&index)
+                && let Ok(result) = handle.await
+                && let Some(target_stats) = self.targets.get_mut(index)
+            {
+                target_stats.add_burst_result(result, self.history_size);
+            }
+        }
+    }
+
+    // A test reuses the target's persistent session, which the SSH probes then go without until
+    // it is back; none starts while a probe has the session
+    fn start_throughput_tests(&mut self) {
+        for &index in &self.dispatch_order {
+            let target_stats = &self.targets[index];
+            let (Some(test), Some(user), Some(port)) = (
+                target_stats.target.throughput,
+                target_stats.target.ssh_user.clone(),
+                target_stats.target.ssh_port,
+            ) else {
+                continue;
+            };
+            if !target_stats.throughput_due()
+                || self.pending_throughput.contains_key(&index)
+                || self.pending_ssh.contains_key(&index)
+            {
+                continue;
+            }
+            let Ok(addr) = target_stats.ssh_address(port) else {
+                continue;
+            };
+
+            let session = self.ssh_sessions.remove(&index);
+            let timeout = self.ssh_timeout;
+            let key_file = self.ssh_key_file.clone();
+            let handle = tokio::task::spawn_blocking(move || {
+                upload_test(
+                    session,
+                    addr,
+                    &user,
+                    key_file.as_deref(),
+                    timeout,
+                    test.bytes,
+                )
+            });
+            self.pending_throughput.insert(index, handle);
+        }
+    }
+
+    async fn collect_throughput_tests(&mut self) {
+        let finished: Vec<usize> = self
+            .pending_throughput
+            .iter()
+            .filter(|(_, handle)| handle.is_finished())
+            .map(|(&index, _)| index)
+            .collect();
+
+        for index in finished {
+            let outcome = match self.pending_throughput.remove(&index) {
+                Some(handle) => handle.await,
+                None => continue,
+            };
+            let (result, session) = outcome.unwrap_or_else(|e| {
+                (
+                    ThroughputResult {
+                        timestamp: clock::now(),
+                        bytes: 0,
+                        mb_per_sec: None,
+                        error: Some(format!("Throughput test panicked: {}", e)),
+                    },
+                    None,
+                )
+            });
+            // A session opened by a probe in the meantime is the one kept
+            if self.ssh_persistent
+                && let Some(session) = session
+            {
+                self.ssh_sessions.entry(index).or_insert(session);
+            }
+            if let Some(target_stats) = self.targets.get_mut(index) {
+                target_stats.add_throughput_result(result, self.history_size);
+            }
+        }
+    }
+
+    pub async fn run_ssh_cycle(&mut self) -> Result<()> {
+        let mut results = self.collect_ssh_probes().await;
+        let mut handles = Vec::new();
+
+        for &index in &self.dispatch_order {
+            let target_stats = &self.targets[index];
+            if target_stats.target.ssh_port.is_some() && target_stats.target.ssh_user.is_some() {
+                // The persistent session is out with a throughput test
+                if self.ssh_persistent && self.pending_throughput.contains_key(&index) {
+                    continue;
+                }
+                if self.pending_ssh.contains_key(&index) {
+                    self.events.push(
+                        Some(&target_stats.target),
+                        EventKind::ProbeSkipped,
+                        "SSH probe overlap skipped, the previous probe is still running"
+                            .to_string(),
+                    );
+                    continue;
+                }
+                let port = target_stats.target.ssh_port.unwrap_or(22);
+                let addr = match target_stats.ssh_address(port) {
+                    Ok(addr) => addr,
+                    Err(reason) => {
+                        handles.push((
+                            index,
+                            tokio::spawn(async move { (index, SshResult::failed(reason), None) }),
+                        ));
+                        continue;
+                    }
+                };
+                let user = target_stats.target.ssh_user.clone().unwrap();
+                let timeout = self.ssh_timeout;
+                let delay = self.jitter_delay();
+                let probe_limit = Arc::clone(&self.probe_limit);
+
+                let handle = if self.ssh_persistent {
+                    let session = self.ssh_sessions.remove(&index);
+                    let key_file = self.ssh_key_file.clone();
+                    tokio::spawn(async move {
+                        tokio::time::sleep(delay).await;
+                        let _permit = probe_limit.acquire_owned().await;
+                        let probe = tokio::task::spawn_blocking(move || {
+                            ssh_probe_persistent(session, addr, &user, key_file.as_deref(), timeout)
+                        })
+                        .await;
+                        match probe {
+                            Ok((result, session)) => (index, result, session),
+                            Err(e) => (
+                                index,
+                                SshResult::failed(format!("SSH probe panicked: {}", e)),
+                                None,
+                            ),
+                        }
+                    })
+                } else {
+                    tokio::spawn(async move {
+                        tokio::time::sleep(delay).await;
+                        let _permit = probe_limit.acquire_owned().await;
+                        (index, ssh_test(addr, &user, timeout).await, None)
+                    })
+                };
+                handles.push((index, handle));
+            }
+        }
+
+        // Probes get the jitter plus a timeout each for connecting and the handshake; any still
+        // going are left to run rather than holding up monitoring, and land in a later cycle
+        let deadline = tokio::time::Instant::now()
+            + self.probe_jitter
+            + self.ssh_timeout * 2
+            + SSH_OVERRUN_GRACE;
+        for (index, mut handle) in handles {
+            match tokio::time::timeout_at(deadline, &mut handle).await {
+                Ok(Ok(result)) => results.push(result),
+                Ok(Err(_)) => {}
+                Err(_) => {
+                    self.pending_ssh.insert(index, handle);
+                }
+            }
+        }
+
+        let local_outage = self.check_local_network();
+        for (index, mut result, session) in results {
+            if let Some(target_stats) = self.targets.get_mut(index) {
+                result.local_outage = local_outage && !result.success;
+                if let Some(session) = session {
+                    self.ssh_sessions.insert(index, session);
+                }
+                if let Some(hook) = &self.result_hook {
+                    hook.fire(ResultEvent::ssh(
+                        &target_stats.target,
+                        &result,
+                        hook.timestamps,
+                    ));
+                }
+                let previous_health = target_stats.health;
+                target_stats.add_ssh_result(result, self.history_size);
+                if let Some((kind, message)) = health_change(previous_health, target_stats) {
+                    self.events.push(Some(&target_stats.target), kind, message);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn collect_ssh_probes(&mut self) -> Vec<SshOutcome> {
+        let finished: Vec<usize> = self
+            .pending_ssh
+            .iter()
+            .filter(|(_, handle)| handle.is_finished())
+            .map(|(&index, _)| index)
+            .collect();
+
+        let mut results = Vec::with_capacity(finished.len());
+        for index in finished {
+            if let Some(handle) = self.pending_ssh.remove(&index)
+                && let Ok(result) = handle.await
+            {
+                results.push(result);
+            }
+        }
+        results
+    }
+}
+
+// How a target's health moved, for the event feed; settling to Healthy at startup isn't news
+pub fn health_change(previous: Health, target_stats: &TargetStats) -> Option<(EventKind, String)> {
+    let current = target_stats.health;
+    match (previous, current) {
+        _ if previous == current => None,
+        (Health::Unknown, Health::Healthy) | (_, Health::Unknown) => None,
+        (_, Health::Down) => Some((
+            EventKind::Down,
+            target_stats.down_reason().unwrap_or("no reply").to_string(),
+        )),
+        (_, Health::Degraded) => Some((
+            EventKind::Degraded,
+            target_stats.degraded_reasons().join(", "),
+        )),
+        (_, Health::Healthy) => Some((EventKind::Recovered, format!("was {}", previous.label()))),
+    }
+}
+
+// Checks that a reply really answers this probe: an echo reply, from the address that was
+// pinged, carrying our identifier and sequence. surge-ping already matches replies on the
+// sender's address, so anything caught here came from a raw socket header that disagrees with
+// it, or from something crafting replies on the wire
+fn validate_reply(
+    packet: &surge_ping::IcmpPacket,
+    addr: std::net::IpAddr,
+    identifier: surge_ping::PingIdentifier,
+    sequence: surge_ping::PingSequence,
+) -> std::result::Result<(), String> {
+    let (source, icmp_type, echo_reply) = match packet {
+        surge_ping::IcmpPacket::V4(packet) => (
+            std::net::IpAddr::V4(packet.get_source()),
+            packet.get_icmp_type().0,
+            ICMPV4_ECHO_REPLY,
+        ),
+        surge_ping::IcmpPacket::V6(packet) => (
+            std::net::IpAddr::V6(packet.get_source()),
+            packet.get_icmpv6_type().0,
+            ICMPV6_ECHO_REPLY,
+        ),
+    };
+
+    if source != addr {
+        return Err(format!("source mismatch: reply from {}", source));
+    }
+    if icmp_type != echo_reply {
+        return Err(format!("type mismatch: ICMP type {}", icmp_type));
+    }
+    if packet.get_identifier() != identifier {
+        return Err(format!(
+            "identifier mismatch: got {}",
+            packet.get_identifier().into_u16()
+        ));
+    }
+    if packet.get_sequence() != sequence {
+        return Err(format!(
+            "sequence mismatch: got {}",
+            packet.get_sequence().into_u16()
+        ));
+    }
+    Ok(())
+}
+
+// Goes through the same recording as a real ping, so health, alerts, hooks and the failure
+// log all see it
+fn synthetic_result(synthetic: &SyntheticCheck, cycle: u64) -> PingResult {
+    let success = synthetic.succeeds(cycle);
+    PingResult {
+        timestamp: clock::now(),
+        latency_ms: success.then_some(synthetic.latency_ms),
+        success,
+        failure_reason: (!success)
+            .then(|| format!("Synthetic failure (pattern \"{}\")", synthetic.pattern)),
+        warmup: false,
+        local_outage: false,
+        rate_limited: false,
+    }
+}
+
+// A reply that fails validation is discarded and the probe counts as lost; the anomaly comes
+// back alongside so it can go to the event feed
+async fn ping_target(
+    client: &surge_ping::Client,
+    addr: std::net::IpAddr,
+    identifier: surge_ping::PingIdentifier,
+    sequence: surge_ping::PingSequence,
+) -> (PingResult, Option<String>) {
+    let timestamp = clock::now();
+
+    let mut pinger = client.pinger(addr, identifier).await;
+
+    let failed = |reason: String| PingResult {
+        timestamp,
+        latency_ms: None,
+        success: false,
+        failure_reason: Some(reason),
+        warmup: false,
+        local_outage: false,
+        rate_limited: false,
+    };
+
+    match pinger.ping(sequence, &[]).await {
+        Ok((packet, duration)) => match validate_reply(&packet, addr, identifier, sequence) {
+            Ok(()) => (
+                PingResult {
+                    timestamp,
+                    latency_ms: Some(duration.as_secs_f64() * 1000.0),
+                    success: true,
+                    failure_reason: None,
+                    warmup: false,
+                    local_outage: false,
+                    rate_limited: false,
+                },
+                None,
+            ),
+            Err(anomaly) => (
+                failed(format!("Reply discarded: {}", anomaly)),
+                Some(anomaly),
+            ),
+        },
+        Err(e) => (failed(format!("Ping failed: {}", e)), None),
+    }
+}
+
+// Link-local targets need their socket bound to the zone's interface to be reachable
+pub fn icmp_config(addr: &std::net::IpAddr, interface: Option<&str>) -> surge_ping::Config {
+    let mut builder = surge_ping::Config::builder();
+    if addr.is_ipv6() {
+        builder = builder.kind(surge_ping::ICMP::V6);
+    }
+    if let Some(interface) = interface {
+        builder = builder.interface(interface);
+    }
+    builder.build()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BurstResult {
+    pub sent: usize,
+    pub received: usize,
+    pub loss_percent: f64,
+    pub stats: Option<Statistics>,
+    pub error: Option<String>,
+}
+
+impl BurstResult {
+    fn failed(error: String) -> Self {
+        Self {
+            sent: 0,
+            received: 0,
+            loss_percent: 100.0,
+            stats: None,
+            error: Some(error),
+        }
+    }
+}
+
+const BURST_REPLY_TIMEOUT: Duration = Duration::from_secs(1);
+const SSH_OVERRUN_GRACE: Duration = Duration::from_secs(1);
+
+// A finished SSH probe, with the session to keep for the next one when sessions persist
+type SshOutcome = (usize, SshResult, Option<ssh2::Session>);
+type ThroughputOutcome = (ThroughputResult, Option<ssh2::Session>);
+
+// Fire `count` echo requests `spacing` apart without waiting for replies in between,
+// then summarise loss and latency with the same percentile machinery as steady probing
+pub async fn run_burst(
+    addr: std::result::Result<std::net::IpAddr, String>,
+    netns: Option<&str>,
+    interface: Option<&str>,
+    count: usize,
+    spacing: Duration,
+    loss_penalty_ms: f64,
+) -> BurstResult {
+    let addr = match addr {
+        Ok(addr) => addr,
+        Err(e) => return BurstResult::failed(e),
+    };
+
+    let config = icmp_config(&addr, interface);
+    let client = match netns {
+        Some(name) => crate::netns::in_netns(name, move || surge_ping::Client::new(&config)),
+        None => surge_ping::Client::new(&config)
+            .map_err(|e| format!("Failed to create ping client: {}", e)),
+    };
+    let client = match client {
+        Ok(client) => client,
+        Err(e) => return BurstResult::failed(e),
+    };
+
+    // Outside every target's identifier, so a burst's replies are never taken for a probe's
+    let identifier = BURST_PING_IDENTIFIER;
+    let mut handles = Vec::with_capacity(count);
+
+    for seq in 0..count {
+        let client = client.clone();
+        handles.push(tokio::spawn(async move {
+            let mut pinger = client.pinger(addr, identifier).await;
+            pinger.timeout(BURST_REPLY_TIMEOUT);
+            let sequence = surge_ping::PingSequence(seq as u16);
+            // Replies that fail validation count as lost, as in steady probing
+            pinger
+                .ping(sequence, &[])
+                .await
+                .ok()
+                .filter(|(packet, _)| validate_reply(packet, addr, identifier, sequence).is_ok())
+                .map(|(_, duration)| duration.as_secs_f64() * 1000.0)
+        }));
+        tokio::time::sleep(spacing).await;
+    }
+
+    let mut latencies = Vec::with_capacity(count);
+    for handle in handles {
+        if let Ok(Some(latency)) = handle.await {
+            latencies.push(latency);
+        }
+    }
+
+    let received = latencies.len();
+    BurstResult {
+        sent: count,
+        received,
+        loss_percent: (count - received) as f64 / count.max(1) as f64 * 100.0,
+        stats: (!latencies.is_empty())
+            .then(|| calculate_statistics(&latencies, count, loss_penalty_ms, None, None)),
+        error: None,
+    }
+}
+
+impl SshResult {
+    fn failed(reason: String) -> Self {
+        Self {
+            timestamp: clock::now(),
+            connection_time_ms: None,
+            success: false,
+            failure_reason: Some(reason),
+            local_outage: false,
+            kind: SshProbeKind::Connect,
+        }
+    }
+}
+
+// With an established session, a probe is a channel open/close round trip on the existing
+// connection rather than a fresh TCP connect and handshake. Channels need an authenticated
+// session, so new sessions authenticate (see connect_ssh_session). A dead session is recorded
+// as a failure and dropped; the next cycle reconnects, and that sample is marked as a connect.
+fn ssh_probe_persistent(
+    session: Option<ssh2::Session>,
+    addr: std::net::SocketAddr,
+    user: &str,
+    key_file: Option<&Path>,
+    timeout: Duration,
+) -> (SshResult, Option<ssh2::Session>) {
+    let start = Instant::now();
+    let timestamp = clock::now();
+
+    let kind = match session {
+        Some(_) => SshProbeKind::Channel,
+        None => SshProbeKind::Connect,
+    };
+    let outcome = match session {
+        Some(session) => match session.channel_session() {
+            Ok(mut channel) => {
+                let _ = channel.close();
+                Ok(session)
+            }
+            Err(e) => Err(format!("SSH session lost: {}", e)),
+        },
+        None => connect_ssh_session(addr, user, key_file, timeout),
+    };
+
+    match outcome {
+        Ok(session) => (
+            SshResult {
+                timestamp,
+                connection_time_ms: Some(start.elapsed().as_secs_f64() * 1000.0),
+                success: true,
+                failure_reason: None,
+                local_outage: false,
+                kind,
+            },
+            Some(session),
+        ),
+        Err(reason) => (
+            SshResult {
+                timestamp,
+                connection_time_ms: None,
+                success: false,
+                failure_reason: Some(reason),
+                local_outage: false,
+                kind,
+            },
+            None,
+        ),
+    }
+}
+
+// Authenticates with `key_file` when one is set, otherwise through the ssh-agent. Under sudo
+// the agent's socket (SSH_AUTH_SOCK) is normally not passed on, so agent auth fails there
+pub fn connect_ssh_session(
+    addr: std::net::SocketAddr,
+    user: &str,
+    key_file: Option<&Path>,
+    timeout: Duration,
+) -> std::result::Result<ssh2::Session, String> {
+    let stream = std::net::TcpStream::connect_timeout(&addr, timeout)
+        .map_err(|e| format!("TCP connection failed: {}", e))?;
+
+    let mut session =
+        ssh2::Session::new().map_err(|e| format!("Failed to create SSH session: {}", e))?;
+    session.set_timeout(timeout.as_millis() as u32);
+    session.set_tcp_stream(stream);
+    session
+        .handshake()
+        .map_err(|e| format!("SSH handshake failed: {}", e))?;
+    match key_file {
+        Some(key_file) => session
+            .userauth_pubkey_file(user, None, key_file, None)
+            .map_err(|e| {
+                format!(
+                    "SSH key authentication with {} failed: {}",
+                    key_file.display(),
+                    e
+                )
+            })?,
+        None => session.userauth_agent(user).map_err(|e| {
+            match std::env::var_os("SSH_AUTH_SOCK") {
+                Some(_) => format!("SSH agent authentication failed: {}", e),
+                None => format!(
+                    "SSH agent authentication failed: {} (no SSH_AUTH_SOCK; under sudo, set ssh_key_file)",
+                    e
+                ),
+            }
+        })?,
+    }
+
+    Ok(session)
+}
+
+async fn ssh_test(addr: std::net::SocketAddr, _user: &str, timeout: Duration) -> SshResult {
+    let start = Instant::now();
+    let timestamp = clock::now();
+
+    // Blocking, like the persistent probe; the connect and the handshake each give up after
+    // `timeout`, so the probe is done once the connection really is
+    let outcome = tokio::task::spawn_blocking(move || {
+        let stream = std::net::TcpStream::connect_timeout(&addr, timeout)
+            .map_err(|e| format!("TCP connection failed: {}", e))?;
+        let mut session =
+            ssh2::Session::new().map_err(|e| format!("Failed to create SSH session: {}", e))?;
+        session.set_timeout(timeout.as_millis() as u32);
+        session.set_tcp_stream(stream);
+        session
+            .handshake()
+            .map_err(|e| format!("SSH handshake failed: {}", e))
+    })
+    .await
+    .unwrap_or_else(|e| Err(format!("SSH probe panicked: {}", e)));
+
+    match outcome {
+        Ok(()) => SshResult {
+            timestamp,
+            connection_time_ms: Some(start.elapsed().as_secs_f64() * 1000.0),
+            success: true,
+            failure_reason: None,
+            local_outage: false,
+            kind: SshProbeKind::Connect,
+        },
+        Err(reason) => SshResult {
+            timestamp,
+            connection_time_ms: None,
+            success: false,
+            failure_reason: Some(reason),
+            local_outage: false,
+            kind: SshProbeKind::Connect,
+        },
+    }
+}
+
+// `values` run oldest to newest. With recency weighting the mean and percentiles lean towards
+// the latest samples; min, max, jitter and the success rate still treat every sample alike.
+// Outliers are left out of everything but the success rate, where they still count as replies
+fn calculate_statistics(
+    values: &[f64],
+    total_count: usize,
+    loss_penalty_ms: f64,
+    recency_weighting: Option<RecencyWeighting>,
+    outlier_filter: Option<OutlierFilter>,
+) -> Statistics {
+    let replies = values.len();
+    let (kept, outlier_limit) = match outlier_filter.and_then(|f| outlier_bounds(f, values)) {
+        Some((low, high)) => {
+            let kept: Vec<f64> = values
+                .iter()
+                .copied()
+                .filter(|v| (low..=high).contains(v))
+                .collect();
+            // A cap below every sample would leave nothing to describe
+            if kept.is_empty() {
+                (values.to_vec(), None)
+            } else {
+                (kept, Some(high))
+            }
+        }
+        None => (values.to_vec(), None),
+    };
+    let outliers_excluded = replies - kept.len();
+    let values = &kept[..];
+
+    let weights: Vec<f64> = match recency_weighting {
+        // A half-life under one sample would leave only the latest counting
+        Some(weighting) => {
+            let half_life = weighting.half_life_samples.max(1.0);
+            (0..values.len())
+                .map(|i| 0.5f64.powf((values.len() - 1 - i) as f64 / half_life))
+                .collect()
+        }
+        None => vec![1.0; values.len()],
+    };
+    let mut sorted: Vec<(f64, f64)> = values.iter().copied().zip(weights).collect();
+    sorted.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    let total_weight = sorted.iter().map(|(_, w)| w).sum::<f64>();
+    let mean = sorted.iter().map(|(v, w)| v * w).sum::<f64>() / total_weight;
+    let median = weighted_percentile(&sorted, 50.0);
+    let min = sorted.first().map_or(0.0, |(v, _)| *v);
+    let max = sorted.last().map_or(0.0, |(v, _)| *v);
+    let success_rate = (replies as f64 / total_count as f64) * 100.0;
+    let effective_latency = mean + (100.0 - success_rate) * loss_penalty_ms;
+    let (success_ci_low, success_ci_high) = wilson_interval(replies, total_count);
+    let jitter = if values.len() > 1 {
+        values.windows(2).map(|w| (w[1] - w[0]).abs()).sum::<f64>() / (values.len() - 1) as f64
+    } else {
+        0.0
+    };
+
+    Statistics {
+        mean,
+        median,
+        min,
+        max,
+        p25: weighted_percentile(&sorted, 25.0),
+        p75: weighted_percentile(&sorted, 75.0),
+        p90: weighted_percentile(&sorted, 90.0),
+        p95: weighted_percentile(&sorted, 95.0),
+        p99: weighted_percentile(&sorted, 99.0),
+        success_rate,
+        total_count,
+        effective_latency,
+        jitter,
+        success_ci_low,
+        success_ci_high,
+        outliers_excluded,
+        outlier_limit,
+    }
+}
+
+// The range of latencies that aren't outliers. The quartiles need a few samples to mean
+// anything, so fewer than 4 keeps them all
+fn outlier_bounds(filter: OutlierFilter, values: &[f64]) -> Option<(f64, f64)> {
+    match filter {
+        OutlierFilter::Cap { max_ms } => Some((f64::NEG_INFINITY, max_ms)),
+        OutlierFilter::Iqr { .. } if values.len() < 4 => None,
+        OutlierFilter::Iqr { k } => {
+            let mut sorted = values.to_vec();
+            sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let (q1, q3) = (percentile(&sorted, 25.0), percentile(&sorted, 75.0));
+            let spread = (q3 - q1) * k;
+            Some((q1 - spread, q3 + spread))
+        }
+    }
+}
+
+// `percentile` over (value, weight) pairs sorted by value. Each sample sits at the weight below
+// it, scaled so the smallest is at 0 and the largest at 1, which with equal weights puts
+// sample k at k/(n-1) and gives the same answers as `percentile`
+fn weighted_percentile(sorted: &[(f64, f64)], p: f64) -> f64 {
+    let Some(&(last, last_weight)) = sorted.last() else {
+        return 0.0;
+    };
+    let span = sorted.iter().map(|(_, w)| w).sum::<f64>() - last_weight;
+    if sorted.len() == 1 || span <= 0.0 {
+        return last;
+    }
+
+    let target = p / 100.0 * span;
+    let mut below = 0.0;
+    for pair in sorted.windows(2) {
+        let ((lower, weight), (upper, _)) = (pair[0], pair[1]);
+        if below + weight >= target {
+            let fraction = (target - below) / weight;
+            return lower * (1.0 - fraction) + upper * fraction;
+        }
+        below += weight;
+    }
+    last
+}
+
+// Unlike the normal approximation, stays within 0-100% and stays wide for a perfect record
+// over few samples: 3 of 3 gives 44-100%
+fn wilson_interval(successes: usize, total: usize) -> (f64, f64) {
+    const Z: f64 = 1.96;
+    if total == 0 {
+        return (0.0, 100.0);
+    }
+    let n = total as f64;
+    let p = successes as f64 / n;
+    let z2 = Z * Z;
+    let center = p + z2 / (2.0 * n);
+    let margin = Z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    let denominator = 1.0 + z2 / n;
+    (
+        ((center - margin) / denominator * 100.0).max(0.0),
+        ((center + margin) / denominator * 100.0).min(100.0),
+    )
+}
+
+pub fn percentile(sorted_values: &[f64], p: f64) -> f64 {
+    if sorted_values.is_empty() {
+        return 0.0;
+    }
+
+    if sorted_values.len() == 1 {
+        return sorted_values[0];
+    }
+
+    let index = (p / 100.0) * (sorted_values.len() - 1) as f64;
+    let lower = index.floor() as usize;
+    let upper = index.ceil() as usize;
 
     if lower == upper {
         sorted_values[lower]
@@ -363,3 +2803,176 @@ fn percentile(sorted_values: &[f64], p: f64) -> f64 {
         sorted_values[lower] * (1.0 - weight) + sorted_values[upper] * weight
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    pub(crate) fn test_target(ip: &str) -> TargetStats {
+        let mut settings = StatsSettings::from_config(&Config::default());
+        settings.warmup_samples = 0;
+        let target = Target {
+            ip: ip.to_string(),
+            ..Default::default()
+        };
+        TargetStats::new(target, 1000, settings)
+    }
+
+    pub(crate) fn ping(success: bool) -> PingResult {
+        PingResult {
+            timestamp: clock::now(),
+            latency_ms: success.then_some(10.0),
+            success,
+            failure_reason: (!success).then(|| "Request timeout".to_string()),
+            warmup: false,
+            local_outage: false,
+            rate_limited: false,
+        }
+    }
+
+    async fn advance_secs(secs: u64) {
+        tokio::time::advance(Duration::from_secs(secs)).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn windowed_stats_cover_only_recent_samples() {
+        let mut target = test_target("10.0.0.1");
+        for _ in 0..5 {
+            target.add_ping_result(ping(false), 1000);
+            advance_secs(10).await;
+        }
+        advance_secs(120).await;
+        for _ in 0..5 {
+            target.add_ping_result(ping(true), 1000);
+            advance_secs(1).await;
+        }
+
+        let windowed = target.windowed(chrono::Duration::seconds(60));
+        assert_eq!(windowed.ping_history.len(), 5);
+        assert_eq!(windowed.ping_stats.unwrap().success_rate, 100.0);
+        assert_eq!(target.ping_stats.unwrap().success_rate, 50.0);
+    }
+
+    #[test]
+    fn latest_samples_trims_the_chart_copy_only() {
+        let mut target = test_target("10.0.0.1");
+        for i in 0..10 {
+            target.add_ping_result(ping(i % 2 == 0), 1000);
+        }
+        let latest = target.latest_samples(4);
+        assert_eq!(latest.ping_history.len(), 4);
+        assert_eq!(target.ping_history.len(), 10);
+        assert_eq!(latest.failure_log.len(), target.failure_log.len());
+        assert_eq!(latest.failures_logged, target.failures_logged);
+        assert_eq!(
+            latest.ping_stats.unwrap().success_rate,
+            target.ping_stats.unwrap().success_rate
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn snapshots_follow_the_snapshot_interval() {
+        let mut target = test_target("10.0.0.1");
+        let interval = target.settings.snapshot_interval.num_seconds() as u64;
+        target.add_ping_result(ping(true), 1000);
+        assert_eq!(target.stats_snapshots.len(), 1);
+
+        advance_secs(interval / 2).await;
+        target.add_ping_result(ping(true), 1000);
+        assert_eq!(target.stats_snapshots.len(), 1);
+
+        advance_secs(interval / 2 + 1).await;
+        target.add_ping_result(ping(true), 1000);
+        assert_eq!(target.stats_snapshots.len(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_full_retention_ago_is_still_found() {
+        let mut target = test_target("10.0.0.1");
+        let interval = target.settings.snapshot_interval;
+        let retention = target.settings.snapshot_retention;
+        for _ in 0..retention * 2 {
+            target.add_ping_result(ping(true), 1000);
+            advance_secs(interval.num_seconds() as u64).await;
+        }
+        target.add_ping_result(ping(true), 1000);
+
+        let span = interval * retention as i32;
+        assert_eq!(target.stats_snapshots.len(), retention + 1);
+        let found = target.snapshot_from(span).unwrap();
+        assert!(clock::now() - found.timestamp >= span);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn down_needs_consecutive_failures() {
+        let mut target = test_target("10.0.0.1");
+        let down_after = target.settings.health.down_after;
+        for _ in 0..20 {
+            target.add_ping_result(ping(true), 1000);
+            advance_secs(1).await;
+        }
+        target.add_ping_result(ping(false), 1000);
+        assert_eq!(target.health, Health::Degraded);
+        advance_secs(1).await;
+        target.add_ping_result(ping(true), 1000);
+        assert_eq!(target.health, Health::Healthy);
+
+        advance_secs(1).await;
+        let first_failure = clock::now();
+        for _ in 0..down_after - 1 {
+            target.add_ping_result(ping(false), 1000);
+            advance_secs(1).await;
+        }
+        assert_eq!(target.health, Health::Degraded);
+        target.add_ping_result(ping(false), 1000);
+        assert_eq!(target.health, Health::Down);
+        assert_eq!(target.down_since, Some(first_failure));
+    }
+
+    #[test]
+    fn reconnects_stay_out_of_persistent_ssh_latency() {
+        let mut target = test_target("10.0.0.1");
+        let ssh = |ms: f64, kind: SshProbeKind| SshResult {
+            timestamp: clock::now(),
+            connection_time_ms: Some(ms),
+            success: true,
+            failure_reason: None,
+            local_outage: false,
+            kind,
+        };
+        target.add_ssh_result(ssh(300.0, SshProbeKind::Connect), 1000);
+        assert_eq!(target.ssh_stats.as_ref().unwrap().mean, 300.0);
+
+        for _ in 0..4 {
+            target.add_ssh_result(ssh(5.0, SshProbeKind::Channel), 1000);
+        }
+        target.add_ssh_result(ssh(300.0, SshProbeKind::Connect), 1000);
+        let stats = target.ssh_stats.as_ref().unwrap();
+        assert_eq!(stats.mean, 5.0);
+        assert_eq!(stats.max, 5.0);
+        assert_eq!(stats.success_rate, 100.0);
+    }
+
+    #[test]
+    fn ping_identifiers_are_distinct_per_target() {
+        let identifiers: HashSet<u16> = (0..MAX_PING_TARGETS)
+            .map(|index| ping_identifier(index).0)
+            .collect();
+        assert_eq!(identifiers.len(), MAX_PING_TARGETS);
+    }
+
+    #[test]
+    fn ping_identifiers_wrap_at_max_targets() {
+        assert_eq!(ping_identifier(MAX_PING_TARGETS).0, ping_identifier(0).0);
+        assert_eq!(
+            ping_identifier(MAX_PING_TARGETS + 7).0,
+            ping_identifier(7).0
+        );
+    }
+
+    #[test]
+    fn burst_identifier_is_not_a_target_identifier() {
+        assert!((0..MAX_PING_TARGETS).all(|index| ping_identifier(index) != BURST_PING_IDENTIFIER));
+    }
+}
@@ -0,0 +1,207 @@
+use crate::clock;
+use crate::config::MulticastCheck;
+use crate::monitor::{PingResult, icmp_config};
+use socket2::Type as SockType;
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+use surge_ping::{AsyncSocket, PingIdentifier, PingSequence};
+
+const ICMPV4_ECHO_REQUEST: u8 = 8;
+pub const ICMPV4_ECHO_REPLY: u8 = 0;
+const ICMPV6_ECHO_REQUEST: u8 = 128;
+pub const ICMPV6_ECHO_REPLY: u8 = 129;
+
+// One echo request to a group or broadcast address, counting the distinct hosts that answer.
+// The latency is the time to the first reply
+pub async fn multicast_probe(
+    addr: IpAddr,
+    netns: Option<&str>,
+    interface: Option<&str>,
+    identifier: PingIdentifier,
+    sequence: PingSequence,
+    check: MulticastCheck,
+) -> PingResult {
+    let timestamp = clock::now();
+
+    let (success, latency_ms, failure_reason) =
+        match count_responders(addr, netns, interface, identifier, sequence, check).await {
+            Ok((responders, first_reply)) if responders >= check.expected_responders => {
+                (true, first_reply, None)
+            }
+            Ok((responders, _)) => (
+                false,
+                None,
+                Some(format!(
+                    "Multicast: {} of {} expected responders answered",
+                    responders, check.expected_responders
+                )),
+            ),
+            Err(reason) => (false, None, Some(reason)),
+        };
+
+    PingResult {
+        timestamp,
+        latency_ms,
+        success,
+        failure_reason,
+        warmup: false,
+        local_outage: false,
+        rate_limited: false,
+    }
+}
+
+// Uses its own socket rather than the shared ping client, which only routes replies coming
+// from the address that was pinged
+async fn count_responders(
+    addr: IpAddr,
+    netns: Option<&str>,
+    interface: Option<&str>,
+    identifier: PingIdentifier,
+    sequence: PingSequence,
+    check: MulticastCheck,
+) -> Result<(usize, Option<f64>), String> {
+    let config = icmp_config(&addr, interface);
+    let socket = match netns {
+        Some(name) => crate::netns::in_netns(name, move || AsyncSocket::new(&config))?,
+        None => {
+            AsyncSocket::new(&config).map_err(|e| format!("Multicast socket unavailable: {}", e))?
+        }
+    };
+    if addr.is_ipv4() {
+        enable_broadcast(&socket).map_err(|e| format!("Broadcast not permitted: {}", e))?;
+    }
+    // Set rather than left to the system default, which is 1 on Linux but can be changed
+    if addr.is_multicast() {
+        limit_multicast_hops(&socket, addr).map_err(|e| format!("Multicast TTL not set: {}", e))?;
+    }
+
+    let mut packet = echo_request(addr, identifier, sequence);
+    let start = Instant::now();
+    socket
+        .send_to(&mut packet, &SocketAddr::new(addr, 0))
+        .await
+        .map_err(|e| format!("Multicast send failed: {}", e))?;
+
+    let deadline = start + Duration::from_millis(check.listen_ms);
+    let mut responders = HashSet::new();
+    let mut first_reply = None;
+    let mut buf = [0u8; 2048];
+
+    while responders.len() < check.expected_responders {
+        let Some(remaining) = deadline.checked_duration_since(Instant::now()) else {
+            break;
+        };
+        let (len, from) = match tokio::time::timeout(remaining, socket.recv_from(&mut buf)).await {
+            Ok(Ok(received)) => received,
+            Ok(Err(e)) => return Err(format!("Multicast receive failed: {}", e)),
+            Err(_) => break,
+        };
+
+        if is_echo_reply(&buf[..len], addr, identifier, sequence, socket.get_type())
+            && responders.insert(from.ip())
+        {
+            first_reply.get_or_insert(start.elapsed().as_secs_f64() * 1000.0);
+        }
+    }
+
+    Ok((responders.len(), first_reply))
+}
+
+fn enable_broadcast(socket: &AsyncSocket) -> std::io::Result<()> {
+    let enable: libc::c_int = 1;
+    let result = unsafe {
+        libc::setsockopt(
+            socket.get_native_sock(),
+            libc::SOL_SOCKET,
+            libc::SO_BROADCAST,
+            &enable as *const libc::c_int as *const libc::c_void,
+            std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+        )
+    };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+// A TTL (hop limit for IPv6) of 1 keeps requests on the local network
+fn limit_multicast_hops(socket: &AsyncSocket, addr: IpAddr) -> std::io::Result<()> {
+    let hops: libc::c_int = 1;
+    let (level, option) = match addr {
+        IpAddr::V4(_) => (libc::IPPROTO_IP, libc::IP_MULTICAST_TTL),
+        IpAddr::V6(_) => (libc::IPPROTO_IPV6, libc::IPV6_MULTICAST_HOPS),
+    };
+    let result = unsafe {
+        libc::setsockopt(
+            socket.get_native_sock(),
+            level,
+            option,
+            &hops as *const libc::c_int as *const libc::c_void,
+            std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+        )
+    };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+// The kernel fills in the ICMPv6 checksum, and the identifier and checksum on unprivileged
+// ICMP sockets
+fn echo_request(addr: IpAddr, identifier: PingIdentifier, sequence: PingSequence) -> Vec<u8> {
+    let kind = match addr {
+        IpAddr::V4(_) => ICMPV4_ECHO_REQUEST,
+        IpAddr::V6(_) => ICMPV6_ECHO_REQUEST,
+    };
+
+    let mut packet = vec![kind, 0, 0, 0];
+    packet.extend(identifier.0.to_be_bytes());
+    packet.extend(sequence.0.to_be_bytes());
+    packet.extend([0u8; 8]);
+
+    if addr.is_ipv4() {
+        let checksum = icmp_checksum(&packet);
+        packet[2..4].copy_from_slice(&checksum.to_be_bytes());
+    }
+    packet
+}
+
+fn icmp_checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = data
+        .chunks(2)
+        .map(|pair| u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)]) as u32)
+        .sum();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+fn is_echo_reply(
+    buf: &[u8],
+    addr: IpAddr,
+    identifier: PingIdentifier,
+    sequence: PingSequence,
+    sock_type: SockType,
+) -> bool {
+    // Raw IPv4 sockets deliver the IP header too; a raw socket also sees every other
+    // ICMP message on the host, so replies are matched on identifier as well as sequence
+    let (icmp, reply_kind) = match addr {
+        IpAddr::V4(_) if sock_type == SockType::RAW => {
+            let header_len = buf.first().map_or(0, |b| (b & 0x0f) as usize * 4);
+            (buf.get(header_len..).unwrap_or(&[]), ICMPV4_ECHO_REPLY)
+        }
+        IpAddr::V4(_) => (buf, ICMPV4_ECHO_REPLY),
+        IpAddr::V6(_) => (buf, ICMPV6_ECHO_REPLY),
+    };
+    if icmp.len() < 8 || icmp[0] != reply_kind {
+        return false;
+    }
+
+    // Unprivileged sockets rewrite the identifier and only receive their own replies
+    let identifier_matches = sock_type != SockType::RAW || icmp[4..6] == identifier.0.to_be_bytes();
+    identifier_matches && icmp[6..8] == sequence.0.to_be_bytes()
+}
@@ -0,0 +1,270 @@
+use crate::clock;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+
+// Whether a target that stopped answering pings still answers ARP, which tells a host that is
+// off apart from one that only drops ICMP
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NeighborCheck {
+    pub timestamp: DateTime<Utc>,
+    // How long the ARP reply took; None when there was none
+    pub reply_ms: Option<f64>,
+    pub error: Option<String>,
+}
+
+// Only IPv4 targets on a directly connected Ethernet-like subnet can be asked; anything else
+// gives None. Needs root, like the pings
+#[cfg(target_os = "linux")]
+pub async fn probe(addr: IpAddr) -> Option<NeighborCheck> {
+    let IpAddr::V4(addr) = addr else {
+        return None;
+    };
+    if addr.is_loopback() {
+        return None;
+    }
+    let interface = linux::on_link_interface(addr)?;
+
+    let timestamp = clock::now();
+    let outcome = tokio::task::spawn_blocking(move || linux::arp_request(&interface, addr))
+        .await
+        .unwrap_or_else(|e| Err(format!("ARP probe panicked: {}", e)));
+    Some(match outcome {
+        Ok(reply_ms) => NeighborCheck {
+            timestamp,
+            reply_ms,
+            error: None,
+        },
+        Err(error) => NeighborCheck {
+            timestamp,
+            reply_ms: None,
+            error: Some(error),
+        },
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub async fn probe(_addr: IpAddr) -> Option<NeighborCheck> {
+    None
+}
+
+// The MAC address the kernel's neighbor table holds for `addr`. Only directly connected IPv4
+// hosts have entries, so routed targets, and ones not yet resolved, give None
+#[cfg(target_os = "linux")]
+pub fn mac_address(addr: IpAddr) -> Option<String> {
+    const ATF_COM: u32 = 0x02;
+    let IpAddr::V4(addr) = addr else {
+        return None;
+    };
+    let content = std::fs::read_to_string("/proc/net/arp").ok()?;
+    let addr = addr.to_string();
+    content.lines().skip(1).find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [ip, _, flags, mac, ..] = fields[..] else {
+            return None;
+        };
+        let flags = u32::from_str_radix(flags.trim_start_matches("0x"), 16).ok()?;
+        (ip == addr && flags & ATF_COM != 0 && mac != "00:00:00:00:00:00")
+            .then(|| mac.to_lowercase())
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn mac_address(_addr: IpAddr) -> Option<String> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::net::{Ipv4Addr, UdpSocket};
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+    use std::time::{Duration, Instant};
+
+    const ARP_TIMEOUT: Duration = Duration::from_secs(1);
+    const ETH_P_ARP: u16 = 0x0806;
+    const ARPHRD_ETHER: &str = "1";
+    const RTF_UP: u16 = 0x0001;
+    const RTF_GATEWAY: u16 = 0x0002;
+
+    // The interface of the most specific gatewayless route covering `addr`, if it does ARP
+    pub fn on_link_interface(addr: Ipv4Addr) -> Option<String> {
+        let content = std::fs::read_to_string("/proc/net/route").ok()?;
+        let octets = u32::from_ne_bytes(addr.octets());
+
+        let mut best: Option<(u32, String)> = None;
+        for line in content.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 8 {
+                continue;
+            }
+            let parse = |field: &str| u32::from_str_radix(field, 16).ok();
+            let (Some(destination), Some(mask), Ok(flags)) = (
+                parse(fields[1]),
+                parse(fields[7]),
+                u16::from_str_radix(fields[3], 16),
+            ) else {
+                continue;
+            };
+            // The default route, even without a gateway, doesn't make the whole internet local
+            if flags & RTF_UP == 0 || flags & RTF_GATEWAY != 0 || mask == 0 {
+                continue;
+            }
+            if octets & mask != destination {
+                continue;
+            }
+            let prefix = mask.count_ones();
+            if best.as_ref().is_none_or(|(best, _)| prefix > *best) {
+                best = Some((prefix, fields[0].to_string()));
+            }
+        }
+
+        let (_, interface) = best?;
+        // Tunnels and other point-to-point links have no neighbors to ask
+        let kind = std::fs::read_to_string(format!("/sys/class/net/{}/type", interface)).ok()?;
+        (kind.trim() == ARPHRD_ETHER).then_some(interface)
+    }
+
+    // Broadcasts one ARP request for `target` and waits for its reply
+    pub fn arp_request(interface: &str, target: Ipv4Addr) -> Result<Option<f64>, String> {
+        let source = source_address(target)?;
+        let mac = interface_mac(interface)?;
+        let index = interface_index(interface)?;
+
+        // SOCK_DGRAM leaves the Ethernet header to the kernel
+        let fd = unsafe {
+            libc::socket(
+                libc::AF_PACKET,
+                libc::SOCK_DGRAM,
+                ETH_P_ARP.to_be() as libc::c_int,
+            )
+        };
+        if fd < 0 {
+            return Err(format!(
+                "Failed to open ARP socket: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        let socket = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        let mut request = [0u8; 28];
+        request[0..2].copy_from_slice(&1u16.to_be_bytes()); // Ethernet
+        request[2..4].copy_from_slice(&0x0800u16.to_be_bytes()); // IPv4
+        request[4] = 6;
+        request[5] = 4;
+        request[6..8].copy_from_slice(&1u16.to_be_bytes()); // request
+        request[8..14].copy_from_slice(&mac);
+        request[14..18].copy_from_slice(&source.octets());
+        request[24..28].copy_from_slice(&target.octets());
+
+        let mut destination: libc::sockaddr_ll = unsafe { std::mem::zeroed() };
+        destination.sll_family = libc::AF_PACKET as u16;
+        destination.sll_protocol = ETH_P_ARP.to_be();
+        destination.sll_ifindex = index;
+        destination.sll_halen = 6;
+        destination.sll_addr[..6].copy_from_slice(&[0xff; 6]);
+
+        let start = Instant::now();
+        let sent = unsafe {
+            libc::sendto(
+                socket.as_raw_fd(),
+                request.as_ptr() as *const libc::c_void,
+                request.len(),
+                0,
+                &destination as *const libc::sockaddr_ll as *const libc::sockaddr,
+                std::mem::size_of::<libc::sockaddr_ll>() as libc::socklen_t,
+            )
+        };
+        if sent < 0 {
+            return Err(format!(
+                "Failed to send ARP request: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+
+        // Every ARP packet on the interface arrives here; only the target's reply counts
+        let mut reply = [0u8; 64];
+        while let Some(left) = ARP_TIMEOUT.checked_sub(start.elapsed()) {
+            set_receive_timeout(&socket, left)?;
+            let received = unsafe {
+                libc::recv(
+                    socket.as_raw_fd(),
+                    reply.as_mut_ptr() as *mut libc::c_void,
+                    reply.len(),
+                    0,
+                )
+            };
+            if received < 0 {
+                let error = std::io::Error::last_os_error();
+                match error.kind() {
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut => break,
+                    std::io::ErrorKind::Interrupted => continue,
+                    _ => return Err(format!("Failed to read ARP reply: {}", error)),
+                }
+            }
+            if received >= 28 && reply[6..8] == [0, 2] && reply[14..18] == target.octets() {
+                return Ok(Some(start.elapsed().as_secs_f64() * 1000.0));
+            }
+        }
+        Ok(None)
+    }
+
+    // The address the kernel would send from; connecting a UDP socket sends nothing
+    fn source_address(target: Ipv4Addr) -> Result<Ipv4Addr, String> {
+        let socket = UdpSocket::bind("0.0.0.0:0")
+            .and_then(|socket| socket.connect((target, 9)).map(|_| socket))
+            .map_err(|e| format!("No route to {}: {}", target, e))?;
+        match socket.local_addr() {
+            Ok(std::net::SocketAddr::V4(local)) => Ok(*local.ip()),
+            Ok(local) => Err(format!("Unexpected source address {}", local)),
+            Err(e) => Err(format!("Failed to find a source address: {}", e)),
+        }
+    }
+
+    fn interface_mac(interface: &str) -> Result<[u8; 6], String> {
+        let path = format!("/sys/class/net/{}/address", interface);
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        let bytes: Vec<u8> = text
+            .trim()
+            .split(':')
+            .filter_map(|part| u8::from_str_radix(part, 16).ok())
+            .collect();
+        bytes
+            .try_into()
+            .map_err(|_| format!("Unexpected MAC address for {}: {}", interface, text.trim()))
+    }
+
+    fn interface_index(interface: &str) -> Result<i32, String> {
+        let name = std::ffi::CString::new(interface)
+            .map_err(|_| format!("Invalid interface name {}", interface))?;
+        match unsafe { libc::if_nametoindex(name.as_ptr()) } {
+            0 => Err(format!("Interface {} not found", interface)),
+            index => Ok(index as i32),
+        }
+    }
+
+    fn set_receive_timeout(socket: &OwnedFd, timeout: Duration) -> Result<(), String> {
+        // A zero timeout would block forever
+        let timeout = timeout.max(Duration::from_millis(1));
+        let value = libc::timeval {
+            tv_sec: timeout.as_secs() as libc::time_t,
+            tv_usec: timeout.subsec_micros() as libc::suseconds_t,
+        };
+        let result = unsafe {
+            libc::setsockopt(
+                socket.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_RCVTIMEO,
+                &value as *const libc::timeval as *const libc::c_void,
+                std::mem::size_of::<libc::timeval>() as libc::socklen_t,
+            )
+        };
+        if result < 0 {
+            return Err(format!(
+                "Failed to set ARP socket timeout: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+// Opens sockets inside a named Linux network namespace, as created by `ip netns add`. setns
+// only moves the calling thread, so a throwaway thread joins the namespace and the rest of the
+// process stays where it is; sockets keep the namespace they were opened in
+#[cfg(all(target_os = "linux", feature = "netns"))]
+pub fn in_netns<T, F>(name: &str, open: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> std::io::Result<T> + Send + 'static,
+{
+    use std::os::fd::AsRawFd;
+
+    if name.is_empty() || name.contains('/') {
+        return Err(format!("Invalid network namespace name '{}'", name));
+    }
+    // Looked up where `ip netns exec` looks
+    let path = std::path::Path::new("/var/run/netns").join(name);
+    let namespace = std::fs::File::open(&path)
+        .map_err(|e| format!("Network namespace '{}' not found: {}", name, e))?;
+
+    let name = name.to_string();
+    // Socket setup registers with the tokio reactor, which a plain thread has to enter
+    let runtime = tokio::runtime::Handle::current();
+    std::thread::spawn(move || {
+        if unsafe { libc::setns(namespace.as_raw_fd(), libc::CLONE_NEWNET) } != 0 {
+            let e = std::io::Error::last_os_error();
+            return Err(match e.raw_os_error() {
+                Some(libc::EPERM) => format!(
+                    "Entering network namespace '{}' needs CAP_SYS_ADMIN: {}",
+                    name, e
+                ),
+                _ => format!("Failed to enter network namespace '{}': {}", name, e),
+            });
+        }
+
+        let _guard = runtime.enter();
+        open().map_err(|e| {
+            format!(
+                "Failed to open socket in network namespace '{}': {}",
+                name, e
+            )
+        })
+    })
+    .join()
+    .map_err(|_| "Network namespace setup panicked".to_string())?
+}
+
+#[cfg(not(all(target_os = "linux", feature = "netns")))]
+pub fn in_netns<T, F>(name: &str, _open: F) -> Result<T, String>
+where
+    F: FnOnce() -> std::io::Result<T>,
+{
+    Err(format!(
+        "Network namespace '{}' needs a Linux build with the netns feature",
+        name
+    ))
+}
@@ -0,0 +1,194 @@
+use crate::clock;
+use crate::config::{Config, Target};
+use crate::events::EventFeed;
+use crate::monitor::{
+    MonitorCommand, MonitorStatus, PingResult, SshProbeKind, SshResult, StatsSettings, TargetStats,
+    health_change,
+};
+use crate::units::ExportedTimestamp;
+use chrono::{DateTime, Utc};
+use color_eyre::{Result, eyre::eyre};
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::{Mutex, mpsc};
+
+// Quiet stretches longer than this, such as while the recording daemon was stopped, are
+// skipped rather than waited out
+const MAX_GAP: chrono::Duration = chrono::Duration::seconds(30);
+
+// One line of a recording: what `on_result_command` receives on stdin
+#[derive(Debug, Deserialize)]
+struct RecordedResult {
+    ip: String,
+    name: Option<String>,
+    check: String,
+    timestamp: ExportedTimestamp,
+    success: bool,
+    latency_ms: Option<f64>,
+    failure_reason: Option<String>,
+    #[serde(default)]
+    local_outage: bool,
+}
+
+pub struct Replay {
+    pub targets: Arc<Mutex<Vec<TargetStats>>>,
+    pub status: Arc<Mutex<MonitorStatus>>,
+    // The frontends can send commands, but there is no monitor to act on them
+    pub commands: mpsc::UnboundedSender<MonitorCommand>,
+}
+
+// Loads a recording and starts feeding it to the shared state as if it were being probed
+// live. The clock follows the recording, so windows and ages read as they did at the time
+pub fn start(path: &Path, speed: f64, config: &Config) -> Result<Replay> {
+    if !(speed > 0.0 && speed.is_finite()) {
+        return Err(eyre!("Replay speed must be above 0, not {}", speed));
+    }
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| eyre!("Could not read {}: {}", path.display(), e))?;
+    let mut records = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: RecordedResult = serde_json::from_str(line)
+            .map_err(|e| eyre!("{} line {}: {}", path.display(), number + 1, e))?;
+        records.push((record.timestamp.to_utc(), record));
+    }
+    // Hooks run concurrently, so lines can land slightly out of order
+    records.sort_by_key(|(timestamp, _)| *timestamp);
+    let Some(&(first, _)) = records.first() else {
+        return Err(eyre!("{} has no results to replay", path.display()));
+    };
+
+    // Targets in the order they first appear, with their settings from the config if it has them
+    let settings = StatsSettings::from_config(config);
+    let mut targets: Vec<TargetStats> = Vec::new();
+    for (_, record) in &records {
+        if targets.iter().any(|t| t.target.ip == record.ip) {
+            continue;
+        }
+        let target = config
+            .targets
+            .iter()
+            .find(|t| t.ip == record.ip)
+            .cloned()
+            .unwrap_or_else(|| {
+                // The recording doesn't say which port SSH used, only that it was checked
+                let ssh = records
+                    .iter()
+                    .any(|(_, r)| r.ip == record.ip && r.check == "ssh");
+                Target {
+                    ip: record.ip.clone(),
+                    name: record.name.clone(),
+                    ssh_port: ssh.then_some(22),
+                    ..Default::default()
+                }
+            });
+        targets.push(TargetStats::new(
+            target,
+            config.history_size,
+            settings.clone(),
+        ));
+    }
+
+    clock::replay_from(first, speed);
+    let shared_targets = Arc::new(Mutex::new(targets.clone()));
+    let status = Arc::new(Mutex::new(MonitorStatus::new(config)));
+    tokio::spawn(feed(
+        records,
+        targets,
+        config.history_size,
+        speed,
+        Arc::clone(&shared_targets),
+        Arc::clone(&status),
+    ));
+
+    let (commands, mut command_rx) = mpsc::unbounded_channel::<MonitorCommand>();
+    tokio::spawn(async move { while command_rx.recv().await.is_some() {} });
+
+    Ok(Replay {
+        targets: shared_targets,
+        status,
+        commands,
+    })
+}
+
+async fn feed(
+    records: Vec<(DateTime<Utc>, RecordedResult)>,
+    mut targets: Vec<TargetStats>,
+    history_size: usize,
+    speed: f64,
+    shared_targets: Arc<Mutex<Vec<TargetStats>>>,
+    status: Arc<Mutex<MonitorStatus>>,
+) {
+    let end = records.last().map(|(timestamp, _)| *timestamp);
+    // Gaps between results are the recording's, not a stalled monitor
+    let ticker_status = Arc::clone(&status);
+    let ticker = tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+        loop {
+            interval.tick().await;
+            ticker_status.lock().await.record_cycle();
+        }
+    });
+
+    let mut events = EventFeed::default();
+    let mut records = records.into_iter().peekable();
+    while let Some((timestamp, record)) = records.next() {
+        let wait = timestamp - clock::now();
+        if wait > MAX_GAP {
+            clock::replay_from(timestamp, speed);
+        } else if let Ok(wait) = wait.to_std() {
+            tokio::time::sleep(wait.div_f64(speed)).await;
+        }
+
+        let Some(target_stats) = targets.iter_mut().find(|t| t.target.ip == record.ip) else {
+            continue;
+        };
+        let previous_health = target_stats.health;
+        match record.check.as_str() {
+            "ssh" => target_stats.add_ssh_result(
+                SshResult {
+                    timestamp,
+                    connection_time_ms: record.latency_ms,
+                    success: record.success,
+                    failure_reason: record.failure_reason,
+                    local_outage: record.local_outage,
+                    kind: SshProbeKind::Connect,
+                },
+                history_size,
+            ),
+            _ => target_stats.add_ping_result(
+                PingResult {
+                    timestamp,
+                    latency_ms: record.latency_ms,
+                    success: record.success,
+                    failure_reason: record.failure_reason,
+                    warmup: false,
+                    local_outage: record.local_outage,
+                    rate_limited: false,
+                },
+                history_size,
+            ),
+        }
+        if let Some((kind, message)) = health_change(previous_health, target_stats) {
+            events.push(Some(&target_stats.target), kind, message);
+        }
+
+        // Results from the same moment are shown together, like one live cycle
+        if records.peek().is_none_or(|(next, _)| *next > clock::now()) {
+            *shared_targets.lock().await = targets.clone();
+            let mut status = status.lock().await;
+            status.events = events.events.clone();
+            status.record_cycle();
+        }
+    }
+
+    // The end of the recording stays on screen without going stale
+    ticker.abort();
+    if let Some(end) = end {
+        clock::replay_from(end, 0.0);
+        status.lock().await.record_cycle();
+    }
+}
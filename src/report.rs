@@ -0,0 +1,250 @@
+use crate::clock;
+use crate::config::LatencyUnit;
+use crate::monitor::{Statistics, TargetStats};
+use crate::ui::{format_availability, format_downtime, format_success};
+use crate::units::format_latency;
+use chrono::{DateTime, Local, Utc};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum ReportFormat {
+    Md,
+}
+
+// A stretch of consecutive failed pings, ended by the next answered one
+struct Outage {
+    start: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+    pings: usize,
+    reason: Option<String>,
+}
+
+// Parses a window such as "90s", "15m" or "2h"
+pub fn parse_window(value: &str) -> Result<chrono::Duration, String> {
+    let (number, unit) = value.split_at(value.len().saturating_sub(1));
+    let amount: i64 = number
+        .parse()
+        .map_err(|_| format!("Invalid window '{}', expected e.g. 90s, 15m or 2h", value))?;
+    match unit {
+        "s" => Ok(chrono::Duration::seconds(amount)),
+        "m" => Ok(chrono::Duration::minutes(amount)),
+        "h" => Ok(chrono::Duration::hours(amount)),
+        _ => Err(format!(
+            "Invalid window '{}', expected e.g. 90s, 15m or 2h",
+            value
+        )),
+    }
+}
+
+fn window_label(window: chrono::Duration) -> String {
+    let secs = window.num_seconds();
+    match secs {
+        _ if secs % 3600 == 0 => format!("{}h", secs / 3600),
+        _ if secs % 60 == 0 => format!("{}m", secs / 60),
+        _ => format!("{}s", secs),
+    }
+}
+
+// Counted pings only, so warmup and local outages don't show up as downtime
+fn outages(target: &TargetStats) -> Vec<Outage> {
+    let mut outages: Vec<Outage> = Vec::new();
+    let mut current: Option<Outage> = None;
+
+    for result in target.ping_history.iter().filter(|r| r.counted()) {
+        match (&mut current, result.success) {
+            (None, false) => {
+                current = Some(Outage {
+                    start: result.timestamp,
+                    end: None,
+                    pings: 1,
+                    reason: result.failure_reason.clone(),
+                });
+            }
+            (Some(outage), false) => outage.pings += 1,
+            (Some(_), true) => {
+                if let Some(mut outage) = current.take() {
+                    outage.end = Some(result.timestamp);
+                    outages.push(outage);
+                }
+            }
+            (None, true) => {}
+        }
+    }
+    outages.extend(current);
+    outages
+}
+
+// Pipes would end the table cell
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn local_time(timestamp: DateTime<Utc>) -> String {
+    timestamp
+        .with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+fn latency_cells(stats: Option<&Statistics>, unit: LatencyUnit) -> (String, String, String) {
+    match stats {
+        Some(stats) => (
+            format_latency(stats.mean, unit, 1),
+            format_latency(stats.p95, unit, 1),
+            format_latency(stats.jitter, unit, 1),
+        ),
+        None => ("-".to_string(), "-".to_string(), "-".to_string()),
+    }
+}
+
+// A couple of lines for pasting into chat mid-incident; `windowed` says whether `target` was
+// narrowed to a window, which availability then covers instead of the session
+pub fn render_chat_summary(target: &TargetStats, windowed: bool, unit: LatencyUnit) -> String {
+    let latest = match target.ping_history.iter().rev().find(|r| r.counted()) {
+        Some(r) => match r.latency_ms {
+            Some(ms) => format_latency(ms, unit, 1),
+            None => "no reply".to_string(),
+        },
+        None => "-".to_string(),
+    };
+    let (mean, p95, _) = latency_cells(target.ping_stats.as_ref(), unit);
+    let loss = target.ping_stats.as_ref().map_or("-".to_string(), |s| {
+        format!("{:.1}%", 100.0 - s.success_rate)
+    });
+    let availability = if windowed {
+        target.window_availability()
+    } else {
+        target.session_availability()
+    };
+
+    format!(
+        "{} ({}): {}\nlatest {} | mean {} | p95 {} | loss {} | uptime {}",
+        target.target.name.as_deref().unwrap_or(&target.target.ip),
+        target.target.ip,
+        target.health.label(),
+        latest,
+        mean,
+        p95,
+        loss,
+        availability.map_or("-".to_string(), |a| format_availability(&a)),
+    )
+}
+
+// `window` narrows every section to recent samples; otherwise the report covers whatever
+// history is retained, with availability over the whole session
+pub fn render_markdown(
+    targets: &[TargetStats],
+    window: Option<chrono::Duration>,
+    unit: LatencyUnit,
+) -> String {
+    let now = clock::now();
+    let targets: Vec<TargetStats> = match window {
+        Some(window) => targets.iter().map(|t| t.windowed(window)).collect(),
+        None => targets.to_vec(),
+    };
+    let name = |t: &TargetStats| cell(t.target.name.as_deref().unwrap_or(&t.target.ip));
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# boxmonitor report\n");
+    let scope = match window {
+        Some(window) => format!("the last {}", window_label(window)),
+        None => match targets.iter().map(|t| t.session_started).min() {
+            Some(started) => format!("the session since {}", local_time(started)),
+            None => "the session".to_string(),
+        },
+    };
+    let _ = writeln!(out, "Generated {} covering {}.\n", local_time(now), scope);
+
+    let _ = writeln!(out, "## Targets\n");
+    let _ = writeln!(
+        out,
+        "| Target | IP | Health | Ping success | Mean | P95 | Jitter | SSH success | Availability |"
+    );
+    let _ = writeln!(out, "|---|---|---|---|---|---|---|---|---|");
+    for target in &targets {
+        let (mean, p95, jitter) = latency_cells(target.ping_stats.as_ref(), unit);
+        let availability = match window {
+            Some(_) => target.window_availability(),
+            None => target.session_availability(),
+        };
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} | {} | {} | {} | {} | {} |",
+            name(target),
+            cell(&target.target.ip),
+            target.health.label(),
+            target
+                .ping_stats
+                .as_ref()
+                .map_or("-".to_string(), |s| format_success(s, 1)),
+            mean,
+            p95,
+            jitter,
+            target
+                .ssh_stats
+                .as_ref()
+                .map_or("-".to_string(), |s| format_success(s, 1)),
+            availability.map_or("-".to_string(), |a| format_availability(&a)),
+        );
+    }
+
+    let _ = writeln!(out, "\n## Downtime\n");
+    let mut any_outage = false;
+    for target in &targets {
+        for outage in outages(target) {
+            if !any_outage {
+                let _ = writeln!(
+                    out,
+                    "| Target | Started | Ended | Duration | Failed pings | Reason |"
+                );
+                let _ = writeln!(out, "|---|---|---|---|---|---|");
+                any_outage = true;
+            }
+            let (ended, duration) = match outage.end {
+                Some(end) => (local_time(end), format_downtime(end - outage.start)),
+                None => (
+                    "ongoing".to_string(),
+                    format!("{}+", format_downtime(now - outage.start)),
+                ),
+            };
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {} | {} |",
+                name(target),
+                local_time(outage.start),
+                ended,
+                duration,
+                outage.pings,
+                cell(outage.reason.as_deref().unwrap_or("-"))
+            );
+        }
+    }
+    if !any_outage {
+        let _ = writeln!(out, "No outages in the retained ping history.");
+    }
+
+    let _ = writeln!(out, "\n## Failure timeline\n");
+    let mut failures: Vec<_> = targets
+        .iter()
+        .flat_map(|t| t.failure_log.iter().map(move |f| (t, f)))
+        .collect();
+    failures.sort_by_key(|(_, f)| f.timestamp);
+    if failures.is_empty() {
+        let _ = writeln!(out, "No failures recorded.");
+    } else {
+        let _ = writeln!(out, "| Time | Target | Check | Reason |");
+        let _ = writeln!(out, "|---|---|---|---|");
+        for (target, failure) in failures {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                local_time(failure.timestamp),
+                name(target),
+                cell(&failure.failure_type.to_lowercase()),
+                cell(&failure.reason)
+            );
+        }
+    }
+
+    out
+}
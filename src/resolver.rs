@@ -0,0 +1,391 @@
+use crate::config::{DnsCheck, DnsProtocol};
+use crate::dns::LookupTiming;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpStream, UdpSocket};
+use tokio_rustls::TlsConnector;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+
+const TYPE_A: u16 = 1;
+const TYPE_AAAA: u16 = 28;
+const MAX_HTTP_HEADER: usize = 16 * 1024;
+
+// Asks the configured server for the host's A and AAAA records. Both queries go out together,
+// as a browser's would, so `query_ms` is the time until both answers are in. For dot and doh,
+// `tls_ms` is the TCP connect and TLS handshake before them
+pub async fn resolve(
+    host: &str,
+    check: &DnsCheck,
+    timing: &mut LookupTiming,
+) -> Result<Vec<IpAddr>, String> {
+    let server = check
+        .server
+        .as_deref()
+        .ok_or_else(|| "dns_check needs a server for this protocol".to_string())?;
+    let id = rand::random::<u16>();
+    let queries = [
+        (id, build_query(id, host, TYPE_A)?),
+        (
+            id.wrapping_add(1),
+            build_query(id.wrapping_add(1), host, TYPE_AAAA)?,
+        ),
+    ];
+
+    let responses = match check.protocol {
+        DnsProtocol::System => unreachable!("system lookups don't go through a server"),
+        DnsProtocol::Udp => over_udp(server, &queries, timing).await?,
+        DnsProtocol::Dot => over_tls(server, check.tls_name.as_deref(), &queries, timing).await?,
+        DnsProtocol::Doh => over_https(server, &queries, timing).await?,
+    };
+
+    // An AAAA failure doesn't matter when there are A records, and the other way round
+    let mut addresses = Vec::new();
+    let mut first_error = None;
+    for (id, _) in &queries {
+        let answer = responses
+            .iter()
+            .find(|response| response_id(response) == Some(*id))
+            .ok_or_else(|| "DNS server didn't answer every query".to_string())
+            .and_then(|response| parse_answer(response));
+        match answer {
+            Ok(found) => addresses.extend(found),
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    match first_error {
+        Some(e) if addresses.is_empty() => Err(e),
+        _ => Ok(addresses),
+    }
+}
+
+async fn over_udp(
+    server: &str,
+    queries: &[(u16, Vec<u8>)],
+    timing: &mut LookupTiming,
+) -> Result<Vec<Vec<u8>>, String> {
+    let (host, port) = split_host_port(server, 53)?;
+    let addr = tokio::net::lookup_host((host.as_str(), port))
+        .await
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .ok_or_else(|| format!("Could not resolve DNS server {}", server))?;
+    let bind: SocketAddr = if addr.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let socket = UdpSocket::bind(bind)
+        .await
+        .map_err(|e| format!("Failed to open DNS socket: {}", e))?;
+    socket
+        .connect(addr)
+        .await
+        .map_err(|e| format!("No route to DNS server {}: {}", addr, e))?;
+
+    let start = Instant::now();
+    for (_, query) in queries {
+        socket
+            .send(query)
+            .await
+            .map_err(|e| format!("Failed to send DNS query: {}", e))?;
+    }
+    let mut responses: Vec<Vec<u8>> = Vec::new();
+    let mut buffer = [0u8; 4096];
+    while responses.len() < queries.len() {
+        let received = socket
+            .recv(&mut buffer)
+            .await
+            .map_err(|e| format!("Failed to read DNS response: {}", e))?;
+        let response = buffer[..received].to_vec();
+        // Stray or duplicate datagrams are dropped
+        let id = response_id(&response);
+        if queries.iter().any(|(query_id, _)| Some(*query_id) == id)
+            && !responses.iter().any(|r| response_id(r) == id)
+        {
+            responses.push(response);
+        }
+    }
+    timing.query_ms = Some(elapsed_ms(start));
+    Ok(responses)
+}
+
+async fn over_tls(
+    server: &str,
+    tls_name: Option<&str>,
+    queries: &[(u16, Vec<u8>)],
+    timing: &mut LookupTiming,
+) -> Result<Vec<Vec<u8>>, String> {
+    let (host, port) = split_host_port(server, 853)?;
+    let start = Instant::now();
+    let mut stream = connect_tls(&host, port, tls_name.unwrap_or(&host)).await?;
+    timing.tls_ms = Some(elapsed_ms(start));
+
+    // Each message is prefixed with its length, as over plain TCP
+    let start = Instant::now();
+    let mut request = Vec::new();
+    for (_, query) in queries {
+        request.extend_from_slice(&(query.len() as u16).to_be_bytes());
+        request.extend_from_slice(query);
+    }
+    stream
+        .write_all(&request)
+        .await
+        .map_err(|e| format!("Failed to send DNS query: {}", e))?;
+
+    let mut responses = Vec::new();
+    for _ in queries {
+        let mut length = [0u8; 2];
+        stream
+            .read_exact(&mut length)
+            .await
+            .map_err(|e| format!("Failed to read DNS response: {}", e))?;
+        let mut response = vec![0u8; u16::from_be_bytes(length) as usize];
+        stream
+            .read_exact(&mut response)
+            .await
+            .map_err(|e| format!("Failed to read DNS response: {}", e))?;
+        responses.push(response);
+    }
+    timing.query_ms = Some(elapsed_ms(start));
+    Ok(responses)
+}
+
+// RFC 8484 POSTs over HTTP/1.1, pipelined on one connection
+async fn over_https(
+    url: &str,
+    queries: &[(u16, Vec<u8>)],
+    timing: &mut LookupTiming,
+) -> Result<Vec<Vec<u8>>, String> {
+    let rest = url
+        .strip_prefix("https://")
+        .ok_or_else(|| format!("DoH server must be an https:// URL: {}", url))?;
+    let (authority, path) = match rest.find('/') {
+        Some(slash) => rest.split_at(slash),
+        None => (rest, "/dns-query"),
+    };
+    let (host, port) = split_host_port(authority, 443)?;
+
+    let start = Instant::now();
+    let mut stream = connect_tls(&host, port, &host).await?;
+    timing.tls_ms = Some(elapsed_ms(start));
+
+    let start = Instant::now();
+    let mut request = Vec::new();
+    for (_, query) in queries {
+        request.extend_from_slice(
+            format!(
+                "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/dns-message\r\n\
+                 Accept: application/dns-message\r\nContent-Length: {}\r\n\r\n",
+                path,
+                authority,
+                query.len()
+            )
+            .as_bytes(),
+        );
+        request.extend_from_slice(query);
+    }
+    stream
+        .write_all(&request)
+        .await
+        .map_err(|e| format!("Failed to send DoH request: {}", e))?;
+
+    let mut buffer = Vec::new();
+    let mut responses = Vec::new();
+    for _ in queries {
+        responses.push(read_http_response(&mut stream, &mut buffer).await?);
+    }
+    timing.query_ms = Some(elapsed_ms(start));
+    Ok(responses)
+}
+
+// Reads one response body; whatever follows it stays in `buffer` for the next
+async fn read_http_response(
+    stream: &mut TlsStream<TcpStream>,
+    buffer: &mut Vec<u8>,
+) -> Result<Vec<u8>, String> {
+    let header_end = loop {
+        if let Some(end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+        if buffer.len() > MAX_HTTP_HEADER {
+            return Err("DoH response headers are too long".to_string());
+        }
+        read_more(stream, buffer).await?;
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).into_owned();
+    let mut lines = head.split("\r\n");
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .unwrap_or("");
+    if status != "200" {
+        return Err(format!("DoH server answered HTTP {}", status));
+    }
+    let length = lines
+        .find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.trim()
+                .eq_ignore_ascii_case("content-length")
+                .then(|| value.trim().parse::<usize>().ok())?
+        })
+        .ok_or_else(|| "DoH response has no Content-Length".to_string())?;
+
+    buffer.drain(..header_end);
+    while buffer.len() < length {
+        read_more(stream, buffer).await?;
+    }
+    Ok(buffer.drain(..length).collect())
+}
+
+async fn read_more(stream: &mut TlsStream<TcpStream>, buffer: &mut Vec<u8>) -> Result<(), String> {
+    let mut chunk = [0u8; 4096];
+    let read = stream
+        .read(&mut chunk)
+        .await
+        .map_err(|e| format!("Failed to read DoH response: {}", e))?;
+    if read == 0 {
+        return Err("DoH server closed the connection".to_string());
+    }
+    buffer.extend_from_slice(&chunk[..read]);
+    Ok(())
+}
+
+pub async fn connect_tls(
+    host: &str,
+    port: u16,
+    tls_name: &str,
+) -> Result<TlsStream<TcpStream>, String> {
+    let name = ServerName::try_from(tls_name.to_string())
+        .map_err(|_| format!("Invalid TLS name {}", tls_name))?;
+    let tcp = TcpStream::connect((host, port))
+        .await
+        .map_err(|e| format!("Failed to connect to {}:{}: {}", host, port, e))?;
+    tls_connector()
+        .connect(name, tcp)
+        .await
+        .map_err(|e| format!("TLS handshake with {} failed: {}", host, e))
+}
+
+// Built once; certificates are checked against the bundled Mozilla roots
+fn tls_connector() -> TlsConnector {
+    static CONFIG: OnceLock<Arc<ClientConfig>> = OnceLock::new();
+    let config = CONFIG.get_or_init(|| {
+        let roots = RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        };
+        Arc::new(
+            ClientConfig::builder()
+                .with_root_certificates(roots)
+                .with_no_client_auth(),
+        )
+    });
+    TlsConnector::from(Arc::clone(config))
+}
+
+// "host", "host:port", "[v6]:port" or a bare IPv6 address
+fn split_host_port(server: &str, default_port: u16) -> Result<(String, u16), String> {
+    if let Ok(ip) = server.parse::<IpAddr>() {
+        return Ok((ip.to_string(), default_port));
+    }
+    if let Ok(addr) = server.parse::<SocketAddr>() {
+        return Ok((addr.ip().to_string(), addr.port()));
+    }
+    match server.rsplit_once(':') {
+        Some((host, port)) => port
+            .parse()
+            .map(|port| (host.to_string(), port))
+            .map_err(|_| format!("Invalid DNS server {}", server)),
+        None => Ok((server.to_string(), default_port)),
+    }
+}
+
+fn build_query(id: u16, host: &str, record_type: u16) -> Result<Vec<u8>, String> {
+    let mut message = Vec::with_capacity(host.len() + 18);
+    message.extend_from_slice(&id.to_be_bytes());
+    message.extend_from_slice(&[0x01, 0x00]); // recursion desired
+    message.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]); // one question
+    for label in host.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(format!("Invalid hostname {}", host));
+        }
+        message.push(label.len() as u8);
+        message.extend_from_slice(label.as_bytes());
+    }
+    message.push(0);
+    message.extend_from_slice(&record_type.to_be_bytes());
+    message.extend_from_slice(&1u16.to_be_bytes()); // IN
+    Ok(message)
+}
+
+fn response_id(message: &[u8]) -> Option<u16> {
+    Some(u16::from_be_bytes([*message.first()?, *message.get(1)?]))
+}
+
+// The A and AAAA records among the answers; CNAMEs leading to them are skipped over
+fn parse_answer(message: &[u8]) -> Result<Vec<IpAddr>, String> {
+    let truncated = || "Malformed DNS response".to_string();
+    let be16 = |pos: usize| {
+        message
+            .get(pos..pos + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+            .ok_or_else(truncated)
+    };
+
+    let flags = be16(2)?;
+    match flags & 0x000f {
+        0 => {}
+        3 => return Err("DNS lookup failed: no such host".to_string()),
+        rcode => return Err(format!("DNS server answered with rcode {}", rcode)),
+    }
+    if flags & 0x0200 != 0 {
+        return Err("DNS response was truncated".to_string());
+    }
+
+    let mut pos = 12;
+    for _ in 0..be16(4)? {
+        pos = skip_name(message, pos).ok_or_else(truncated)? + 4;
+    }
+    let mut addresses = Vec::new();
+    for _ in 0..be16(6)? {
+        pos = skip_name(message, pos).ok_or_else(truncated)?;
+        let record_type = be16(pos)?;
+        let length = be16(pos + 8)? as usize;
+        let data = message
+            .get(pos + 10..pos + 10 + length)
+            .ok_or_else(truncated)?;
+        if let Ok(octets) = <[u8; 4]>::try_from(data)
+            && record_type == TYPE_A
+        {
+            addresses.push(IpAddr::from(octets));
+        } else if let Ok(octets) = <[u8; 16]>::try_from(data)
+            && record_type == TYPE_AAAA
+        {
+            addresses.push(IpAddr::from(octets));
+        }
+        pos += 10 + length;
+    }
+    Ok(addresses)
+}
+
+// Position just past a name, which ends at a zero label or a compression pointer
+fn skip_name(message: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let length = *message.get(pos)?;
+        match length {
+            0 => return Some(pos + 1),
+            length if length & 0xc0 == 0xc0 => return Some(pos + 2),
+            length => pos += 1 + length as usize,
+        }
+    }
+}
+
+fn elapsed_ms(start: Instant) -> f64 {
+    start.elapsed().as_secs_f64() * 1000.0
+}
@@ -0,0 +1,261 @@
+use crate::clock;
+use crate::config::{ExportTimestamps, get_config_dir};
+use crate::monitor::{Statistics, TargetStats};
+use crate::theme::Theme;
+use crate::units::ExportedTimestamp;
+use chrono::Local;
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Latency has to move by more than this before a change counts as a regression, so ordinary
+// jitter between runs isn't flagged
+const LATENCY_REGRESSION_PERCENT: f64 = 5.0;
+const SUCCESS_RATE_REGRESSION_POINTS: f64 = 0.5;
+
+const PING_METRICS: (&str, &str, &str) = ("ping mean", "ping p95", "ping success");
+const SSH_METRICS: (&str, &str, &str) = ("ssh mean", "ssh p95", "ssh success");
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsSnapshotFile {
+    pub taken_at: ExportedTimestamp,
+    pub targets: Vec<TargetSnapshot>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetSnapshot {
+    pub ip: String,
+    pub name: Option<String>,
+    pub ping_stats: Option<Statistics>,
+    pub ssh_stats: Option<Statistics>,
+}
+
+impl TargetSnapshot {
+    fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.ip)
+    }
+}
+
+pub fn save_stats_snapshot(
+    targets: &[TargetStats],
+    timestamps: ExportTimestamps,
+) -> Result<PathBuf> {
+    let export_dir = get_config_dir()?.join("exports");
+    fs::create_dir_all(&export_dir)?;
+
+    let snapshot = StatsSnapshotFile {
+        taken_at: timestamps.stamp(clock::now()),
+        targets: targets
+            .iter()
+            .map(|t| TargetSnapshot {
+                ip: t.target.ip.clone(),
+                name: t.target.name.clone(),
+                ping_stats: t.ping_stats.clone(),
+                ssh_stats: t.ssh_stats.clone(),
+            })
+            .collect(),
+    };
+
+    let path = export_dir.join(format!(
+        "snapshot-{}.json",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::write(&path, serde_json::to_string_pretty(&snapshot)?)?;
+    Ok(path)
+}
+
+fn load_stats_snapshot(path: &Path) -> Result<StatsSnapshotFile> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| color_eyre::eyre::eyre!("Could not read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| color_eyre::eyre::eyre!("Invalid snapshot {}: {}", path.display(), e))
+}
+
+#[derive(Debug, Serialize)]
+struct MetricDiff {
+    metric: &'static str,
+    before: f64,
+    after: f64,
+    delta: f64,
+    regression: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Presence {
+    Both,
+    OnlyBefore,
+    OnlyAfter,
+}
+
+#[derive(Debug, Serialize)]
+struct TargetDiff {
+    ip: String,
+    name: String,
+    presence: Presence,
+    metrics: Vec<MetricDiff>,
+}
+
+fn latency_diff(metric: &'static str, before: f64, after: f64) -> MetricDiff {
+    let delta = after - before;
+    let regression = before > 0.0 && delta / before * 100.0 > LATENCY_REGRESSION_PERCENT;
+    MetricDiff {
+        metric,
+        before,
+        after,
+        delta,
+        regression,
+    }
+}
+
+fn success_rate_diff(metric: &'static str, before: f64, after: f64) -> MetricDiff {
+    let delta = after - before;
+    MetricDiff {
+        metric,
+        before,
+        after,
+        delta,
+        regression: -delta > SUCCESS_RATE_REGRESSION_POINTS,
+    }
+}
+
+// Metric names are (mean, p95, success rate) for one check type
+fn stats_diffs(
+    (mean, p95, success): (&'static str, &'static str, &'static str),
+    before: &Option<Statistics>,
+    after: &Option<Statistics>,
+    metrics: &mut Vec<MetricDiff>,
+) {
+    let (Some(before), Some(after)) = (before, after) else {
+        return;
+    };
+
+    metrics.push(latency_diff(mean, before.mean, after.mean));
+    metrics.push(latency_diff(p95, before.p95, after.p95));
+    metrics.push(success_rate_diff(
+        success,
+        before.success_rate,
+        after.success_rate,
+    ));
+}
+
+fn diff_snapshots(before: &StatsSnapshotFile, after: &StatsSnapshotFile) -> Vec<TargetDiff> {
+    let mut diffs = Vec::new();
+
+    for old in &before.targets {
+        match after.targets.iter().find(|new| new.ip == old.ip) {
+            Some(new) => {
+                let mut metrics = Vec::new();
+                stats_diffs(PING_METRICS, &old.ping_stats, &new.ping_stats, &mut metrics);
+                stats_diffs(SSH_METRICS, &old.ssh_stats, &new.ssh_stats, &mut metrics);
+                diffs.push(TargetDiff {
+                    ip: old.ip.clone(),
+                    name: new.display_name().to_string(),
+                    presence: Presence::Both,
+                    metrics,
+                });
+            }
+            None => diffs.push(TargetDiff {
+                ip: old.ip.clone(),
+                name: old.display_name().to_string(),
+                presence: Presence::OnlyBefore,
+                metrics: Vec::new(),
+            }),
+        }
+    }
+
+    for new in &after.targets {
+        if !before.targets.iter().any(|old| old.ip == new.ip) {
+            diffs.push(TargetDiff {
+                ip: new.ip.clone(),
+                name: new.display_name().to_string(),
+                presence: Presence::OnlyAfter,
+                metrics: Vec::new(),
+            });
+        }
+    }
+
+    diffs
+}
+
+// `boxmonitor diff a.json b.json`: compares two saved snapshots, `a` being the baseline
+pub fn run_diff(before_path: &Path, after_path: &Path, json: bool) -> Result<()> {
+    let before = load_stats_snapshot(before_path)?;
+    let after = load_stats_snapshot(after_path)?;
+    let diffs = diff_snapshots(&before, &after);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diffs)?);
+        return Ok(());
+    }
+
+    // Plain text for pipes and NO_COLOR, red highlighting on an interactive terminal
+    use std::io::IsTerminal;
+    let color = std::io::stdout().is_terminal() && !Theme::from_env(false).is_monochrome();
+
+    println!(
+        "Before: {} ({})",
+        before_path.display(),
+        before
+            .taken_at
+            .to_utc()
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S")
+    );
+    println!(
+        "After:  {} ({})",
+        after_path.display(),
+        after
+            .taken_at
+            .to_utc()
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S")
+    );
+
+    for diff in &diffs {
+        println!();
+        match diff.presence {
+            Presence::OnlyBefore => {
+                println!("{} ({}): removed, only in before", diff.name, diff.ip);
+                continue;
+            }
+            Presence::OnlyAfter => {
+                println!("{} ({}): added, only in after", diff.name, diff.ip);
+                continue;
+            }
+            Presence::Both => println!("{} ({})", diff.name, diff.ip),
+        }
+
+        if diff.metrics.is_empty() {
+            println!("  no statistics in both snapshots");
+        }
+
+        for metric in &diff.metrics {
+            let unit = if metric.metric.ends_with("success") {
+                "%"
+            } else {
+                "ms"
+            };
+            let line = format!(
+                "  {:<14} {:>10.2}{unit} -> {:>10.2}{unit}  {:>+10.2}{unit}{}",
+                metric.metric,
+                metric.before,
+                metric.after,
+                metric.delta,
+                if metric.regression {
+                    "  REGRESSION"
+                } else {
+                    ""
+                },
+            );
+            if metric.regression && color {
+                println!("\x1b[31m{}\x1b[0m", line);
+            } else {
+                println!("{}", line);
+            }
+        }
+    }
+
+    Ok(())
+}
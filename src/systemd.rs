@@ -0,0 +1,35 @@
+use crate::monitor::MonitorStatus;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+// Readiness and watchdog notifications for a Type=notify service. Outside systemd, or in a
+// build without the systemd feature, these do nothing
+#[cfg(feature = "systemd")]
+pub fn notify_ready() {
+    let _ = sd_notify::notify(false, &[sd_notify::NotifyState::Ready]);
+}
+
+#[cfg(not(feature = "systemd"))]
+pub fn notify_ready() {}
+
+// Pings the watchdog at half its timeout while monitoring cycles keep completing, going by the
+// same last-cycle time as the stall warning; once the monitor loop wedges the pings stop and
+// systemd restarts the service
+#[cfg(feature = "systemd")]
+pub async fn run_watchdog(status: Arc<Mutex<MonitorStatus>>) {
+    let mut timeout_usec = 0;
+    if !sd_notify::watchdog_enabled(false, &mut timeout_usec) {
+        return;
+    }
+
+    let mut interval = tokio::time::interval(std::time::Duration::from_micros(timeout_usec / 2));
+    loop {
+        interval.tick().await;
+        if status.lock().await.stalled_for().is_none() {
+            let _ = sd_notify::notify(false, &[sd_notify::NotifyState::Watchdog]);
+        }
+    }
+}
+
+#[cfg(not(feature = "systemd"))]
+pub async fn run_watchdog(_status: Arc<Mutex<MonitorStatus>>) {}
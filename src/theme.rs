@@ -0,0 +1,256 @@
+use crate::clock;
+use crate::config::{ChartStyles, GraphKind, LatencyUnit, MarkerKind};
+use crate::units::TimeAxis;
+use chrono::{DateTime, Utc};
+use ratatui::{
+    style::{Color, Modifier, Style},
+    symbols::Marker,
+    widgets::GraphType,
+};
+use std::time::Duration;
+
+// Without color, series are told apart by marker shape instead
+const MONOCHROME_MARKERS: [Marker; 5] = [
+    Marker::Braille,
+    Marker::Dot,
+    Marker::Block,
+    Marker::HalfBlock,
+    Marker::Bar,
+];
+
+// Per-target series colors in the multi-target charts
+const DEFAULT_SERIES_COLORS: [Color; 12] = [
+    Color::Green,
+    Color::Blue,
+    Color::Yellow,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Red,
+    Color::LightGreen,
+    Color::LightBlue,
+    Color::LightYellow,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::LightRed,
+];
+
+// Okabe-Ito colors, distinguishable without red-green perception
+const DEUTERANOPIA_SERIES_COLORS: [Color; 8] = [
+    Color::Rgb(0, 114, 178),
+    Color::Rgb(230, 159, 0),
+    Color::Rgb(86, 180, 233),
+    Color::Rgb(240, 228, 66),
+    Color::Rgb(204, 121, 167),
+    Color::Rgb(213, 94, 0),
+    Color::Rgb(0, 158, 115),
+    Color::Rgb(255, 255, 255),
+];
+
+// Paul Tol's bright scheme without its red, which reads as near-black with protanopia
+const PROTANOPIA_SERIES_COLORS: [Color; 6] = [
+    Color::Rgb(68, 119, 170),
+    Color::Rgb(204, 187, 68),
+    Color::Rgb(102, 204, 238),
+    Color::Rgb(170, 51, 119),
+    Color::Rgb(34, 136, 51),
+    Color::Rgb(187, 187, 187),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Palette {
+    #[default]
+    Default,
+    Deuteranopia,
+    Protanopia,
+    // Every series in the terminal's own color, told apart by shape; the rest of the UI keeps
+    // its colors (unlike --no-color)
+    Monochrome,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckKind {
+    Ping,
+    Ssh,
+}
+
+impl CheckKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            CheckKind::Ping => "Ping",
+            CheckKind::Ssh => "SSH",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    monochrome: bool,
+    chart_styles: ChartStyles,
+    palette: Palette,
+    latency_unit: LatencyUnit,
+    time_axis: TimeAxis,
+}
+
+impl Theme {
+    // Honors the NO_COLOR convention (https://no-color.org): any non-empty value disables color
+    pub fn from_env(no_color_flag: bool) -> Self {
+        let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        Self {
+            monochrome: no_color_flag || no_color_env,
+            chart_styles: ChartStyles::default(),
+            palette: Palette::Default,
+            latency_unit: LatencyUnit::default(),
+            time_axis: TimeAxis::default(),
+        }
+    }
+
+    pub fn with_palette(self, palette: Palette) -> Self {
+        Self { palette, ..self }
+    }
+
+    pub fn with_chart_styles(self, chart_styles: ChartStyles) -> Self {
+        Self {
+            chart_styles,
+            ..self
+        }
+    }
+
+    pub fn with_latency_unit(self, latency_unit: LatencyUnit) -> Self {
+        Self {
+            latency_unit,
+            ..self
+        }
+    }
+
+    pub fn with_time_axis(self, time_axis: TimeAxis) -> Self {
+        Self { time_axis, ..self }
+    }
+
+    pub fn time_axis(&self) -> TimeAxis {
+        self.time_axis
+    }
+
+    pub fn latency_unit(&self) -> LatencyUnit {
+        self.latency_unit
+    }
+
+    pub fn axis_settle(&self) -> Duration {
+        Duration::from_secs_f64(self.chart_styles.axis_settle_secs.max(0.0))
+    }
+
+    // Labels for a chart's time axis, from the timestamps of its samples
+    pub fn time_labels(&self, x_max: f64, timestamps: &[DateTime<Utc>]) -> Vec<String> {
+        crate::units::time_labels(self.time_axis, x_max, timestamps, clock::now())
+    }
+
+    pub fn latency(&self, ms: f64, decimals: usize) -> String {
+        crate::units::format_latency(ms, self.latency_unit, decimals)
+    }
+
+    // The unit a chart axis reaching `max_ms` is labelled in
+    pub fn axis_unit(&self, max_ms: f64) -> LatencyUnit {
+        self.latency_unit.resolve(max_ms)
+    }
+
+    pub fn is_monochrome(&self) -> bool {
+        self.monochrome
+    }
+
+    pub fn fg(&self, color: Color) -> Style {
+        if self.monochrome {
+            Style::default()
+        } else {
+            Style::default().fg(color)
+        }
+    }
+
+    // Background highlights become reverse video so selection stays visible
+    pub fn bg(&self, color: Color) -> Style {
+        if self.monochrome {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default().bg(color)
+        }
+    }
+
+    pub fn highlight(&self, color: Color) -> Style {
+        if self.monochrome {
+            Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD)
+        } else {
+            Style::default().fg(color)
+        }
+    }
+
+    pub fn style(&self, style: Style) -> Style {
+        if self.monochrome {
+            Style {
+                fg: None,
+                bg: None,
+                ..style
+            }
+        } else {
+            style
+        }
+    }
+
+    // On the default palette, a target's SSH series is the lighter shade of its ping color;
+    // the other palettes have no shades to spare, so SSH differs by marker shape only
+    pub fn series_color(&self, target_idx: usize, check: CheckKind) -> Color {
+        let colors: &[Color] = match self.palette {
+            Palette::Default => &DEFAULT_SERIES_COLORS,
+            Palette::Deuteranopia => &DEUTERANOPIA_SERIES_COLORS,
+            Palette::Protanopia => &PROTANOPIA_SERIES_COLORS,
+            Palette::Monochrome => &[Color::Reset],
+        };
+        let color = colors[target_idx % colors.len()];
+
+        match (self.palette, check) {
+            (Palette::Default, CheckKind::Ssh) => match color {
+                Color::Green => Color::LightGreen,
+                Color::Blue => Color::LightBlue,
+                Color::Yellow => Color::LightYellow,
+                Color::Magenta => Color::LightMagenta,
+                Color::Cyan => Color::LightCyan,
+                Color::Red => Color::LightRed,
+                _ => Color::White,
+            },
+            _ => color,
+        }
+    }
+
+    // Without color, or with a color-blind palette, series also cycle through marker shapes
+    pub fn series_marker(&self, series_idx: usize, default: Marker) -> Marker {
+        if self.monochrome || self.palette != Palette::Default {
+            MONOCHROME_MARKERS[series_idx % MONOCHROME_MARKERS.len()]
+        } else {
+            default
+        }
+    }
+
+    // Configured marker for a check type; monochrome still cycles shapes to tell series apart
+    pub fn check_marker(&self, check: CheckKind, series_idx: usize) -> Marker {
+        let marker = match self.check_style(check).marker {
+            MarkerKind::Braille => Marker::Braille,
+            MarkerKind::Dot => Marker::Dot,
+            MarkerKind::Block => Marker::Block,
+            MarkerKind::HalfBlock => Marker::HalfBlock,
+            MarkerKind::Bar => Marker::Bar,
+        };
+        self.series_marker(series_idx, marker)
+    }
+
+    pub fn check_graph(&self, check: CheckKind) -> GraphType {
+        match self.check_style(check).graph {
+            GraphKind::Line => GraphType::Line,
+            GraphKind::Scatter => GraphType::Scatter,
+            GraphKind::Bar => GraphType::Bar,
+        }
+    }
+
+    fn check_style(&self, check: CheckKind) -> crate::config::SeriesStyle {
+        match check {
+            CheckKind::Ping => self.chart_styles.ping,
+            CheckKind::Ssh => self.chart_styles.ssh,
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::clock;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+const CHUNK_SIZE: usize = 32 * 1024;
+
+// One upload of a fixed-size buffer over SSH; what latency alone misses, like bufferbloat
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThroughputResult {
+    pub timestamp: DateTime<Utc>,
+    pub bytes: u64,
+    // Megabytes (10^6 bytes) per second; None when the transfer failed
+    pub mb_per_sec: Option<f64>,
+    pub error: Option<String>,
+}
+
+// Blocking. Streams `bytes` zeros into `cat > /dev/null` on the target over `session`, or over
+// a new session when there is none, and hands the session back for reuse if it still works
+pub fn upload_test(
+    session: Option<ssh2::Session>,
+    addr: std::net::SocketAddr,
+    user: &str,
+    key_file: Option<&std::path::Path>,
+    timeout: Duration,
+    bytes: u64,
+) -> (ThroughputResult, Option<ssh2::Session>) {
+    let timestamp = clock::now();
+    let session = match session {
+        Some(session) => Ok(session),
+        None => crate::monitor::connect_ssh_session(addr, user, key_file, timeout),
+    };
+
+    let outcome = session.and_then(|session| upload(&session, bytes).map(|secs| (secs, session)));
+    match outcome {
+        Ok((secs, session)) => (
+            ThroughputResult {
+                timestamp,
+                bytes,
+                mb_per_sec: Some(bytes as f64 / secs.max(f64::EPSILON) / 1_000_000.0),
+                error: None,
+            },
+            Some(session),
+        ),
+        Err(error) => (
+            ThroughputResult {
+                timestamp,
+                bytes,
+                mb_per_sec: None,
+                error: Some(error),
+            },
+            None,
+        ),
+    }
+}
+
+// Seconds from the first byte written until the remote end has read the last one
+fn upload(session: &ssh2::Session, bytes: u64) -> Result<f64, String> {
+    let mut channel = session
+        .channel_session()
+        .map_err(|e| format!("SSH session lost: {}", e))?;
+    channel
+        .exec("cat > /dev/null")
+        .map_err(|e| format!("Failed to start the transfer: {}", e))?;
+
+    let buffer = [0u8; CHUNK_SIZE];
+    let start = Instant::now();
+    let mut left = bytes;
+    while left > 0 {
+        let chunk = left.min(CHUNK_SIZE as u64) as usize;
+        channel
+            .write_all(&buffer[..chunk])
+            .map_err(|e| format!("Transfer failed: {}", e))?;
+        left -= chunk as u64;
+    }
+    // cat only exits, closing its side, once it has read everything
+    channel
+        .send_eof()
+        .and_then(|_| channel.wait_eof())
+        .and_then(|_| channel.wait_close())
+        .map_err(|e| format!("Transfer failed: {}", e))?;
+    let elapsed = start.elapsed().as_secs_f64();
+
+    match channel.exit_status() {
+        Ok(0) => Ok(elapsed),
+        Ok(status) => Err(format!("Remote cat exited with status {}", status)),
+        Err(e) => Err(format!("Transfer failed: {}", e)),
+    }
+}
@@ -1,7 +1,34 @@
-use crate::monitor::{Statistics, TargetStats};
+use crate::alerts::{Acknowledgement, Alert, AlertLevel, MassOutage};
+use crate::clock;
+use crate::config::{DnsProtocol, ExportTimestamps, PrimaryCheck};
+use crate::export::{ExportSeries, export_chart_svg};
+use crate::monitor::{
+    Availability, Diagnostics, Health, LocalOutage, MonitorCommand, MonitorStatus, Statistics,
+    TargetStats, percentile, run_burst,
+};
+use crate::report::render_chat_summary;
+use crate::snapshot::save_stats_snapshot;
+use crate::theme::{CheckKind, Theme};
+use crate::ui_diagnostics::render_diagnostics;
+use crate::ui_editor::{ConfigEditor, EditorAction, render_config_editor};
+use crate::ui_events::render_event_feed;
 use crate::ui_failure_charts::{
     render_all_targets_failure_chart, render_single_target_failure_chart,
 };
+use crate::ui_gauges::render_gauges;
+use crate::ui_histogram::render_latency_histogram;
+use crate::ui_popup::{
+    BurstPopup, Notice, render_burst_popup, render_confirm_popup, render_failures_popup,
+    render_input_popup, render_notice_popup,
+};
+use crate::ui_relative::{reference_for, relative_summary, render_relative_chart};
+use crate::ui_samples_table::{
+    render_all_targets_samples_table, render_single_target_samples_table,
+};
+use crate::ui_sampling::{render_all_targets_sampling, render_sampling_chart};
+use crate::ui_throughput::render_throughput_chart;
+use crate::units::axis_labels;
+use chrono::{DateTime, Utc};
 use color_eyre::Result;
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
@@ -9,24 +36,155 @@ use crossterm::{
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
 use ratatui::{
-    Frame, Terminal,
+    Frame, Terminal, TerminalOptions, Viewport,
     backend::{Backend, CrosstermBackend},
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
+    style::{Color, Modifier},
     symbols,
     text::{Line, Span},
-    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, List, ListItem, Paragraph, Tabs},
+    widgets::{
+        Axis, Block, Borders, Chart, Dataset, GraphType, List, ListItem, Paragraph, Row, Table,
+        TableState, Tabs,
+    },
 };
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
 use std::io;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, mpsc};
+
+const INLINE_HEIGHT: u16 = 40;
+const BURST_COUNT: usize = 100;
+const BURST_SPACING: Duration = Duration::from_millis(10);
+// Durations 'm' steps through when muting all alerts, before unmuting again
+const ALERT_MUTE_MINUTES: [i64; 4] = [15, 30, 60, 120];
 
 #[derive(Clone, Copy, PartialEq)]
 pub enum PlotView {
     AllTargets,
     PingOnly,
     SshOnly,
+    Throughput,
     FailureChart,
+    SamplesTable,
+    Histogram,
+    Gauges,
+    Relative,
+    Sampling,
+    Events,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum StatsWindow {
+    LastMinute,
+    LastFiveMinutes,
+    LastFifteenMinutes,
+    All,
+}
+
+impl StatsWindow {
+    fn next(self) -> Self {
+        match self {
+            StatsWindow::LastMinute => StatsWindow::LastFiveMinutes,
+            StatsWindow::LastFiveMinutes => StatsWindow::LastFifteenMinutes,
+            StatsWindow::LastFifteenMinutes => StatsWindow::All,
+            StatsWindow::All => StatsWindow::LastMinute,
+        }
+    }
+
+    fn duration(self) -> Option<chrono::Duration> {
+        match self {
+            StatsWindow::LastMinute => Some(chrono::Duration::minutes(1)),
+            StatsWindow::LastFiveMinutes => Some(chrono::Duration::minutes(5)),
+            StatsWindow::LastFifteenMinutes => Some(chrono::Duration::minutes(15)),
+            StatsWindow::All => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            StatsWindow::LastMinute => "last 1m",
+            StatsWindow::LastFiveMinutes => "last 5m",
+            StatsWindow::LastFifteenMinutes => "last 15m",
+            StatsWindow::All => "all",
+        }
+    }
+}
+
+// Samples each overlay line is smoothed over when not showing raw latency
+const OVERLAY_WINDOW: usize = 10;
+
+// The top of each latency chart's y axis as last drawn, by chart. A new peak raises it at
+// once; once the peak scrolls away it eases back down, closing about two thirds of the gap
+// per settle time (Theme::axis_settle), so one spike doesn't make the chart jump twice
+#[derive(Default)]
+pub struct AxisBounds {
+    shown: HashMap<String, (f64, Instant)>,
+}
+
+impl AxisBounds {
+    fn smooth(&mut self, chart: String, y_max: f64, theme: &Theme) -> f64 {
+        let settle = theme.axis_settle();
+        let now = Instant::now();
+        let y_max = match self.shown.get(&chart) {
+            Some(&(shown, at)) if y_max < shown && !settle.is_zero() => {
+                let keep = (-(now - at).as_secs_f64() / settle.as_secs_f64()).exp();
+                y_max + (shown - y_max) * keep
+            }
+            _ => y_max,
+        };
+        self.shown.insert(chart, (y_max, now));
+        y_max
+    }
+}
+
+// What the all-targets overlay draws per series: every sample, or a rolling aggregate of the
+// latest OVERLAY_WINDOW
+#[derive(Clone, Copy, PartialEq)]
+pub enum OverlayAggregate {
+    Raw,
+    RollingMean,
+    RollingP95,
+}
+
+impl OverlayAggregate {
+    fn next(self) -> Self {
+        match self {
+            OverlayAggregate::Raw => OverlayAggregate::RollingMean,
+            OverlayAggregate::RollingMean => OverlayAggregate::RollingP95,
+            OverlayAggregate::RollingP95 => OverlayAggregate::Raw,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            OverlayAggregate::Raw => "raw",
+            OverlayAggregate::RollingMean => "rolling mean",
+            OverlayAggregate::RollingP95 => "rolling p95",
+        }
+    }
+
+    // Each point becomes the aggregate of itself and the points before it in the window
+    fn apply(self, data: &[(f64, f64)]) -> Vec<(f64, f64)> {
+        if self == OverlayAggregate::Raw {
+            return data.to_vec();
+        }
+        (0..data.len())
+            .map(|i| {
+                let window = &data[(i + 1).saturating_sub(OVERLAY_WINDOW)..=i];
+                let value = match self {
+                    OverlayAggregate::RollingP95 => {
+                        let mut values: Vec<f64> = window.iter().map(|(_, y)| *y).collect();
+                        values.sort_by(f64::total_cmp);
+                        percentile(&values, 95.0)
+                    }
+                    _ => window.iter().map(|(_, y)| *y).sum::<f64>() / window.len() as f64,
+                };
+                (data[i].0, value)
+            })
+            .collect()
+    }
 }
 
 #[derive(Clone, Copy, PartialEq)]
@@ -35,49 +193,317 @@ pub enum TabMode {
     Individual(usize),
 }
 
+// What '+' and '-' step through: how many of the latest samples the time-series charts show,
+// with None for all of them
+const CHART_SPANS: [Option<usize>; 7] = [
+    Some(25),
+    Some(50),
+    Some(100),
+    Some(250),
+    Some(500),
+    Some(1000),
+    None,
+];
+
 pub struct App {
     pub should_quit: bool,
-    pub current_tab: usize,
     pub current_plot_view: PlotView,
     pub tab_mode: TabMode,
+    pub stats_window: StatsWindow,
+    pub normalize_overlay: bool,
+    pub overlay_aggregate: OverlayAggregate,
+    // Target info and statistics squeezed onto one line, leaving the height to the charts
+    pub compact: bool,
+    // The monitor's own counters, shown with 'D' in place of the view; never part of the 'p'
+    // rotation
+    pub show_diagnostics: bool,
+    pub diagnostics: Diagnostics,
+    // Target picked with Up/Down on the overview, whose latest failures show in a popup; an
+    // index into the targets the tag filter lets through
+    pub overview_selection: Option<usize>,
+    // Only targets with this tag get a tab and a place on the overview
+    pub tag_filter: Option<String>,
     pub targets: Arc<Mutex<Vec<TargetStats>>>,
+    pub status: Arc<Mutex<MonitorStatus>>,
+    pub commands: mpsc::UnboundedSender<MonitorCommand>,
+    pub export_timestamps: ExportTimestamps,
+    pub stalled_for: Option<chrono::Duration>,
+    // What pings are due at, for judging how far apart they actually landed
+    pub ping_interval_ms: u64,
+    pub active_alerts: Vec<Alert>,
+    pub quiet_hours: bool,
+    pub alerts_muted_until: Option<DateTime<Utc>>,
+    // The ALERT_MUTE_MINUTES entry the last 'm' picked and the end it asked for, until then
+    pub alert_mute: Option<(usize, DateTime<Utc>)>,
+    pub config_warning: Option<String>,
+    pub hook_error: Option<String>,
+    pub collector_error: Option<String>,
+    pub local_outage: Option<LocalOutage>,
+    pub mass_outage: Option<MassOutage>,
+    pub samples_table_state: TableState,
+    pub events: VecDeque<crate::events::Event>,
+    pub events_table_state: TableState,
+    pub burst_popup: Arc<std::sync::Mutex<Option<BurstPopup>>>,
+    pub notice: Option<Notice>,
+    // Kept for the whole session: on X11 copied text is only available while it is alive
+    clipboard: Option<arboard::Clipboard>,
+    // When 's' asked the monitor to save its config; its outcome comes back in the event feed
+    pub config_save_requested: Option<DateTime<Utc>>,
+    // Newest monitor restart seen; its panic report was printed over the screen
+    pub last_restart: Option<DateTime<Utc>>,
+    // Note being typed for an acknowledgement of the current target; captures all keys
+    pub ack_input: Option<String>,
+    // Target whose history is about to be cleared, waiting for confirmation
+    pub clear_confirm: Option<usize>,
+    pub editor: Option<ConfigEditor>,
+    // Sample picked with the arrow keys on the ping chart, the first end of a range being
+    // marked, and the range zoomed into, which replaces the stats window everywhere
+    pub chart_cursor: Option<DateTime<Utc>>,
+    pub zoom_mark: Option<DateTime<Utc>>,
+    pub zoom: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    pub axis_bounds: AxisBounds,
+    // Latest samples the time-series charts show, out of what the stats window or zoom leaves;
+    // None shows them all. Statistics are unaffected
+    pub chart_span: Option<usize>,
+    // Armed with 'F', the next target to go down freezes the screen on its tab, like a
+    // triggered capture on an oscilloscope; health as of the last frame spots the transition
+    pub freeze_armed: bool,
+    pub frozen: Option<Frozen>,
+    previous_health: Vec<Health>,
+    pub theme: Theme,
+}
+
+// The targets as they were the moment `target` went down, drawn instead of the live ones
+pub struct Frozen {
+    pub targets: Arc<Vec<TargetStats>>,
+    pub target: usize,
+    pub at: DateTime<Utc>,
 }
 
 impl App {
-    pub fn new(targets: Arc<Mutex<Vec<TargetStats>>>) -> Self {
+    pub fn new(
+        targets: Arc<Mutex<Vec<TargetStats>>>,
+        status: Arc<Mutex<MonitorStatus>>,
+        commands: mpsc::UnboundedSender<MonitorCommand>,
+        theme: Theme,
+        export_timestamps: ExportTimestamps,
+    ) -> Self {
         Self {
             should_quit: false,
-            current_tab: 0,
             current_plot_view: PlotView::AllTargets,
             tab_mode: TabMode::AllTargets,
+            stats_window: StatsWindow::All,
+            normalize_overlay: false,
+            overlay_aggregate: OverlayAggregate::Raw,
+            compact: false,
+            show_diagnostics: false,
+            diagnostics: Diagnostics::default(),
+            overview_selection: None,
+            tag_filter: None,
             targets,
+            status,
+            commands,
+            export_timestamps,
+            stalled_for: None,
+            ping_interval_ms: 0,
+            active_alerts: Vec::new(),
+            quiet_hours: false,
+            alerts_muted_until: None,
+            alert_mute: None,
+            config_warning: None,
+            hook_error: None,
+            collector_error: None,
+            local_outage: None,
+            mass_outage: None,
+            samples_table_state: TableState::default(),
+            events: VecDeque::new(),
+            events_table_state: TableState::default(),
+            burst_popup: Arc::new(std::sync::Mutex::new(None)),
+            notice: None,
+            clipboard: None,
+            config_save_requested: None,
+            last_restart: None,
+            ack_input: None,
+            clear_confirm: None,
+            editor: None,
+            chart_cursor: None,
+            zoom_mark: None,
+            zoom: None,
+            axis_bounds: AxisBounds::default(),
+            chart_span: None,
+            freeze_armed: false,
+            frozen: None,
+            previous_health: Vec::new(),
+            theme,
+        }
+    }
+
+    // `visible` are the indices of the targets with a tab; position 0 is "All Targets"
+    pub fn tab_position(&self, visible: &[usize]) -> usize {
+        match self.tab_mode {
+            TabMode::AllTargets => 0,
+            TabMode::Individual(idx) => visible
+                .iter()
+                .position(|&i| i == idx)
+                .map_or(0, |position| position + 1),
         }
     }
 
-    pub fn next_tab(&mut self, max_tabs: usize) {
-        let total_tabs = max_tabs + 1; // +1 for "All Targets" tab
-        self.current_tab = (self.current_tab + 1) % total_tabs;
-        self.update_tab_mode(max_tabs);
+    pub fn next_tab(&mut self, visible: &[usize]) {
+        let position = (self.tab_position(visible) + 1) % (visible.len() + 1);
+        self.select_tab(position.checked_sub(1).map(|p| visible[p]));
     }
 
-    pub fn previous_tab(&mut self, max_tabs: usize) {
-        let total_tabs = max_tabs + 1; // +1 for "All Targets" tab
-        if self.current_tab > 0 {
-            self.current_tab -= 1;
-        } else {
-            self.current_tab = total_tabs - 1;
+    pub fn previous_tab(&mut self, visible: &[usize]) {
+        let position = (self.tab_position(visible) + visible.len()) % (visible.len() + 1);
+        self.select_tab(position.checked_sub(1).map(|p| visible[p]));
+    }
+
+    // Whether the tag filter lets `target` into the tabs and the overview
+    pub fn shows(&self, target: &TargetStats) -> bool {
+        self.tag_filter
+            .as_ref()
+            .is_none_or(|tag| target.target.tags.contains(tag))
+    }
+
+    pub fn visible_targets(&self, targets: &[TargetStats]) -> Vec<usize> {
+        (0..targets.len())
+            .filter(|&idx| self.shows(&targets[idx]))
+            .collect()
+    }
+
+    // Every tag in turn, alphabetically, then no filter again
+    pub fn cycle_tag_filter(&mut self, targets: &[TargetStats]) {
+        let mut tags: Vec<&String> = targets.iter().flat_map(|t| &t.target.tags).collect();
+        tags.sort();
+        tags.dedup();
+        if tags.is_empty() {
+            self.notice = Some(Notice {
+                title: "No Tags".to_string(),
+                message: "Set `tags` on targets in the config to filter by them".to_string(),
+                is_error: true,
+            });
+            return;
         }
-        self.update_tab_mode(max_tabs);
+
+        self.tag_filter = match &self.tag_filter {
+            None => Some(tags[0].clone()),
+            Some(current) => tags
+                .iter()
+                .position(|tag| *tag == current)
+                .and_then(|position| tags.get(position + 1))
+                .map(|tag| (*tag).clone()),
+        };
+        self.overview_selection = None;
+        if let TabMode::Individual(idx) = self.tab_mode
+            && !targets.get(idx).is_some_and(|t| self.shows(t))
+        {
+            self.select_tab(None);
+        }
+    }
+
+    // Up/Down through the targets on the overview, wrapping at either end
+    pub fn move_overview_selection(&mut self, step: isize, target_count: usize) {
+        if target_count == 0 {
+            return;
+        }
+        let count = target_count as isize;
+        self.overview_selection = Some(match self.overview_selection {
+            Some(idx) => (idx as isize + step).rem_euclid(count) as usize,
+            None if step > 0 => 0,
+            None => target_count - 1,
+        });
     }
 
-    fn update_tab_mode(&mut self, _max_targets: usize) {
-        if self.current_tab == 0 {
-            self.tab_mode = TabMode::AllTargets;
+    pub fn open_overview_selection(&mut self, visible: &[usize]) {
+        if let Some(&idx) = self.overview_selection.and_then(|p| visible.get(p)) {
+            self.select_tab(Some(idx));
+        }
+    }
+
+    // Jumps to the target whose failure log has the newest entry: what just broke. Targets the
+    // tag filter hides are passed over
+    pub async fn focus_latest_failure(&mut self) {
+        let latest = {
+            let targets = self.targets.lock().await;
+            targets
+                .iter()
+                .enumerate()
+                .filter(|(_, t)| self.shows(t))
+                .filter_map(|(idx, t)| t.failure_log.back().map(|f| (idx, f.timestamp)))
+                .max_by_key(|&(_, timestamp)| timestamp)
+                .map(|(idx, _)| idx)
+        };
+
+        match latest {
+            Some(idx) => self.select_tab(Some(idx)),
+            None => {
+                self.notice = Some(Notice {
+                    title: "Latest Failure".to_string(),
+                    message: "No failures recorded yet".to_string(),
+                    is_error: false,
+                });
+            }
+        }
+    }
+
+    // 'F': arms the freeze, disarms it, or resumes the live view once it has fired
+    pub fn toggle_freeze(&mut self) {
+        let message = if self.frozen.take().is_some() {
+            "Back to the live view; press 'F' again to re-arm".to_string()
+        } else if self.freeze_armed {
+            self.freeze_armed = false;
+            "Disarmed; the screen stays live when a target goes down".to_string()
         } else {
-            self.tab_mode = TabMode::Individual(self.current_tab - 1);
+            self.freeze_armed = true;
+            "Armed; the screen freezes on the next target to go down".to_string()
+        };
+        self.notice = Some(Notice {
+            title: "Freeze On Failure".to_string(),
+            message,
+            is_error: false,
+        });
+    }
+
+    // Freezes on the first shown target that has gone down since the last frame
+    fn check_freeze(&mut self, targets: &[TargetStats]) {
+        let went_down = targets
+            .iter()
+            .zip(&self.previous_health)
+            .position(|(t, previous)| {
+                t.health == Health::Down && *previous != Health::Down && self.shows(t)
+            });
+        self.previous_health = targets.iter().map(|t| t.health).collect();
+        if !self.freeze_armed || self.frozen.is_some() {
+            return;
+        }
+        if let Some(target) = went_down {
+            self.freeze_armed = false;
+            self.frozen = Some(Frozen {
+                targets: Arc::new(targets.to_vec()),
+                target,
+                at: clock::now(),
+            });
+            self.zoom = None;
+            self.select_tab(Some(target));
         }
     }
 
+    // None is the "All Targets" tab
+    fn select_tab(&mut self, target: Option<usize>) {
+        // Each tab shows different samples, so start the table back at the top
+        self.samples_table_state = TableState::default();
+        self.events_table_state = TableState::default();
+        self.chart_cursor = None;
+        self.zoom_mark = None;
+        self.overview_selection = None;
+
+        self.tab_mode = match target {
+            Some(idx) => TabMode::Individual(idx),
+            None => TabMode::AllTargets,
+        };
+    }
+
     pub fn next_plot_view(&mut self, has_ssh: bool) {
         self.current_plot_view = match self.current_plot_view {
             PlotView::AllTargets => PlotView::PingOnly,
@@ -88,28 +514,511 @@ impl App {
                     PlotView::FailureChart
                 }
             }
-            PlotView::SshOnly => PlotView::FailureChart,
-            PlotView::FailureChart => PlotView::AllTargets,
+            // Throughput is per target too
+            PlotView::SshOnly => match self.tab_mode {
+                TabMode::Individual(_) => PlotView::Throughput,
+                TabMode::AllTargets => PlotView::FailureChart,
+            },
+            PlotView::Throughput => PlotView::FailureChart,
+            PlotView::FailureChart => PlotView::SamplesTable,
+            // The histogram is per target and the gauges compare targets, so each tab skips
+            // the other's
+            PlotView::SamplesTable => match self.tab_mode {
+                TabMode::Individual(_) => PlotView::Histogram,
+                TabMode::AllTargets => PlotView::Gauges,
+            },
+            // So is the comparison against the reference target
+            PlotView::Histogram => PlotView::Relative,
+            PlotView::Relative | PlotView::Gauges => PlotView::Sampling,
+            PlotView::Sampling => PlotView::Events,
+            PlotView::Events => PlotView::AllTargets,
         };
     }
+
+    pub fn start_burst(
+        &self,
+        addr: Result<std::net::IpAddr, String>,
+        netns: Option<String>,
+        interface: Option<String>,
+        target_name: String,
+        loss_penalty_ms: f64,
+    ) {
+        let mut popup = self.burst_popup.lock().unwrap();
+        if matches!(*popup, Some(BurstPopup::Running(_))) {
+            return;
+        }
+        *popup = Some(BurstPopup::Running(target_name.clone()));
+
+        let burst_popup = Arc::clone(&self.burst_popup);
+        tokio::spawn(async move {
+            let result = run_burst(
+                addr,
+                netns.as_deref(),
+                interface.as_deref(),
+                BURST_COUNT,
+                BURST_SPACING,
+                loss_penalty_ms,
+            )
+            .await;
+            *burst_popup.lock().unwrap() = Some(BurstPopup::Done(target_name, result));
+        });
+    }
+
+    // Mutes every alert for the next duration in ALERT_MUTE_MINUTES, counted from now; past
+    // the last one it unmutes
+    pub fn cycle_alert_mute(&mut self) {
+        let step = match self.alert_mute {
+            None => Some(0),
+            Some((step, _)) if step + 1 < ALERT_MUTE_MINUTES.len() => Some(step + 1),
+            Some(_) => None,
+        };
+        self.alert_mute = step.map(|step| {
+            let until = clock::now() + chrono::Duration::minutes(ALERT_MUTE_MINUTES[step]);
+            (step, until)
+        });
+        self.send_command(MonitorCommand::MuteAlerts(
+            self.alert_mute.map(|(_, until)| until),
+        ));
+    }
+
+    fn alert_mute_label(&self) -> String {
+        // The monitor's mute, or one just asked for that it hasn't published yet
+        let muted_until = self
+            .alerts_muted_until
+            .or(self.alert_mute.map(|(_, until)| until));
+        match muted_until {
+            Some(until) if until > clock::now() => {
+                let left = (until - clock::now()).num_seconds();
+                format!(
+                    " - alerts muted {}:{:02} ('m' for longer)",
+                    left / 60,
+                    left % 60
+                )
+            }
+            _ if self.quiet_hours => " - quiet hours, alerts silenced".to_string(),
+            _ => String::new(),
+        }
+    }
+
+    pub fn close_burst_popup(&self) {
+        let mut popup = self.burst_popup.lock().unwrap();
+        if matches!(*popup, Some(BurstPopup::Done(..))) {
+            *popup = None;
+        }
+    }
+
+    // Exports what the current tab and view show: SSH times on the SSH view, ping latency otherwise
+    pub async fn export_current_chart(&mut self) {
+        let mut selected: Vec<TargetStats> = {
+            let targets = self.targets.lock().await;
+            match self.tab_mode {
+                TabMode::AllTargets => targets.clone(),
+                TabMode::Individual(idx) => targets.get(idx).cloned().into_iter().collect(),
+            }
+        };
+        if let Some((from, to)) = self.view_range() {
+            selected = selected.iter().map(|t| t.between(from, to)).collect();
+        }
+
+        let label = match (self.tab_mode, selected.first()) {
+            (TabMode::Individual(_), Some(target)) => target
+                .target
+                .name
+                .clone()
+                .unwrap_or_else(|| target.target.ip.clone()),
+            _ => "All Targets".to_string(),
+        };
+        let series = if self.current_plot_view == PlotView::SshOnly {
+            ExportSeries::Ssh
+        } else {
+            ExportSeries::Ping
+        };
+
+        self.notice = Some(match export_chart_svg(&selected, series, &label) {
+            Ok(path) => Notice {
+                title: "Chart Exported".to_string(),
+                message: path.display().to_string(),
+                is_error: false,
+            },
+            Err(e) => Notice {
+                title: "Export Failed".to_string(),
+                message: e.to_string(),
+                is_error: true,
+            },
+        });
+    }
+
+    // The current target's key numbers, in the current window, as text for chat
+    pub async fn copy_stats(&mut self) {
+        let TabMode::Individual(idx) = self.tab_mode else {
+            self.notice = Some(Notice {
+                title: "Nothing To Copy".to_string(),
+                message: "Select a target tab to copy its stats".to_string(),
+                is_error: true,
+            });
+            return;
+        };
+        let Some(mut target) = self.targets.lock().await.get(idx).cloned() else {
+            return;
+        };
+        let range = self.view_range();
+        if let Some((from, to)) = range {
+            target = target.between(from, to);
+        }
+        let text = render_chat_summary(&target, range.is_some(), self.theme.latency_unit());
+
+        let copied = match &mut self.clipboard {
+            Some(clipboard) => Ok(clipboard),
+            None => arboard::Clipboard::new().map(|clipboard| self.clipboard.insert(clipboard)),
+        }
+        .and_then(|clipboard| clipboard.set_text(text.clone()));
+        self.notice = Some(match copied {
+            Ok(()) => Notice {
+                title: "Stats Copied".to_string(),
+                message: text,
+                is_error: false,
+            },
+            Err(e) => Notice {
+                title: "Copy Failed".to_string(),
+                message: format!("Clipboard unavailable: {}", e),
+                is_error: true,
+            },
+        });
+    }
+
+    // Statistics for every target, in the current window, for `boxmonitor diff`
+    pub async fn save_snapshot(&mut self) {
+        let mut targets = self.targets.lock().await.clone();
+        if let Some((from, to)) = self.view_range() {
+            targets = targets.iter().map(|t| t.between(from, to)).collect();
+        }
+
+        self.notice = Some(
+            match save_stats_snapshot(&targets, self.export_timestamps) {
+                Ok(path) => Notice {
+                    title: "Snapshot Saved".to_string(),
+                    message: path.display().to_string(),
+                    is_error: false,
+                },
+                Err(e) => Notice {
+                    title: "Snapshot Failed".to_string(),
+                    message: e.to_string(),
+                    is_error: true,
+                },
+            },
+        );
+    }
+
+    pub fn save_config(&mut self) {
+        self.config_save_requested = Some(clock::now());
+        self.send_command(MonitorCommand::SaveConfig);
+    }
+
+    pub async fn open_editor(&mut self) {
+        let targets = self
+            .targets
+            .lock()
+            .await
+            .iter()
+            .map(|t| t.target.clone())
+            .collect();
+        let ping_interval_ms = self.status.lock().await.ping_interval_ms;
+        self.editor = Some(ConfigEditor::new(targets, ping_interval_ms));
+    }
+
+    pub fn edit_config(&mut self, key: KeyCode) {
+        let Some(editor) = &mut self.editor else {
+            return;
+        };
+        match editor.handle_key(key) {
+            EditorAction::None => {}
+            EditorAction::Close => self.editor = None,
+            EditorAction::Save(targets, ping_interval_ms) => {
+                self.editor = None;
+                self.config_save_requested = Some(clock::now());
+                self.send_command(MonitorCommand::SaveEditedConfig {
+                    targets,
+                    ping_interval_ms,
+                });
+            }
+        }
+    }
+
+    // Shows how the requested save went once the monitor has reported it
+    fn check_config_saved(&mut self) {
+        let Some(requested) = self.config_save_requested else {
+            return;
+        };
+        let Some(event) =
+            self.events.iter().rev().find(|e| {
+                e.kind == crate::events::EventKind::ConfigSaved && e.timestamp >= requested
+            })
+        else {
+            return;
+        };
+        self.notice = Some(Notice {
+            title: "Save Config".to_string(),
+            message: event.message.clone(),
+            is_error: event.message.starts_with("save failed"),
+        });
+        self.config_save_requested = None;
+    }
+
+    // Acknowledges the current target's problem, or clears an existing acknowledgement
+    pub async fn toggle_ack(&mut self) {
+        let TabMode::Individual(idx) = self.tab_mode else {
+            return;
+        };
+        let state = {
+            let targets = self.targets.lock().await;
+            targets.get(idx).map(|t| (t.ack.is_some(), t.health))
+        };
+
+        match state {
+            Some((true, _)) => self.send_command(MonitorCommand::ClearAcknowledgement(idx)),
+            Some((false, Health::Degraded | Health::Down)) => self.ack_input = Some(String::new()),
+            Some((false, _)) => {
+                self.notice = Some(Notice {
+                    title: "Acknowledge".to_string(),
+                    message: "Nothing to acknowledge, the target isn't degraded or down"
+                        .to_string(),
+                    is_error: false,
+                });
+            }
+            None => {}
+        }
+    }
+
+    pub fn edit_ack_note(&mut self, key: KeyCode) {
+        let Some(note) = &mut self.ack_input else {
+            return;
+        };
+        match key {
+            KeyCode::Char(c) => note.push(c),
+            KeyCode::Backspace => {
+                note.pop();
+            }
+            KeyCode::Esc => self.ack_input = None,
+            KeyCode::Enter => {
+                if let (Some(note), TabMode::Individual(idx)) =
+                    (self.ack_input.take(), self.tab_mode)
+                {
+                    self.send_command(MonitorCommand::Acknowledge(
+                        idx,
+                        Acknowledgement::new(note.trim().to_string()),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn request_clear_history(&mut self) {
+        if let TabMode::Individual(idx) = self.tab_mode {
+            self.clear_confirm = Some(idx);
+        }
+    }
+
+    pub fn confirm_clear_history(&mut self, key: KeyCode) {
+        let Some(idx) = self.clear_confirm.take() else {
+            return;
+        };
+        if matches!(key, KeyCode::Enter | KeyCode::Char('y')) {
+            // The cursor points at a sample that is about to go
+            self.chart_cursor = None;
+            self.send_command(MonitorCommand::ClearHistory(idx));
+        }
+    }
+
+    fn send_command(&mut self, command: MonitorCommand) {
+        if self.commands.send(command).is_err() {
+            self.notice = Some(Notice {
+                title: "Command Failed".to_string(),
+                message: "Monitoring has stopped".to_string(),
+                is_error: true,
+            });
+        }
+    }
+
+    // The zoomed range, or else the stats window; None shows the whole history
+    fn view_range(&self) -> Option<(DateTime<Utc>, Option<DateTime<Utc>>)> {
+        match (self.zoom, self.stats_window.duration(), &self.frozen) {
+            (Some((from, to)), _, _) => Some((from, Some(to))),
+            // A frozen screen's window ends where it froze
+            (None, Some(since), Some(frozen)) => Some((frozen.at - since, Some(frozen.at))),
+            (None, Some(since), None) => Some((clock::now() - since, None)),
+            (None, None, _) => None,
+        }
+    }
+
+    fn view_label(&self) -> String {
+        match (self.zoom, self.stats_window) {
+            (Some((from, to)), _) => format!(
+                "zoomed {}-{}",
+                from.with_timezone(&chrono::Local).format("%H:%M:%S"),
+                to.with_timezone(&chrono::Local).format("%H:%M:%S")
+            ),
+            (None, window) => window.label().to_string(),
+        }
+    }
+
+    // Steps the chart cursor through the current target's pings, starting from the newest
+    pub async fn move_chart_cursor(&mut self, step: isize) {
+        let TabMode::Individual(idx) = self.tab_mode else {
+            return;
+        };
+        let timestamps: Vec<DateTime<Utc>> = {
+            let targets = self.targets.lock().await;
+            let Some(target) = targets.get(idx) else {
+                return;
+            };
+            let target = match self.view_range() {
+                Some((from, to)) => target.between(from, to),
+                None => target.clone(),
+            };
+            charted(&target, self.chart_span)
+                .ping_history
+                .iter()
+                .map(|r| r.timestamp)
+                .collect()
+        };
+        let Some(last) = timestamps.len().checked_sub(1) else {
+            return;
+        };
+
+        let position = match self
+            .chart_cursor
+            .and_then(|cursor| timestamps.iter().position(|&t| t == cursor))
+        {
+            Some(position) => position.saturating_add_signed(step).min(last),
+            None => last,
+        };
+        self.chart_cursor = Some(timestamps[position]);
+    }
+
+    // The first press marks one end of the range at the cursor, the second zooms to it
+    pub fn mark_zoom(&mut self) {
+        let Some(cursor) = self.chart_cursor else {
+            self.notice = Some(Notice {
+                title: "Zoom".to_string(),
+                message: "Pick a sample on the ping chart with Left/Right first".to_string(),
+                is_error: false,
+            });
+            return;
+        };
+
+        match self.zoom_mark.take() {
+            None => self.zoom_mark = Some(cursor),
+            Some(mark) if mark == cursor => {
+                self.zoom_mark = Some(mark);
+                self.notice = Some(Notice {
+                    title: "Zoom".to_string(),
+                    message: "Move the cursor to the other end of the range".to_string(),
+                    is_error: false,
+                });
+            }
+            Some(mark) => self.zoom = Some((mark.min(cursor), mark.max(cursor))),
+        }
+    }
+
+    // Towards fewer, more detailed samples for a negative step, the whole history for positive.
+    // Spans at least as long as every history would show the same as None, so are skipped
+    pub async fn step_chart_span(&mut self, step: isize) {
+        let longest = {
+            let targets = self.targets.lock().await;
+            targets
+                .iter()
+                .map(|t| t.ping_history.len().max(t.ssh_history.len()))
+                .max()
+                .unwrap_or(0)
+        };
+        let spans: Vec<Option<usize>> = CHART_SPANS
+            .into_iter()
+            .filter(|span| span.is_none_or(|count| count < longest))
+            .collect();
+        // The current span may have been skipped since, as histories grow and get cleared
+        let current = self
+            .chart_span
+            .and_then(|current| {
+                spans
+                    .iter()
+                    .position(|span| span.is_none_or(|count| count >= current))
+            })
+            .unwrap_or(spans.len() - 1);
+        let next = current.saturating_add_signed(step).min(spans.len() - 1);
+        self.chart_span = spans[next];
+    }
+
+    fn chart_span_label(&self) -> String {
+        match self.chart_span {
+            Some(count) => format!(", charts: last {} samples", count),
+            None => String::new(),
+        }
+    }
+
+    pub fn reset_zoom(&mut self) {
+        self.zoom = None;
+        self.zoom_mark = None;
+        self.chart_cursor = None;
+    }
+
+    pub fn scroll_samples(&mut self, key: KeyCode) {
+        let state = match self.current_plot_view {
+            PlotView::Events => &mut self.events_table_state,
+            _ => &mut self.samples_table_state,
+        };
+        match key {
+            KeyCode::Up => state.select_previous(),
+            KeyCode::Down => state.select_next(),
+            KeyCode::PageUp => state.scroll_up_by(10),
+            KeyCode::PageDown => state.scroll_down_by(10),
+            KeyCode::Home => state.select_first(),
+            KeyCode::End => state.select_last(),
+            _ => {}
+        }
+    }
 }
 
-pub async fn run_ui(targets: Arc<Mutex<Vec<TargetStats>>>) -> Result<()> {
+pub async fn run_ui(
+    targets: Arc<Mutex<Vec<TargetStats>>>,
+    status: Arc<Mutex<MonitorStatus>>,
+    commands: mpsc::UnboundedSender<MonitorCommand>,
+    theme: Theme,
+    export_timestamps: ExportTimestamps,
+    inline: bool,
+) -> Result<()> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
 
-    let mut app = App::new(targets);
+    // Inline mode draws in a fixed-height region of the normal screen, so earlier output and
+    // the final frame stay in the scrollback; mouse capture would block scrolling back to them
+    let mut terminal = if inline {
+        let (_, rows) = crossterm::terminal::size()?;
+        let height = INLINE_HEIGHT.min(rows.saturating_sub(1)).max(1);
+        Terminal::with_options(
+            CrosstermBackend::new(stdout),
+            TerminalOptions {
+                viewport: Viewport::Inline(height),
+            },
+        )?
+    } else {
+        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+        Terminal::new(CrosstermBackend::new(stdout))?
+    };
+
+    let mut app = App::new(targets, status, commands, theme, export_timestamps);
     let res = run_app(&mut terminal, &mut app).await;
 
     disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
+    if inline {
+        println!();
+    } else {
+        execute!(
+            terminal.backend_mut(),
+            LeaveAlternateScreen,
+            DisableMouseCapture
+        )?;
+    }
     terminal.show_cursor()?;
 
     if let Err(err) = res {
@@ -121,52 +1030,227 @@ pub async fn run_ui(targets: Arc<Mutex<Vec<TargetStats>>>) -> Result<()> {
 
 async fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()> {
     loop {
-        let targets = app.targets.lock().await;
-        terminal.draw(|f| ui(f, app, &targets))?;
-        drop(targets);
-
-        if event::poll(std::time::Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Char('q') => {
-                            app.should_quit = true;
-                        }
-                        KeyCode::Tab => {
-                            let target_count = {
-                                let targets = app.targets.lock().await;
-                                targets.len()
-                            };
-                            app.next_tab(target_count);
-                        }
-                        KeyCode::BackTab => {
-                            let target_count = {
-                                let targets = app.targets.lock().await;
-                                targets.len()
-                            };
-                            app.previous_tab(target_count);
-                        }
-                        KeyCode::Char('p') => {
-                            let has_ssh = {
-                                let targets = app.targets.lock().await;
-                                match app.tab_mode {
-                                    TabMode::AllTargets => {
-                                        targets.iter().any(|t| t.target.ssh_port.is_some())
-                                    }
-                                    TabMode::Individual(idx) => {
-                                        if let Some(target) = targets.get(idx) {
-                                            target.target.ssh_port.is_some()
-                                        } else {
-                                            false
-                                        }
-                                    }
+        {
+            let status = app.status.lock().await;
+            app.stalled_for = status.stalled_for();
+            app.ping_interval_ms = status.ping_interval_ms;
+            app.diagnostics = status.diagnostics.clone();
+            app.active_alerts = status.active_alerts.clone();
+            app.quiet_hours = status.quiet_hours;
+            app.alerts_muted_until = status.alerts_muted_until;
+            if app
+                .alert_mute
+                .is_some_and(|(_, until)| until <= clock::now())
+            {
+                app.alert_mute = None;
+            }
+            app.config_warning = status.config_warning.clone();
+            app.hook_error = status.hook_error.clone();
+            app.collector_error = status.collector_error.clone();
+            app.local_outage = status.local_outage.clone();
+            app.mass_outage = status.mass_outage;
+            app.events = status.events.clone();
+        }
+        app.check_config_saved();
+        let last_restart = app
+            .events
+            .iter()
+            .rev()
+            .find(|e| e.kind == crate::events::EventKind::MonitorRestarted)
+            .map(|e| e.timestamp);
+        if last_restart != app.last_restart {
+            app.last_restart = last_restart;
+            terminal.clear()?;
+        }
+
+        let shared_targets = Arc::clone(&app.targets);
+        let live = shared_targets.lock().await;
+        app.check_freeze(&live);
+        let frozen = app
+            .frozen
+            .as_ref()
+            .map(|frozen| Arc::clone(&frozen.targets));
+        let targets: &[TargetStats] = frozen.as_deref().unwrap_or(&live);
+        let windowed;
+        let visible: &[TargetStats] = match app.view_range() {
+            Some((from, to)) => {
+                windowed = targets
+                    .iter()
+                    .map(|t| t.between(from, to))
+                    .collect::<Vec<_>>();
+                &windowed
+            }
+            None => targets,
+        };
+        terminal.draw(|f| ui(f, app, visible))?;
+        drop(live);
+
+        if event::poll(std::time::Duration::from_millis(100))?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            if app.ack_input.is_some() {
+                app.edit_ack_note(key.code);
+                continue;
+            }
+            if app.clear_confirm.is_some() {
+                app.confirm_clear_history(key.code);
+                continue;
+            }
+            if app.editor.is_some() {
+                app.edit_config(key.code);
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Char('q') => {
+                    app.should_quit = true;
+                }
+                KeyCode::Tab => {
+                    let visible = app.visible_targets(&app.targets.lock().await);
+                    app.next_tab(&visible);
+                }
+                KeyCode::BackTab => {
+                    let visible = app.visible_targets(&app.targets.lock().await);
+                    app.previous_tab(&visible);
+                }
+                KeyCode::Char('t') => {
+                    let targets = Arc::clone(&app.targets);
+                    app.cycle_tag_filter(&targets.lock().await);
+                }
+                KeyCode::Char('p') => {
+                    let has_ssh = {
+                        let targets = app.targets.lock().await;
+                        match app.tab_mode {
+                            TabMode::AllTargets => {
+                                targets.iter().any(|t| t.target.ssh_port.is_some())
+                            }
+                            TabMode::Individual(idx) => {
+                                if let Some(target) = targets.get(idx) {
+                                    target.target.ssh_port.is_some()
+                                } else {
+                                    false
                                 }
-                            };
-                            app.next_plot_view(has_ssh);
+                            }
+                        }
+                    };
+                    app.next_plot_view(has_ssh);
+                }
+                KeyCode::Char('b') => {
+                    if let TabMode::Individual(idx) = app.tab_mode {
+                        let target = {
+                            let targets = app.targets.lock().await;
+                            targets.get(idx).map(|t| {
+                                (
+                                    t.target.clone(),
+                                    t.probe_address(),
+                                    t.probe_interface(),
+                                    t.settings.loss_penalty_ms,
+                                )
+                            })
+                        };
+                        if let Some((target, addr, interface, loss_penalty_ms)) = target {
+                            let name = target.name.unwrap_or_else(|| target.ip.clone());
+                            app.start_burst(addr, target.netns, interface, name, loss_penalty_ms);
                         }
-                        _ => {}
                     }
                 }
+                KeyCode::Char('n') => {
+                    app.normalize_overlay = !app.normalize_overlay;
+                }
+                KeyCode::Char('A') => {
+                    app.overlay_aggregate = app.overlay_aggregate.next();
+                }
+                KeyCode::Char('X') => {
+                    app.request_clear_history();
+                }
+                KeyCode::Char('E') => {
+                    app.open_editor().await;
+                }
+                KeyCode::Char('c') => {
+                    app.compact = !app.compact;
+                }
+                KeyCode::Char('D') => {
+                    app.show_diagnostics = !app.show_diagnostics;
+                }
+                KeyCode::Char('x') => {
+                    app.theme = app.theme.with_time_axis(app.theme.time_axis().next());
+                }
+                KeyCode::Char('w') => {
+                    app.stats_window = app.stats_window.next();
+                }
+                KeyCode::Char('S') => {
+                    app.save_snapshot().await;
+                }
+                KeyCode::Char('C') => {
+                    app.copy_stats().await;
+                }
+                KeyCode::Char('F') => {
+                    app.toggle_freeze();
+                }
+                KeyCode::Char('s') => {
+                    app.save_config();
+                }
+                KeyCode::Char('e') => {
+                    app.export_current_chart().await;
+                }
+                KeyCode::Char('r') => {
+                    app.send_command(MonitorCommand::ProbeNow);
+                }
+                KeyCode::Char('a') => {
+                    app.toggle_ack().await;
+                }
+                KeyCode::Char('f') => {
+                    app.focus_latest_failure().await;
+                }
+                KeyCode::Char('m') => {
+                    app.cycle_alert_mute();
+                }
+                KeyCode::Esc => {
+                    app.notice = None;
+                    app.overview_selection = None;
+                    app.close_burst_popup();
+                    app.status.lock().await.config_warning = None;
+                }
+                KeyCode::Left | KeyCode::Right if app.current_plot_view == PlotView::PingOnly => {
+                    let step = if key.code == KeyCode::Left { -1 } else { 1 };
+                    app.move_chart_cursor(step).await;
+                }
+                KeyCode::Char('z') => {
+                    app.mark_zoom();
+                }
+                KeyCode::Char('Z') => {
+                    app.reset_zoom();
+                }
+                KeyCode::Char('+') | KeyCode::Char('=') => {
+                    app.step_chart_span(-1).await;
+                }
+                KeyCode::Char('-') => {
+                    app.step_chart_span(1).await;
+                }
+                KeyCode::Up
+                | KeyCode::Down
+                | KeyCode::PageUp
+                | KeyCode::PageDown
+                | KeyCode::Home
+                | KeyCode::End
+                    if matches!(
+                        app.current_plot_view,
+                        PlotView::SamplesTable | PlotView::Events
+                    ) =>
+                {
+                    app.scroll_samples(key.code);
+                }
+                KeyCode::Up | KeyCode::Down if app.tab_mode == TabMode::AllTargets => {
+                    let step = if key.code == KeyCode::Up { -1 } else { 1 };
+                    let target_count = app.visible_targets(&app.targets.lock().await).len();
+                    app.move_overview_selection(step, target_count);
+                }
+                KeyCode::Enter if app.tab_mode == TabMode::AllTargets => {
+                    let visible = app.visible_targets(&app.targets.lock().await);
+                    app.open_overview_selection(&visible);
+                }
+                _ => {}
             }
         }
 
@@ -177,171 +1261,801 @@ async fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Resul
     Ok(())
 }
 
-fn ui(f: &mut Frame, app: &App, targets: &[TargetStats]) {
+fn ui(f: &mut Frame, app: &mut App, targets: &[TargetStats]) {
+    let theme = app.theme;
     let size = f.area();
 
     if targets.is_empty() {
         let block = Block::default().title("Box Monitor").borders(Borders::ALL);
         let paragraph = Paragraph::new("No targets configured. Check ~/.config/box/.iplist")
             .block(block)
-            .style(Style::default().fg(Color::Red));
+            .style(theme.fg(Color::Red));
         f.render_widget(paragraph, size);
         return;
     }
 
-    let mut tab_titles: Vec<Line> = vec![Line::from(vec![Span::raw("All Targets")])];
-    tab_titles.extend(targets.iter().map(|target| {
+    let visible = app.visible_targets(targets);
+    let all_title = match &app.tag_filter {
+        Some(tag) => format!("All Targets [{}]", tag),
+        None => "All Targets".to_string(),
+    };
+    let mut tab_titles: Vec<Line> = vec![Line::from(vec![Span::raw(all_title)])];
+    tab_titles.extend(visible.iter().map(|&idx| {
+        let target = &targets[idx];
         let name = target.target.name.as_ref().unwrap_or(&target.target.ip);
-        Line::from(vec![Span::raw(name)])
+        let badge = match (&target.ack, &target.upstream_down) {
+            (Some(_), _) => " [ack]",
+            (None, Some(_)) => " [upstream down]",
+            (None, None) => "",
+        };
+        // Without color the health state is spelled out instead
+        match target.health {
+            Health::Degraded | Health::Down if theme.is_monochrome() => {
+                Line::from(format!("{} ({}){}", name, target.health.label(), badge))
+            }
+            _ => Line::from(vec![
+                Span::styled(name.as_str(), theme.fg(target_health_color(target))),
+                Span::raw(badge),
+            ]),
+        }
     }));
 
     let tabs = Tabs::new(tab_titles)
-        .block(Block::default().title("Targets").borders(Borders::ALL))
-        .style(Style::default().fg(Color::White))
-        .highlight_style(Style::default().fg(Color::Yellow))
-        .select(app.current_tab);
-
+        .block(
+            Block::default()
+                .title(format!(
+                    "Targets - window: {}{} ('w' to change, 'r' to probe now, 'f' for latest failure){}{}",
+                    app.view_label(),
+                    app.chart_span_label(),
+                    app.alert_mute_label(),
+                    if app.freeze_armed {
+                        " - freeze on failure armed"
+                    } else {
+                        ""
+                    }
+                ))
+                .borders(Borders::ALL),
+        )
+        .style(theme.fg(Color::White))
+        .highlight_style(theme.highlight(Color::Yellow))
+        .select(app.tab_position(&visible));
+
+    // Banners only take space while there is something to warn about
+    let mut banners = Vec::new();
+    if let Some(frozen) = &app.frozen {
+        let target = &frozen.targets[frozen.target];
+        banners.push(Line::from(format!(
+            " FROZEN at {}: {} went down ({}) - 'F' to resume the live view",
+            frozen.at.with_timezone(&chrono::Local).format("%H:%M:%S"),
+            target.target.name.as_ref().unwrap_or(&target.target.ip),
+            target.down_reason().unwrap_or("no reason given")
+        )));
+    }
+    if let Some(config_warning) = &app.config_warning {
+        banners.push(Line::from(format!(
+            " WARNING: {} - Esc to dismiss",
+            config_warning
+        )));
+    }
+    if let Some(outage) = &app.local_outage {
+        banners.push(Line::from(format!(
+            " LOCAL NETWORK UNAVAILABLE since {} ({}) - failures aren't counted against targets",
+            outage
+                .since
+                .with_timezone(&chrono::Local)
+                .format("%H:%M:%S"),
+            outage.reason
+        )));
+    }
+    // Not shown on top of a known local outage, which already explains it
+    if let Some(outage) = app.mass_outage.filter(|_| app.local_outage.is_none()) {
+        banners.push(Line::from(format!(
+            " LIKELY LOCAL NETWORK ISSUE: {} of {} targets down at once - check this machine's connection",
+            outage.down, outage.total
+        )));
+    }
+    if let Some(stalled_for) = app.stalled_for {
+        banners.push(Line::from(format!(
+            " WARNING: monitoring stalled {} seconds ago - data below is stale",
+            stalled_for.num_seconds()
+        )));
+    }
+    if let Some(hook_error) = &app.hook_error {
+        banners.push(Line::from(format!(
+            " WARNING: on_result_command failed: {}",
+            hook_error
+        )));
+    }
+    if let Some(collector_error) = &app.collector_error {
+        banners.push(Line::from(format!(
+            " WARNING: could not send statistics to the collector: {}",
+            collector_error
+        )));
+    }
+    if let Some(latest) = app.active_alerts.last() {
+        let more = match app.active_alerts.len() {
+            1 => String::new(),
+            n => format!(" (+{} more)", n - 1),
+        };
+        let level = match latest.kind.level() {
+            AlertLevel::Critical => "ALERT",
+            AlertLevel::Warning => "WARNING",
+        };
+        banners.push(Line::from(format!(
+            " {}: {}{}",
+            level, latest.message, more
+        )));
+    }
+    let warning_height = banners.len() as u16;
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
-        .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+        .constraints(
+            [
+                Constraint::Length(warning_height),
+                Constraint::Length(3),
+                Constraint::Min(0),
+            ]
+            .as_ref(),
+        )
         .split(size);
 
-    f.render_widget(tabs, chunks[0]);
+    if !banners.is_empty() {
+        let warning =
+            Paragraph::new(banners).style(theme.bg(Color::Red).add_modifier(Modifier::BOLD));
+        f.render_widget(warning, chunks[0]);
+    }
+
+    f.render_widget(tabs, chunks[1]);
 
+    // The overview covers what the tag filter lets through; without one that is everything
+    let shown: Vec<&TargetStats> = match app.tag_filter {
+        Some(_) => visible.iter().map(|&idx| &targets[idx]).collect(),
+        None => targets.iter().collect(),
+    };
     match app.tab_mode {
+        _ if app.show_diagnostics => {
+            render_diagnostics(
+                f,
+                chunks[2],
+                &app.diagnostics,
+                targets,
+                &app.events,
+                app.ping_interval_ms,
+                &theme,
+            );
+        }
         TabMode::AllTargets => {
-            render_all_targets_view(f, chunks[1], targets, app.current_plot_view);
+            let tags = tag_summary(targets, &theme);
+            render_all_targets_view(f, chunks[2], &shown, tags, app);
         }
         TabMode::Individual(idx) => {
             if let Some(target) = targets.get(idx) {
-                render_target_details(f, chunks[1], target, app.current_plot_view);
+                let reference = reference_for(target, targets);
+                render_target_details(f, chunks[2], target, reference, app);
             }
         }
     }
+
+    if let (TabMode::AllTargets, Some(idx)) = (app.tab_mode, app.overview_selection)
+        && !app.show_diagnostics
+        && let Some(target) = shown.get(idx)
+    {
+        render_failures_popup(f, chunks[2], target, &theme);
+    }
+
+    if let Some(popup) = app.burst_popup.lock().unwrap().as_ref() {
+        render_burst_popup(f, size, popup, &theme);
+    }
+
+    if let Some(notice) = &app.notice {
+        render_notice_popup(f, size, notice, &theme);
+    }
+
+    if let (Some(note), TabMode::Individual(idx)) = (&app.ack_input, app.tab_mode)
+        && let Some(target) = targets.get(idx)
+    {
+        let name = target.target.name.as_ref().unwrap_or(&target.target.ip);
+        render_input_popup(f, size, &format!("Acknowledge {}", name), note, &theme);
+    }
+
+    if let Some(editor) = &mut app.editor {
+        render_config_editor(f, size, editor, &theme);
+    }
+
+    if let Some(idx) = app.clear_confirm
+        && let Some(target) = targets.get(idx)
+    {
+        let name = target.target.name.as_ref().unwrap_or(&target.target.ip);
+        render_confirm_popup(
+            f,
+            size,
+            "Clear History",
+            &format!(
+                "Drop all samples, failures and statistics for {}? Other targets are kept.",
+                name
+            ),
+            &theme,
+        );
+    }
 }
 
 fn render_all_targets_view(
     f: &mut Frame,
     area: Rect,
-    targets: &[TargetStats],
-    plot_view: PlotView,
+    targets: &[&TargetStats],
+    tags: Option<Line>,
+    app: &mut App,
 ) {
+    let theme = app.theme;
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(3), Constraint::Min(10)])
+        .constraints([
+            Constraint::Length(3 + tags.is_some() as u16),
+            Constraint::Min(10),
+        ])
         .split(area);
 
-    render_all_targets_info(f, chunks[0], targets);
-    render_all_targets_charts(f, chunks[1], targets, plot_view);
+    render_all_targets_info(f, chunks[0], targets, tags, &theme);
+    render_all_targets_charts(f, chunks[1], targets, app);
 }
 
-fn render_target_details(f: &mut Frame, area: Rect, target: &TargetStats, plot_view: PlotView) {
+fn render_target_details(
+    f: &mut Frame,
+    area: Rect,
+    target: &TargetStats,
+    reference: Option<&TargetStats>,
+    app: &mut App,
+) {
+    let theme = app.theme;
+    if app.compact {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(area);
+        render_compact_summary(f, chunks[0], target, &theme);
+        render_single_target_charts(f, chunks[1], target, reference, app);
+        return;
+    }
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3),
+            Constraint::Length(
+                4 + target.target.burst.is_some() as u16
+                    + target.neighbor.is_some() as u16
+                    + target.rate_limit_pattern().is_some() as u16,
+            ),
             Constraint::Length(8),
             Constraint::Min(10),
         ])
         .split(area);
 
-    render_target_info(f, chunks[0], target);
-    render_statistics(f, chunks[1], target);
-    render_single_target_charts(f, chunks[2], target, plot_view);
+    let window_label = match (app.zoom, app.stats_window) {
+        (None, StatsWindow::All) => "history".to_string(),
+        _ => app.view_label(),
+    };
+    render_target_info(f, chunks[0], target, reference, &window_label, &theme);
+    render_statistics(f, chunks[1], target, &theme);
+    render_single_target_charts(f, chunks[2], target, reference, app);
+}
+
+fn render_target_info(
+    f: &mut Frame,
+    area: Rect,
+    target: &TargetStats,
+    reference: Option<&TargetStats>,
+    window_label: &str,
+    theme: &Theme,
+) {
+    let target_name = target.target.name.as_ref().unwrap_or(&target.target.ip);
+
+    let mut spans = vec![
+        Span::raw("Target: "),
+        Span::styled(target_name, theme.fg(Color::Cyan)),
+        Span::raw(" ("),
+        Span::raw(&target.target.ip),
+    ];
+    // A hostname shows where it currently points, or why it points nowhere
+    if let Some(resolution) = &target.resolution {
+        spans.push(match resolution.current {
+            Some(addr) => Span::raw(format!(" -> {}", addr)),
+            None => Span::styled(
+                format!(" -> {}", resolution.last_error().unwrap_or("resolving...")),
+                theme.fg(Color::Red),
+            ),
+        });
+        if let Some(timing) = resolution
+            .last_lookup()
+            .and_then(|lookup| format_lookup_timing(lookup, theme))
+        {
+            spans.push(Span::styled(timing, theme.fg(Color::DarkGray)));
+        }
+    }
+    if let Some(interface) = &target.target.interface {
+        spans.push(Span::raw(format!(" via {}", interface)));
+    }
+    if let Some(mac) = &target.mac {
+        spans.push(Span::styled(
+            format!(" at {}", mac),
+            theme.fg(Color::DarkGray),
+        ));
+    }
+    spans.extend([
+        Span::raw(") - "),
+        Span::styled(target.health.label(), theme.fg(target_health_color(target))),
+    ]);
+    if target.primary_check() == PrimaryCheck::Ssh {
+        spans.push(Span::raw(" (by SSH)"));
+    }
+    if let Some(upstream) = &target.upstream_down {
+        spans.push(Span::styled(
+            format!(" - upstream down: masked by {}'s outage", upstream),
+            theme.fg(Color::DarkGray),
+        ));
+    }
+    if let Some(backoff) = &target.backoff {
+        let wait = (backoff.next_probe_at - clock::now()).num_seconds().max(0);
+        spans.push(Span::styled(
+            format!(" - backing off, next ping in {}s", wait),
+            theme.fg(Color::DarkGray),
+        ));
+    }
+    if let Some(relative) = reference.and_then(|r| relative_summary(target, r, theme)) {
+        spans.push(Span::styled(
+            format!(" - {}", relative),
+            theme.fg(Color::Cyan),
+        ));
+    }
+    if let Some(first) = &target.duplicate_of {
+        spans.push(Span::styled(
+            format!(" - same address as {}", first),
+            theme.fg(Color::Yellow),
+        ));
+    }
+    match target.duplicate_replies {
+        0 => {}
+        1 => spans.push(Span::styled(
+            " - 1 duplicate reply (DUP!)",
+            theme.fg(Color::Red),
+        )),
+        n => spans.push(Span::styled(
+            format!(" - {} duplicate replies (DUP!)", n),
+            theme.fg(Color::Red),
+        )),
+    }
+    match &target.ack {
+        Some(ack) => spans.push(Span::styled(
+            format!(
+                " - acknowledged by {} at {}: {} ('a' to clear)",
+                ack.by,
+                ack.at.with_timezone(&chrono::Local).format("%H:%M"),
+                ack.note
+            ),
+            theme.fg(Color::Yellow),
+        )),
+        None if matches!(target.health, Health::Degraded | Health::Down) => {
+            spans.push(Span::raw(" - 'a' to acknowledge"))
+        }
+        None => {}
+    }
+    spans.push(Span::raw(" - 'b' to run a burst test"));
+
+    let availability = |availability: Option<Availability>| {
+        availability.map_or("no data".to_string(), |a| format_availability(&a))
+    };
+    let mut availability_line = format!(
+        "Availability: {} {} - session {}",
+        window_label,
+        availability(target.window_availability()),
+        availability(target.session_availability())
+    );
+    let parts = target.composite_parts();
+    if parts.len() > 1
+        && let Some(composite) = target.composite_availability()
+    {
+        let total: f64 = parts.iter().map(|(_, weight, _)| weight).sum();
+        let shares: Vec<String> = parts
+            .iter()
+            .map(|(check, weight, _)| format!("{} {:.0}%", check, weight / total * 100.0))
+            .collect();
+        availability_line.push_str(&format!(
+            " - composite {} ({})",
+            format_availability(&composite),
+            shares.join(", ")
+        ));
+    }
+    if target.target.icmp_rate_limited {
+        let throttled = target
+            .ping_history
+            .iter()
+            .filter(|r| r.rate_limited)
+            .count();
+        availability_line.push_str(&format!(
+            " - {} misses put down to rate limiting",
+            throttled
+        ));
+    }
+    let mut info_text = vec![Line::from(spans), Line::from(availability_line)];
+    if let Some(one_in) = target.rate_limit_pattern() {
+        info_text.push(Line::styled(
+            format!(
+                "Replies look rate-limited: about 1 in {} pings answered at a steady beat - set \
+                 icmp_rate_limited to stop counting the gaps as loss",
+                one_in
+            ),
+            theme.fg(Color::Yellow),
+        ));
+    }
+    if let Some(profile) = target.target.burst {
+        let steady = target
+            .ping_stats
+            .as_ref()
+            .map_or("no data".to_string(), |s| {
+                format!("{:.1}%", 100.0 - s.success_rate)
+            });
+        let burst = target
+            .burst_loss()
+            .map_or("no bursts yet".to_string(), |(loss, bursts)| {
+                format!("{:.1}% over {} bursts", loss, bursts)
+            });
+        info_text.push(Line::from(format!(
+            "Loss: steady {} - burst {} ({} pings every {} cycles)",
+            steady, burst, profile.count, profile.every_cycles
+        )));
+    }
+    // Pings are failing; whether the host still answers at layer 2 says why
+    if let Some(neighbor) = &target.neighbor {
+        info_text.push(match (neighbor.reply_ms, &neighbor.error) {
+            (Some(reply_ms), _) => Line::styled(
+                format!(
+                    "ARP: answered in {} - the host is up and not answering ICMP",
+                    theme.latency(reply_ms, 2)
+                ),
+                theme.fg(Color::Yellow),
+            ),
+            (None, None) => Line::styled(
+                "ARP: no reply either - the host looks to be off or gone",
+                theme.fg(Color::Red),
+            ),
+            (None, Some(error)) => Line::styled(
+                format!("ARP: check failed: {}", error),
+                theme.fg(Color::DarkGray),
+            ),
+        });
+    }
+
+    let paragraph = Paragraph::new(info_text)
+        .block(Block::default().title("Target Info").borders(Borders::ALL));
+    f.render_widget(paragraph, area);
+}
+
+// e.g. ", DoH: TLS 38ms + query 12ms"; encrypted lookups split out the connection setup
+fn format_lookup_timing(lookup: &crate::dns::Lookup, theme: &Theme) -> Option<String> {
+    let protocol = match lookup.protocol {
+        DnsProtocol::System => "lookup",
+        DnsProtocol::Udp => "UDP",
+        DnsProtocol::Dot => "DoT",
+        DnsProtocol::Doh => "DoH",
+    };
+    match (lookup.tls_ms, lookup.query_ms) {
+        (Some(tls_ms), Some(query_ms)) => Some(format!(
+            ", {}: TLS {} + query {}",
+            protocol,
+            theme.latency(tls_ms, 1),
+            theme.latency(query_ms, 1)
+        )),
+        (Some(tls_ms), None) => Some(format!(", {}: TLS {}", protocol, theme.latency(tls_ms, 1))),
+        (None, Some(query_ms)) => Some(format!(", {} {}", protocol, theme.latency(query_ms, 1))),
+        (None, None) => None,
+    }
+}
+
+// Compact mode's stand-in for the info and statistics panels
+fn render_compact_summary(f: &mut Frame, area: Rect, target: &TargetStats, theme: &Theme) {
+    let target_name = target.target.name.as_ref().unwrap_or(&target.target.ip);
+    let summary = |label: &str, stats: Option<&Statistics>| match stats {
+        Some(stats) => format!(
+            " - {} {} p95 {} {:.1}%",
+            label,
+            theme.latency(stats.mean, 1),
+            theme.latency(stats.p95, 1),
+            stats.success_rate
+        ),
+        None => format!(" - {} no data", label),
+    };
+
+    let mut spans = vec![
+        Span::styled(target_name, theme.fg(Color::Cyan)),
+        Span::raw(" "),
+        Span::styled(target.health.label(), theme.fg(target_health_color(target))),
+        Span::raw(summary("ping", target.ping_stats.as_ref())),
+    ];
+    if let Some(upstream) = &target.upstream_down {
+        spans.push(Span::styled(
+            format!(" - upstream down ({})", upstream),
+            theme.fg(Color::DarkGray),
+        ));
+    }
+    if target.duplicate_replies > 0 {
+        spans.push(Span::styled(
+            format!(" - {} DUP!", target.duplicate_replies),
+            theme.fg(Color::Red),
+        ));
+    }
+    if target.target.ssh_port.is_some() {
+        spans.push(Span::raw(summary("ssh", target.ssh_stats.as_ref())));
+    }
+    if target.ack.is_some() {
+        spans.push(Span::styled(" - acknowledged", theme.fg(Color::Yellow)));
+    }
+    spans.push(Span::styled(
+        " - 'c' for details",
+        theme.fg(Color::DarkGray),
+    ));
+
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+// The checks a target runs, in panel order
+fn active_checks(target: &TargetStats) -> Vec<CheckKind> {
+    let mut checks = vec![CheckKind::Ping];
+    if target.target.ssh_port.is_some() {
+        checks.push(CheckKind::Ssh);
+    }
+    checks
+}
+
+// A check's statistics, and whether it has run at all
+fn check_stats(target: &TargetStats, check: CheckKind) -> (Option<&Statistics>, bool) {
+    match check {
+        CheckKind::Ping => (target.ping_stats.as_ref(), !target.ping_history.is_empty()),
+        CheckKind::Ssh => (target.ssh_stats.as_ref(), !target.ssh_history.is_empty()),
+    }
+}
+
+// A panel per active check sharing the width, then the trend
+fn render_statistics(f: &mut Frame, area: Rect, target: &TargetStats, theme: &Theme) {
+    let checks = active_checks(target);
+    let trend_percent = if checks.len() == 1 { 35 } else { 30 };
+    let mut constraints = vec![Constraint::Fill(1); checks.len()];
+    constraints.push(Constraint::Percentage(trend_percent));
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(constraints)
+        .split(area);
+
+    // Flagged, since weighted numbers read differently from a plain average
+    let weighted = match target.settings.recency_weighting {
+        Some(weighting) => format!(
+            " (recent-weighted, half-life {})",
+            weighting.half_life_samples
+        ),
+        None => String::new(),
+    };
+
+    for (&check, &chunk) in checks.iter().zip(chunks.iter()) {
+        match check_stats(target, check) {
+            (Some(stats), _) => render_check_stats(f, chunk, check, stats, &weighted, theme),
+            (None, ran) => {
+                let block = Block::default()
+                    .title(format!("{} Stats", check.label()))
+                    .borders(Borders::ALL);
+                let data = match check {
+                    CheckKind::Ping => "ping data",
+                    CheckKind::Ssh => "SSH data",
+                };
+                let message = if ran {
+                    format!("No {} available", data)
+                } else {
+                    collecting(data)
+                };
+                f.render_widget(Paragraph::new(message).block(block), chunk);
+            }
+        }
+    }
+
+    render_stats_trend(f, chunks[chunks.len() - 1], target, theme);
+}
+
+fn render_stats_trend(f: &mut Frame, area: Rect, target: &TargetStats, theme: &Theme) {
+    let mut rows = vec![trend_row("Now", target.ping_stats.as_ref(), theme)];
+    for (label, minutes) in [("10m ago", 10), ("1h ago", 60)] {
+        let snapshot = target.snapshot_from(chrono::Duration::minutes(minutes));
+        rows.push(trend_row(
+            label,
+            snapshot.and_then(|snapshot| snapshot.ping_stats.as_ref()),
+            theme,
+        ));
+    }
+
+    let header = Row::new(vec!["", "Mean", "P95", "Success"]).style(theme.fg(Color::Yellow));
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(8),
+            Constraint::Length(9),
+            Constraint::Length(9),
+            Constraint::Min(7),
+        ],
+    )
+    .header(header)
+    .block(Block::default().title("Ping Trend").borders(Borders::ALL))
+    .style(theme.fg(Color::White));
+
+    f.render_widget(table, area);
+}
+
+fn trend_row<'a>(label: &'a str, stats: Option<&Statistics>, theme: &Theme) -> Row<'a> {
+    match stats {
+        Some(stats) => Row::new(vec![
+            label.to_string(),
+            theme.latency(stats.mean, 1),
+            theme.latency(stats.p95, 1),
+            format!("{:.1}%", stats.success_rate),
+        ]),
+        None => Row::new(vec![label, "-", "-", "-"]),
+    }
+}
+
+fn render_check_stats(
+    f: &mut Frame,
+    area: Rect,
+    check: CheckKind,
+    stats: &Statistics,
+    weighted: &str,
+    theme: &Theme,
+) {
+    // SSH checks are fewer, so a single failure moves the rate by more than a rounding step
+    let success_decimals = match check {
+        CheckKind::Ping => 1,
+        CheckKind::Ssh => 2,
+    };
+    let items = vec![
+        ListItem::new(format!("Mean: {}", theme.latency(stats.mean, 2))),
+        ListItem::new(format!("Median: {}", theme.latency(stats.median, 2))),
+        ListItem::new(format!(
+            "Min/Max: {}/{}",
+            theme.latency(stats.min, 2),
+            theme.latency(stats.max, 2)
+        )),
+        ListItem::new(format!("P95: {}", theme.latency(stats.p95, 2))),
+        ListItem::new(format!(
+            "Success: {}",
+            format_success(stats, success_decimals)
+        )),
+        ListItem::new(format!(
+            "Effective: {}",
+            theme.latency(stats.effective_latency, 2)
+        )),
+    ];
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(format!(
+                    "{} Stats{}{}",
+                    check.label(),
+                    weighted,
+                    outlier_note(stats, theme)
+                ))
+                .borders(Borders::ALL),
+        )
+        .style(theme.fg(Color::White));
+
+    f.render_widget(list, area);
+}
+
+// Left out of the numbers, but not hidden
+fn outlier_note(stats: &Statistics, theme: &Theme) -> String {
+    match stats.outlier_limit {
+        Some(limit) => format!(
+            " - {} outliers over {} excluded",
+            stats.outliers_excluded,
+            theme.latency(limit, 1)
+        ),
+        None => String::new(),
+    }
 }
 
-fn render_target_info(f: &mut Frame, area: Rect, target: &TargetStats) {
-    let target_name = target.target.name.as_ref().unwrap_or(&target.target.ip);
+// e.g. "100.0% (n=3, 95% CI 44–100%)", so a perfect record over a few samples isn't over-trusted
+pub fn format_success(stats: &Statistics, decimals: usize) -> String {
+    format!(
+        "{:.*}% (n={}, 95% CI {:.0}–{:.0}%)",
+        decimals,
+        stats.success_rate,
+        stats.total_count,
+        stats.success_ci_low,
+        stats.success_ci_high
+    )
+}
 
-    let info_text = vec![Line::from(vec![
-        Span::raw("Target: "),
-        Span::styled(target_name, Style::default().fg(Color::Cyan)),
-        Span::raw(" ("),
-        Span::raw(&target.target.ip),
-        Span::raw(")"),
-    ])];
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
 
-    let paragraph = Paragraph::new(info_text)
-        .block(Block::default().title("Target Info").borders(Borders::ALL));
-    f.render_widget(paragraph, area);
+// Empty panels before their first result, so startup doesn't look broken; the frame follows
+// the clock, as the screen redraws every 100ms
+pub fn collecting(what: &str) -> String {
+    let frame = (clock::now().timestamp_millis() / 100) as usize % SPINNER.len();
+    format!("{} Collecting {}…", SPINNER[frame], what)
 }
 
-fn render_statistics(f: &mut Frame, area: Rect, target: &TargetStats) {
-    let has_ssh = target.target.ssh_port.is_some();
-
-    let chunks = if has_ssh {
-        Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(area)
-    } else {
-        Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(100)])
-            .split(area)
+// e.g. "99.870% (2.9 nines, 52s down)"
+pub fn format_availability(availability: &Availability) -> String {
+    let Some(nines) = availability.nines() else {
+        return "100% (no downtime)".to_string();
     };
+    format!(
+        "{:.3}% ({:.1} nines, {} down)",
+        availability.ratio * 100.0,
+        nines,
+        format_downtime(availability.downtime)
+    )
+}
 
-    if let Some(ping_stats) = &target.ping_stats {
-        render_ping_stats(f, chunks[0], ping_stats);
-    } else {
-        let block = Block::default().title("Ping Stats").borders(Borders::ALL);
-        let paragraph = Paragraph::new("No ping data available").block(block);
-        f.render_widget(paragraph, chunks[0]);
+pub fn format_downtime(downtime: chrono::Duration) -> String {
+    let secs = downtime.num_seconds();
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
     }
+}
 
-    if has_ssh {
-        if let Some(ssh_stats) = &target.ssh_stats {
-            render_ssh_stats(f, chunks[1], ssh_stats);
-        } else {
-            let block = Block::default().title("SSH Stats").borders(Borders::ALL);
-            let paragraph = Paragraph::new("No SSH data available").block(block);
-            f.render_widget(paragraph, chunks[1]);
-        }
+fn health_color(health: Health) -> Color {
+    match health {
+        Health::Unknown => Color::White,
+        Health::Healthy => Color::Green,
+        Health::Degraded => Color::Yellow,
+        Health::Down => Color::Red,
     }
 }
 
-fn render_ping_stats(f: &mut Frame, area: Rect, stats: &Statistics) {
-    let items = vec![
-        ListItem::new(format!("Mean: {:.2}ms", stats.mean)),
-        ListItem::new(format!("Median: {:.2}ms", stats.median)),
-        ListItem::new(format!("Min/Max: {:.2}/{:.2}ms", stats.min, stats.max)),
-        ListItem::new(format!("P95: {:.2}ms", stats.p95)),
-        ListItem::new(format!("Success: {:.1}%", stats.success_rate)),
-    ];
-
-    let list = List::new(items)
-        .block(Block::default().title("Ping Stats").borders(Borders::ALL))
-        .style(Style::default().fg(Color::White));
-
-    f.render_widget(list, area);
+// Dimmed while the failures are down to an upstream outage
+fn target_health_color(target: &TargetStats) -> Color {
+    match target.upstream_down {
+        Some(_) => Color::DarkGray,
+        None => health_color(target.health),
+    }
 }
 
-fn render_ssh_stats(f: &mut Frame, area: Rect, stats: &Statistics) {
-    let items = vec![
-        ListItem::new(format!("Mean: {:.2}ms", stats.mean)),
-        ListItem::new(format!("Median: {:.2}ms", stats.median)),
-        ListItem::new(format!("Min/Max: {:.2}/{:.2}ms", stats.min, stats.max)),
-        ListItem::new(format!("P95: {:.2}ms", stats.p95)),
-        ListItem::new(format!("Success: {:.2}%", stats.success_rate)),
-    ];
-
-    let list = List::new(items)
-        .block(Block::default().title("SSH Stats").borders(Borders::ALL))
-        .style(Style::default().fg(Color::White));
-
-    f.render_widget(list, area);
-}
+fn render_all_targets_info(
+    f: &mut Frame,
+    area: Rect,
+    targets: &[&TargetStats],
+    tags: Option<Line>,
+    theme: &Theme,
+) {
+    let count = |health: Health| targets.iter().filter(|t| t.health == health).count();
+    let masked = match targets.iter().filter(|t| t.upstream_down.is_some()).count() {
+        0 => String::new(),
+        masked => format!(", {} masked by upstream outages", masked),
+    };
 
-fn render_all_targets_info(f: &mut Frame, area: Rect, targets: &[TargetStats]) {
-    let info_text = vec![Line::from(vec![
+    let mut info_text = vec![Line::from(vec![
         Span::raw("Monitoring "),
+        Span::styled(format!("{} targets", targets.len()), theme.fg(Color::Cyan)),
+        Span::raw(" ("),
         Span::styled(
-            format!("{} targets", targets.len()),
-            Style::default().fg(Color::Cyan),
+            format!("{} healthy", count(Health::Healthy)),
+            theme.fg(health_color(Health::Healthy)),
+        ),
+        Span::raw(", "),
+        Span::styled(
+            format!("{} degraded", count(Health::Degraded)),
+            theme.fg(health_color(Health::Degraded)),
+        ),
+        Span::raw(", "),
+        Span::styled(
+            format!("{} down", count(Health::Down)),
+            theme.fg(health_color(Health::Down)),
+        ),
+        Span::styled(masked, theme.fg(Color::DarkGray)),
+        Span::raw(
+            ") - Use Tab/Shift+Tab to switch views, 'p' to cycle plot types, Up/Down to peek at failures",
         ),
-        Span::raw(" - Use Tab/Shift+Tab to switch views, 'p' to cycle plot types"),
     ])];
+    info_text.extend(tags);
 
     let paragraph = Paragraph::new(info_text).block(
         Block::default()
@@ -351,24 +2065,132 @@ fn render_all_targets_info(f: &mut Frame, area: Rect, targets: &[TargetStats]) {
     f.render_widget(paragraph, area);
 }
 
-fn render_all_targets_charts(
-    f: &mut Frame,
-    area: Rect,
-    targets: &[TargetStats],
-    plot_view: PlotView,
-) {
-    match plot_view {
+// Health per tag across every target, whatever the filter, e.g. "prod 3/4 healthy, 1 down";
+// None when no target is tagged
+fn tag_summary(targets: &[TargetStats], theme: &Theme) -> Option<Line<'static>> {
+    let mut tags: Vec<&String> = targets.iter().flat_map(|t| &t.target.tags).collect();
+    tags.sort();
+    tags.dedup();
+    if tags.is_empty() {
+        return None;
+    }
+
+    let mut spans = vec![Span::raw("Tags ('t' to filter): ")];
+    for (i, tag) in tags.into_iter().enumerate() {
+        let tagged: Vec<&TargetStats> = targets
+            .iter()
+            .filter(|t| t.target.tags.contains(tag))
+            .collect();
+        let count = |health: Health| tagged.iter().filter(|t| t.health == health).count();
+        if i > 0 {
+            spans.push(Span::raw(" | "));
+        }
+        spans.push(Span::styled(tag.clone(), theme.fg(Color::Cyan)));
+        spans.push(Span::raw(format!(
+            " {}/{} healthy",
+            count(Health::Healthy),
+            tagged.len()
+        )));
+        for health in [Health::Degraded, Health::Down] {
+            if count(health) > 0 {
+                spans.push(Span::styled(
+                    format!(", {} {}", count(health), health.label()),
+                    theme.fg(health_color(health)),
+                ));
+            }
+        }
+    }
+    Some(Line::from(spans))
+}
+
+// The latest `span` samples of each target, for the charts plotted over time
+fn charted(target: &TargetStats, span: Option<usize>) -> Cow<'_, TargetStats> {
+    match span {
+        Some(count) if target.ping_history.len() > count || target.ssh_history.len() > count => {
+            Cow::Owned(target.latest_samples(count))
+        }
+        _ => Cow::Borrowed(target),
+    }
+}
+
+// Only the targets with more than `span` samples are copied
+fn charted_all<'a>(targets: &[&'a TargetStats], span: Option<usize>) -> Vec<Cow<'a, TargetStats>> {
+    targets.iter().map(|t| charted(t, span)).collect()
+}
+
+fn render_all_targets_charts(f: &mut Frame, area: Rect, targets: &[&TargetStats], app: &mut App) {
+    let theme = app.theme;
+    let charted = charted_all(targets, app.chart_span);
+    let charted: Vec<&TargetStats> = charted.iter().map(|t| t.as_ref()).collect();
+    match app.current_plot_view {
         PlotView::AllTargets => {
-            render_all_targets_overlay_chart(f, area, targets);
+            render_all_targets_overlay_chart(
+                f,
+                area,
+                &charted,
+                app.normalize_overlay,
+                app.overlay_aggregate,
+                &mut app.axis_bounds,
+                &theme,
+            );
         }
         PlotView::PingOnly => {
-            render_all_targets_ping_chart(f, area, targets);
+            render_all_targets_ping_chart(f, area, &charted, &mut app.axis_bounds, &theme);
         }
         PlotView::SshOnly => {
-            render_all_targets_ssh_chart(f, area, targets);
+            render_all_targets_ssh_chart(f, area, &charted, &mut app.axis_bounds, &theme);
+        }
+        PlotView::Throughput => {
+            let block = Block::default()
+                .title("SSH Throughput - Press 'p' to cycle views")
+                .borders(Borders::ALL);
+            let paragraph =
+                Paragraph::new("Select a target's tab to see its throughput").block(block);
+            f.render_widget(paragraph, area);
         }
         PlotView::FailureChart => {
-            render_all_targets_failure_chart(f, area, targets);
+            render_all_targets_failure_chart(f, area, targets, &theme);
+        }
+        PlotView::SamplesTable => {
+            render_all_targets_samples_table(
+                f,
+                area,
+                targets,
+                &mut app.samples_table_state,
+                &theme,
+            );
+        }
+        PlotView::Histogram => {
+            let block = Block::default()
+                .title("Ping Latency Histogram - Press 'p' to cycle views")
+                .borders(Borders::ALL);
+            let paragraph =
+                Paragraph::new("Select a target's tab to see its latency histogram").block(block);
+            f.render_widget(paragraph, area);
+        }
+        PlotView::Gauges => {
+            render_gauges(f, area, targets, &theme);
+        }
+        PlotView::Relative => {
+            let block = Block::default()
+                .title("Latency vs Reference - Press 'p' to cycle views")
+                .borders(Borders::ALL);
+            let paragraph =
+                Paragraph::new("Select a target's tab to compare it to the reference").block(block);
+            f.render_widget(paragraph, area);
+        }
+        PlotView::Sampling => {
+            render_all_targets_sampling(f, area, &charted, app.ping_interval_ms, &theme);
+        }
+        PlotView::Events => {
+            render_event_feed(
+                f,
+                area,
+                &app.events,
+                None,
+                &mut app.events_table_state,
+                &theme,
+            );
         }
     }
 }
@@ -377,47 +2199,121 @@ fn render_single_target_charts(
     f: &mut Frame,
     area: Rect,
     target: &TargetStats,
-    plot_view: PlotView,
+    reference: Option<&TargetStats>,
+    app: &mut App,
 ) {
+    let theme = app.theme;
     let has_ssh = target.target.ssh_port.is_some();
+    let charted_target = charted(target, app.chart_span);
+    let charted_reference = reference.map(|r| charted(r, app.chart_span));
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
         .split(area);
 
-    match plot_view {
+    match app.current_plot_view {
         PlotView::AllTargets => {
-            render_overlay_chart(f, chunks[0], target);
+            render_overlay_chart(f, chunks[0], &charted_target, &mut app.axis_bounds, &theme);
         }
         PlotView::PingOnly => {
-            render_ping_chart(f, chunks[0], target);
+            render_ping_chart(
+                f,
+                chunks[0],
+                &charted_target,
+                app.chart_cursor,
+                app.zoom_mark,
+                &mut app.axis_bounds,
+                &theme,
+            );
         }
         PlotView::SshOnly => {
             if has_ssh {
-                render_ssh_chart(f, chunks[0], target);
+                render_ssh_chart(f, chunks[0], &charted_target, &mut app.axis_bounds, &theme);
             } else {
                 let block = Block::default().title("SSH Chart").borders(Borders::ALL);
                 let paragraph = Paragraph::new("SSH monitoring not configured").block(block);
                 f.render_widget(paragraph, chunks[0]);
             }
         }
+        PlotView::Throughput => {
+            render_throughput_chart(f, chunks[0], target, &theme);
+        }
         PlotView::FailureChart => {
-            render_single_target_failure_chart(f, chunks[0], target);
+            render_single_target_failure_chart(f, chunks[0], target, &theme);
+        }
+        PlotView::SamplesTable => {
+            render_single_target_samples_table(
+                f,
+                chunks[0],
+                target,
+                &mut app.samples_table_state,
+                &theme,
+            );
+        }
+        PlotView::Histogram => {
+            render_latency_histogram(f, chunks[0], target, &theme);
+        }
+        PlotView::Gauges => {
+            render_gauges(f, chunks[0], &[target], &theme);
+        }
+        PlotView::Relative => {
+            render_relative_chart(
+                f,
+                chunks[0],
+                &charted_target,
+                charted_reference.as_deref(),
+                &theme,
+            );
+        }
+        PlotView::Sampling => {
+            render_sampling_chart(f, chunks[0], &charted_target, app.ping_interval_ms, &theme);
+        }
+        PlotView::Events => {
+            render_event_feed(
+                f,
+                chunks[0],
+                &app.events,
+                Some(&target.target.ip),
+                &mut app.events_table_state,
+                &theme,
+            );
         }
     }
 
-    render_box_plot(f, chunks[1], target);
+    render_box_plot(f, chunks[1], target, &theme);
+}
+
+fn ping_times(target: &TargetStats) -> Vec<DateTime<Utc>> {
+    target.ping_history.iter().map(|r| r.timestamp).collect()
+}
+
+fn ssh_times(target: &TargetStats) -> Vec<DateTime<Utc>> {
+    target.ssh_history.iter().map(|r| r.timestamp).collect()
+}
+
+// Several series share one time axis, read off the longest of them
+fn longest_times(times: impl IntoIterator<Item = Vec<DateTime<Utc>>>) -> Vec<DateTime<Utc>> {
+    times
+        .into_iter()
+        .max_by_key(|t| t.len())
+        .unwrap_or_default()
 }
 
-fn render_overlay_chart(f: &mut Frame, area: Rect, target: &TargetStats) {
+fn render_overlay_chart(
+    f: &mut Frame,
+    area: Rect,
+    target: &TargetStats,
+    axis: &mut AxisBounds,
+    theme: &Theme,
+) {
     let has_ssh = target.target.ssh_port.is_some();
 
     if target.ping_history.is_empty() && (!has_ssh || target.ssh_history.is_empty()) {
         let block = Block::default()
             .title("Latency Overlay")
             .borders(Borders::ALL);
-        let paragraph = Paragraph::new("No data available").block(block);
+        let paragraph = Paragraph::new(collecting("data")).block(block);
         f.render_widget(paragraph, area);
         return;
     }
@@ -429,33 +2325,28 @@ fn render_overlay_chart(f: &mut Frame, area: Rect, target: &TargetStats) {
 
     let ssh_data: Vec<(f64, f64)>;
     let ping_data: Vec<(f64, f64)>;
+    let warmup_data: Vec<(f64, f64)>;
     // Ping data
     if !target.ping_history.is_empty() {
-        ping_data = target
-            .ping_history
-            .iter()
-            .enumerate()
-            .filter_map(|(i, result)| result.latency_ms.map(|latency| (i as f64, latency)))
-            .collect();
+        (ping_data, warmup_data) = split_warmup_pings(target);
 
-        if !ping_data.is_empty() {
-            max_latency = max_latency.max(ping_data.iter().map(|(_, y)| *y).fold(0.0, f64::max));
-            min_latency = min_latency.min(
-                ping_data
-                    .iter()
-                    .map(|(_, y)| *y)
-                    .fold(f64::INFINITY, f64::min),
-            );
+        if !ping_data.is_empty() || !warmup_data.is_empty() {
+            let all_pings = || ping_data.iter().chain(&warmup_data).map(|(_, y)| *y);
+            max_latency = max_latency.max(scale_max(all_pings(), target.ping_scale_limit()));
+            min_latency = min_latency.min(all_pings().fold(f64::INFINITY, f64::min));
             max_length = max_length.max(target.ping_history.len());
 
             datasets.push(
                 Dataset::default()
                     .name("Ping")
-                    .marker(symbols::Marker::Braille)
-                    .style(Style::default().fg(Color::Green))
-                    .graph_type(GraphType::Line)
+                    .marker(theme.check_marker(CheckKind::Ping, 0))
+                    .style(theme.fg(Color::Green))
+                    .graph_type(theme.check_graph(CheckKind::Ping))
                     .data(&ping_data),
             );
+            if !warmup_data.is_empty() {
+                datasets.push(warmup_dataset(&warmup_data, theme));
+            }
         }
     }
     // SSH data
@@ -468,7 +2359,10 @@ fn render_overlay_chart(f: &mut Frame, area: Rect, target: &TargetStats) {
             .collect();
 
         if !ssh_data.is_empty() {
-            max_latency = max_latency.max(ssh_data.iter().map(|(_, y)| *y).fold(0.0, f64::max));
+            max_latency = max_latency.max(scale_max(
+                ssh_data.iter().map(|(_, y)| *y),
+                target.ssh_scale_limit(),
+            ));
             min_latency = min_latency.min(
                 ssh_data
                     .iter()
@@ -480,9 +2374,9 @@ fn render_overlay_chart(f: &mut Frame, area: Rect, target: &TargetStats) {
             datasets.push(
                 Dataset::default()
                     .name("SSH")
-                    .marker(symbols::Marker::Braille)
-                    .style(Style::default().fg(Color::Blue))
-                    .graph_type(GraphType::Line)
+                    .marker(theme.check_marker(CheckKind::Ssh, 1))
+                    .style(theme.fg(Color::Blue))
+                    .graph_type(theme.check_graph(CheckKind::Ssh))
                     .data(&ssh_data),
             );
         }
@@ -497,35 +2391,42 @@ fn render_overlay_chart(f: &mut Frame, area: Rect, target: &TargetStats) {
         return;
     }
 
-    let y_max = max_latency * 1.1;
+    let y_max = axis.smooth(
+        format!("overlay {}", target.target.ip),
+        max_latency * 1.1,
+        theme,
+    );
     let y_min = min_latency.min(0.0);
     let x_max = max_length as f64;
 
-    let y_labels: Vec<String> = (0..=5)
-        .map(|i| format!("{:.1}", y_min + (y_max - y_min) * i as f64 / 5.0))
-        .collect();
+    let unit = theme.axis_unit(y_max);
+    let y_labels = axis_labels(y_min, y_max, unit);
 
-    let x_labels: Vec<String> = (0..=5)
-        .map(|i| format!("{:.0}", x_max * i as f64 / 5.0))
-        .collect();
+    let x_labels = theme.time_labels(
+        x_max,
+        &longest_times([ping_times(target), ssh_times(target)]),
+    );
 
     let chart = Chart::new(datasets)
         .block(
             Block::default()
-                .title("Latency Overlay (ms) - Press 'p' to cycle views")
+                .title(format!(
+                    "Latency Overlay ({}) - Press 'p' to cycle views",
+                    unit.symbol()
+                ))
                 .borders(Borders::ALL),
         )
         .x_axis(
             Axis::default()
-                .title("Time (samples)")
-                .style(Style::default().fg(Color::Gray))
+                .title(theme.time_axis().title())
+                .style(theme.fg(Color::Gray))
                 .bounds([0.0, x_max])
                 .labels(x_labels.iter().map(|s| s.as_str()).collect::<Vec<_>>()),
         )
         .y_axis(
             Axis::default()
-                .title("Latency (ms)")
-                .style(Style::default().fg(Color::Gray))
+                .title(format!("Latency ({})", unit.symbol()))
+                .style(theme.fg(Color::Gray))
                 .bounds([y_min, y_max])
                 .labels(y_labels.iter().map(|s| s.as_str()).collect::<Vec<_>>()),
         );
@@ -533,72 +2434,222 @@ fn render_overlay_chart(f: &mut Frame, area: Rect, target: &TargetStats) {
     f.render_widget(chart, area);
 }
 
-fn render_ping_chart(f: &mut Frame, area: Rect, target: &TargetStats) {
-    if target.ping_history.is_empty() {
-        let block = Block::default().title("Ping Latency").borders(Borders::ALL);
-        let paragraph = Paragraph::new("No ping data yet...").block(block);
-        f.render_widget(paragraph, area);
-        return;
+type Series = Vec<(f64, f64)>;
+
+// Successful pings split into (counted, warmup) series
+fn split_warmup_pings(target: &TargetStats) -> (Series, Series) {
+    let mut ping_data = Vec::new();
+    let mut warmup_data = Vec::new();
+
+    for (i, result) in target.ping_history.iter().enumerate() {
+        if let Some(latency) = result.latency_ms {
+            if result.warmup {
+                warmup_data.push((i as f64, latency));
+            } else {
+                ping_data.push((i as f64, latency));
+            }
+        }
     }
 
-    let ping_data: Vec<(f64, f64)> = target
-        .ping_history
-        .iter()
-        .enumerate()
-        .filter_map(|(i, result)| result.latency_ms.map(|latency| (i as f64, latency)))
-        .collect();
+    (ping_data, warmup_data)
+}
+
+fn warmup_dataset<'a>(data: &'a [(f64, f64)], theme: &Theme) -> Dataset<'a> {
+    Dataset::default()
+        .name("Warmup")
+        .marker(symbols::Marker::Dot)
+        .style(theme.fg(Color::DarkGray))
+        .graph_type(GraphType::Scatter)
+        .data(data)
+}
 
-    if ping_data.is_empty() {
+// The top of a chart: the slowest sample that isn't an outlier. Outliers are drawn off the top
+fn scale_max(values: impl Iterator<Item = f64>, limit: f64) -> f64 {
+    values.filter(|v| *v <= limit).fold(0.0, f64::max)
+}
+
+// Full-height columns per sample, drawn under the line as a success/failure background
+fn reliability_shading(target: &TargetStats, y_max: f64) -> (Series, Series) {
+    let mut success = Vec::new();
+    let mut failure = Vec::new();
+
+    for (i, result) in target.ping_history.iter().enumerate() {
+        if result.success {
+            success.push((i as f64, y_max));
+        } else {
+            failure.push((i as f64, y_max));
+        }
+    }
+
+    (success, failure)
+}
+
+fn shading_datasets<'a>(
+    success: &'a [(f64, f64)],
+    failure: &'a [(f64, f64)],
+    theme: &Theme,
+) -> Vec<Dataset<'a>> {
+    // Solid blocks in monochrome would hide the line, so only failures get a light texture
+    let (marker, success_color, failure_color) = if theme.is_monochrome() {
+        (symbols::Marker::Dot, None, Color::Reset)
+    } else {
+        (
+            symbols::Marker::Block,
+            Some(Color::Rgb(0, 48, 0)),
+            Color::Rgb(80, 0, 0),
+        )
+    };
+
+    let mut datasets = Vec::new();
+    if let Some(color) = success_color {
+        datasets.push(
+            Dataset::default()
+                .marker(marker)
+                .style(theme.fg(color))
+                .graph_type(GraphType::Bar)
+                .data(success),
+        );
+    }
+    // Failures last, so a column mixing both reads as an outage
+    datasets.push(
+        Dataset::default()
+            .marker(marker)
+            .style(theme.fg(failure_color))
+            .graph_type(GraphType::Bar)
+            .data(failure),
+    );
+    datasets
+}
+
+fn render_ping_chart(
+    f: &mut Frame,
+    area: Rect,
+    target: &TargetStats,
+    cursor: Option<DateTime<Utc>>,
+    zoom_mark: Option<DateTime<Utc>>,
+    axis: &mut AxisBounds,
+    theme: &Theme,
+) {
+    if target.ping_history.is_empty() {
         let block = Block::default().title("Ping Latency").borders(Borders::ALL);
-        let paragraph = Paragraph::new("All pings failed").block(block);
+        let paragraph = Paragraph::new(collecting("ping data")).block(block);
         f.render_widget(paragraph, area);
         return;
     }
 
-    let max_latency = ping_data.iter().map(|(_, y)| *y).fold(0.0, f64::max);
+    let (ping_data, warmup_data) = split_warmup_pings(target);
+    // Still drawn, so the failure shading shows how long the outage has lasted
+    let all_failed = ping_data.is_empty() && warmup_data.is_empty();
+
+    let max_latency = scale_max(
+        ping_data.iter().chain(&warmup_data).map(|(_, y)| *y),
+        target.ping_scale_limit(),
+    );
     let min_latency = ping_data
         .iter()
+        .chain(&warmup_data)
         .map(|(_, y)| *y)
         .fold(f64::INFINITY, f64::min);
 
-    let datasets = vec![
+    // Keep the expected-latency line on screen even when every sample is below it
+    let baseline = target.target.baseline_ms;
+    // With no latency at all there is nothing to scale to; any height shows the shading
+    let top = match max_latency.max(baseline.unwrap_or(0.0)) {
+        top if top > 0.0 => top,
+        _ => 1.0,
+    };
+    let y_max = axis.smooth(format!("ping {}", target.target.ip), top * 1.1, theme);
+    let y_min = min_latency.min(0.0);
+    let x_max = target.ping_history.len() as f64;
+    let (success_shading, failure_shading) = reliability_shading(target, y_max);
+    let baseline_data: Vec<(f64, f64)> = baseline
+        .map(|baseline| vec![(0.0, baseline), (x_max, baseline)])
+        .unwrap_or_default();
+    // Vertical lines at the cursor and the marked end of a zoom range
+    let sample_at = |timestamp: Option<DateTime<Utc>>| {
+        let timestamp = timestamp?;
+        let i = target
+            .ping_history
+            .iter()
+            .position(|r| r.timestamp == timestamp)?;
+        Some((i, &target.ping_history[i]))
+    };
+    let vertical = |i: usize| vec![(i as f64, y_min), (i as f64, y_max)];
+    let cursor_sample = sample_at(cursor);
+    let cursor_data = cursor_sample.map_or(Vec::new(), |(i, _)| vertical(i));
+    let mark_data = sample_at(zoom_mark).map_or(Vec::new(), |(i, _)| vertical(i));
+
+    let mut datasets = shading_datasets(&success_shading, &failure_shading, theme);
+    if !baseline_data.is_empty() {
+        datasets.push(
+            Dataset::default()
+                .name("Expected")
+                .marker(symbols::Marker::Braille)
+                .style(theme.fg(Color::Yellow))
+                .graph_type(GraphType::Line)
+                .data(&baseline_data),
+        );
+    }
+    datasets.push(
         Dataset::default()
             .name("Ping")
-            .marker(symbols::Marker::Braille)
-            .style(Style::default().fg(Color::Green))
-            .graph_type(GraphType::Line)
+            .marker(theme.check_marker(CheckKind::Ping, 0))
+            .style(theme.fg(Color::Green))
+            .graph_type(theme.check_graph(CheckKind::Ping))
             .data(&ping_data),
-    ];
-
-    let y_max = max_latency * 1.1;
-    let y_min = min_latency.min(0.0);
-    let x_max = target.ping_history.len() as f64;
-
-    let y_labels: Vec<String> = (0..=5)
-        .map(|i| format!("{:.1}", y_min + (y_max - y_min) * i as f64 / 5.0))
-        .collect();
-
-    let x_labels: Vec<String> = (0..=5)
-        .map(|i| format!("{:.0}", x_max * i as f64 / 5.0))
-        .collect();
+    );
+    if !warmup_data.is_empty() {
+        datasets.push(warmup_dataset(&warmup_data, theme));
+    }
+    for (data, color) in [(&mark_data, Color::Magenta), (&cursor_data, Color::White)] {
+        if !data.is_empty() {
+            datasets.push(
+                Dataset::default()
+                    .marker(symbols::Marker::Braille)
+                    .style(theme.fg(color))
+                    .graph_type(GraphType::Line)
+                    .data(data),
+            );
+        }
+    }
 
+    let unit = theme.axis_unit(y_max);
+    let y_labels = axis_labels(y_min, y_max, unit);
+
+    let x_labels = theme.time_labels(x_max, &ping_times(target));
+
+    let title = match cursor_sample {
+        Some((_, sample)) => format!(
+            "Ping Latency ({}) - cursor {} {} - 'z' to {} zoom range, 'Z' to reset",
+            unit.symbol(),
+            sample
+                .timestamp
+                .with_timezone(&chrono::Local)
+                .format("%H:%M:%S"),
+            sample
+                .latency_ms
+                .map_or("failed".to_string(), |ms| theme.latency(ms, 1)),
+            if zoom_mark.is_some() { "end" } else { "start" }
+        ),
+        None if all_failed => format!("Ping Latency ({}) - All pings failed", unit.symbol()),
+        None => format!(
+            "Ping Latency ({}) - Press 'p' to cycle views, Left/Right to pick a sample",
+            unit.symbol()
+        ),
+    };
     let chart = Chart::new(datasets)
-        .block(
-            Block::default()
-                .title("Ping Latency (ms) - Press 'p' to cycle views")
-                .borders(Borders::ALL),
-        )
+        .block(Block::default().title(title).borders(Borders::ALL))
         .x_axis(
             Axis::default()
-                .title("Time (samples)")
-                .style(Style::default().fg(Color::Gray))
+                .title(theme.time_axis().title())
+                .style(theme.fg(Color::Gray))
                 .bounds([0.0, x_max])
                 .labels(x_labels.iter().map(|s| s.as_str()).collect::<Vec<_>>()),
         )
         .y_axis(
             Axis::default()
-                .title("Latency (ms)")
-                .style(Style::default().fg(Color::Gray))
+                .title(format!("Latency ({})", unit.symbol()))
+                .style(theme.fg(Color::Gray))
                 .bounds([y_min, y_max])
                 .labels(y_labels.iter().map(|s| s.as_str()).collect::<Vec<_>>()),
         );
@@ -606,7 +2657,7 @@ fn render_ping_chart(f: &mut Frame, area: Rect, target: &TargetStats) {
     f.render_widget(chart, area);
 }
 
-fn render_box_plot(f: &mut Frame, area: Rect, target: &TargetStats) {
+fn render_box_plot(f: &mut Frame, area: Rect, target: &TargetStats, theme: &Theme) {
     if let Some(stats) = &target.ping_stats {
         let box_data = vec![
             (0.0, stats.min),
@@ -623,13 +2674,13 @@ fn render_box_plot(f: &mut Frame, area: Rect, target: &TargetStats) {
             Dataset::default()
                 .name("Box Plot")
                 .marker(symbols::Marker::Block)
-                .style(Style::default().fg(Color::Cyan))
+                .style(theme.fg(Color::Cyan))
                 .graph_type(GraphType::Line)
                 .data(&box_data),
             Dataset::default()
                 .name("Outliers")
                 .marker(symbols::Marker::Dot)
-                .style(Style::default().fg(Color::Red))
+                .style(theme.fg(Color::Red))
                 .graph_type(GraphType::Scatter)
                 .data(&outlier_data),
         ];
@@ -638,27 +2689,26 @@ fn render_box_plot(f: &mut Frame, area: Rect, target: &TargetStats) {
         let y_max = stats.max.max(stats.p99) * 1.1;
         let y_min = stats.min * 0.9;
 
-        let y_labels: Vec<String> = (0..=5)
-            .map(|i| format!("{:.1}", y_min + (y_max - y_min) * i as f64 / 5.0))
-            .collect();
+        let unit = theme.axis_unit(y_max);
+        let y_labels = axis_labels(y_min, y_max, unit);
 
         let chart = Chart::new(datasets)
             .block(
                 Block::default()
-                    .title("Ping Latency Box Plot (ms)")
+                    .title(format!("Ping Latency Box Plot ({})", unit.symbol()))
                     .borders(Borders::ALL),
             )
             .x_axis(
                 Axis::default()
                     .title("Quartiles & Percentiles")
-                    .style(Style::default().fg(Color::Gray))
+                    .style(theme.fg(Color::Gray))
                     .bounds([0.0, 7.0])
                     .labels(x_labels.to_vec()),
             )
             .y_axis(
                 Axis::default()
-                    .title("Latency (ms)")
-                    .style(Style::default().fg(Color::Gray))
+                    .title(format!("Latency ({})", unit.symbol()))
+                    .style(theme.fg(Color::Gray))
                     .bounds([y_min, y_max])
                     .labels(y_labels.iter().map(|s| s.as_str()).collect::<Vec<_>>()),
             );
@@ -668,12 +2718,24 @@ fn render_box_plot(f: &mut Frame, area: Rect, target: &TargetStats) {
         let block = Block::default()
             .title("Ping Latency Box Plot")
             .borders(Borders::ALL);
-        let paragraph = Paragraph::new("No ping data available for box plot").block(block);
-        f.render_widget(paragraph, area);
+        let message = if target.ping_history.is_empty() {
+            collecting("ping data")
+        } else {
+            "No ping data available for box plot".to_string()
+        };
+        f.render_widget(Paragraph::new(message).block(block), area);
     }
 }
 
-fn render_all_targets_overlay_chart(f: &mut Frame, area: Rect, targets: &[TargetStats]) {
+fn render_all_targets_overlay_chart(
+    f: &mut Frame,
+    area: Rect,
+    targets: &[&TargetStats],
+    normalize: bool,
+    aggregate: OverlayAggregate,
+    axis: &mut AxisBounds,
+    theme: &Theme,
+) {
     if targets.is_empty() {
         let block = Block::default()
             .title("All Targets Overlay")
@@ -686,30 +2748,14 @@ fn render_all_targets_overlay_chart(f: &mut Frame, area: Rect, targets: &[Target
     let mut all_data = Vec::new();
     let mut all_names = Vec::new();
     let mut all_colors = Vec::new();
-    let mut all_markers = Vec::new();
+    let mut all_checks = Vec::new();
     let mut max_latency: f64 = 0.0;
     let mut min_latency = f64::INFINITY;
     let mut max_length = 0;
 
-    // Define colors for different targets
-    let colors = [
-        Color::Green,
-        Color::Blue,
-        Color::Yellow,
-        Color::Magenta,
-        Color::Cyan,
-        Color::Red,
-        Color::LightGreen,
-        Color::LightBlue,
-        Color::LightYellow,
-        Color::LightMagenta,
-        Color::LightCyan,
-        Color::LightRed,
-    ];
-
     for (target_idx, target) in targets.iter().enumerate() {
         let target_name = target.target.name.as_ref().unwrap_or(&target.target.ip);
-        let color = colors[target_idx % colors.len()];
+        let color = theme.series_color(target_idx, CheckKind::Ping);
 
         // Ping data for this target
         if !target.ping_history.is_empty() {
@@ -719,10 +2765,13 @@ fn render_all_targets_overlay_chart(f: &mut Frame, area: Rect, targets: &[Target
                 .enumerate()
                 .filter_map(|(i, result)| result.latency_ms.map(|latency| (i as f64, latency)))
                 .collect();
+            let ping_data = aggregate.apply(&ping_data);
 
             if !ping_data.is_empty() {
-                max_latency =
-                    max_latency.max(ping_data.iter().map(|(_, y)| *y).fold(0.0, f64::max));
+                max_latency = max_latency.max(scale_max(
+                    ping_data.iter().map(|(_, y)| *y),
+                    target.ping_scale_limit(),
+                ));
                 min_latency = min_latency.min(
                     ping_data
                         .iter()
@@ -734,7 +2783,7 @@ fn render_all_targets_overlay_chart(f: &mut Frame, area: Rect, targets: &[Target
                 all_data.push(ping_data);
                 all_names.push(format!("{} (Ping)", target_name));
                 all_colors.push(color);
-                all_markers.push(symbols::Marker::Braille);
+                all_checks.push(CheckKind::Ping);
             }
         }
 
@@ -746,9 +2795,13 @@ fn render_all_targets_overlay_chart(f: &mut Frame, area: Rect, targets: &[Target
                 .enumerate()
                 .filter_map(|(i, result)| result.connection_time_ms.map(|time| (i as f64, time)))
                 .collect();
+            let ssh_data = aggregate.apply(&ssh_data);
 
             if !ssh_data.is_empty() {
-                max_latency = max_latency.max(ssh_data.iter().map(|(_, y)| *y).fold(0.0, f64::max));
+                max_latency = max_latency.max(scale_max(
+                    ssh_data.iter().map(|(_, y)| *y),
+                    target.ssh_scale_limit(),
+                ));
                 min_latency = min_latency.min(
                     ssh_data
                         .iter()
@@ -757,21 +2810,12 @@ fn render_all_targets_overlay_chart(f: &mut Frame, area: Rect, targets: &[Target
                 );
                 max_length = max_length.max(target.ssh_history.len());
 
-                // Use dashed line style for SSH by alternating color intensity
-                let ssh_color = match color {
-                    Color::Green => Color::LightGreen,
-                    Color::Blue => Color::LightBlue,
-                    Color::Yellow => Color::LightYellow,
-                    Color::Magenta => Color::LightMagenta,
-                    Color::Cyan => Color::LightCyan,
-                    Color::Red => Color::LightRed,
-                    _ => Color::White,
-                };
+                let ssh_color = theme.series_color(target_idx, CheckKind::Ssh);
 
                 all_data.push(ssh_data);
                 all_names.push(format!("{} (SSH)", target_name));
                 all_colors.push(ssh_color);
-                all_markers.push(symbols::Marker::Dot);
+                all_checks.push(CheckKind::Ssh);
             }
         }
     }
@@ -780,55 +2824,100 @@ fn render_all_targets_overlay_chart(f: &mut Frame, area: Rect, targets: &[Target
         let block = Block::default()
             .title("All Targets Overlay")
             .borders(Borders::ALL);
-        let paragraph = Paragraph::new("No data available for any target").block(block);
-        f.render_widget(paragraph, area);
+        let message = if targets
+            .iter()
+            .all(|t| t.ping_history.is_empty() && t.ssh_history.is_empty())
+        {
+            collecting("data")
+        } else {
+            "No data available for any target".to_string()
+        };
+        f.render_widget(Paragraph::new(message).block(block), area);
         return;
     }
 
+    // Each series as a percentage of its own median, so links with very different absolute
+    // latency can be compared on one scale
+    if normalize {
+        for data in &mut all_data {
+            let mut values: Vec<f64> = data.iter().map(|(_, y)| *y).collect();
+            values.sort_by(f64::total_cmp);
+            let baseline = values[values.len() / 2];
+            if baseline > 0.0 {
+                for (_, y) in data.iter_mut() {
+                    *y = *y / baseline * 100.0;
+                }
+            }
+        }
+        let all_values = || all_data.iter().flatten().map(|(_, y)| *y);
+        max_latency = all_values().fold(0.0, f64::max);
+        min_latency = all_values().fold(f64::INFINITY, f64::min);
+    }
+
     let datasets: Vec<Dataset> = all_data
         .iter()
         .zip(all_names.iter())
         .zip(all_colors.iter())
-        .zip(all_markers.iter())
-        .map(|(((data, name), color), marker)| {
+        .zip(all_checks.iter())
+        .enumerate()
+        .map(|(series_idx, (((data, name), color), check))| {
             Dataset::default()
                 .name(name.as_str())
-                .marker(*marker)
-                .style(Style::default().fg(*color))
-                .graph_type(GraphType::Line)
+                .marker(theme.check_marker(*check, series_idx))
+                .style(theme.fg(*color))
+                .graph_type(theme.check_graph(*check))
                 .data(data)
         })
         .collect();
 
-    let y_max = max_latency * 1.1;
+    // Normalized and aggregated lines are on other scales, so each keeps its own axis
+    let chart = format!("overview overlay {} {}", normalize, aggregate.label());
+    let y_max = axis.smooth(chart, max_latency * 1.1, theme);
     let y_min = min_latency.min(0.0);
     let x_max = max_length as f64;
 
-    let y_labels: Vec<String> = (0..=5)
-        .map(|i| format!("{:.1}", y_min + (y_max - y_min) * i as f64 / 5.0))
-        .collect();
+    let unit = theme.axis_unit(y_max);
+    let (title, y_title, y_labels) = if normalize {
+        (
+            format!(
+                "All Targets Latency Overlay (% of own median, {}) - 'n' for ms, 'A' to smooth, 'p' to cycle views",
+                aggregate.label()
+            ),
+            "% of median".to_string(),
+            (0..=5)
+                .map(|i| format!("{:.1}", y_min + (y_max - y_min) * i as f64 / 5.0))
+                .collect(),
+        )
+    } else {
+        (
+            format!(
+                "All Targets Latency Overlay ({}, {}) - 'n' to normalize, 'A' to smooth, 'p' to cycle views",
+                unit.symbol(),
+                aggregate.label()
+            ),
+            format!("Latency ({})", unit.symbol()),
+            axis_labels(y_min, y_max, unit),
+        )
+    };
 
-    let x_labels: Vec<String> = (0..=5)
-        .map(|i| format!("{:.0}", x_max * i as f64 / 5.0))
-        .collect();
+    let x_labels = theme.time_labels(
+        x_max,
+        &longest_times(targets.iter().flat_map(|t| [ping_times(t), ssh_times(t)])),
+    );
 
     let chart = Chart::new(datasets)
-        .block(
-            Block::default()
-                .title("All Targets Latency Overlay (ms) - Press 'p' to cycle views")
-                .borders(Borders::ALL),
-        )
+        .block(Block::default().title(title).borders(Borders::ALL))
         .x_axis(
             Axis::default()
-                .title("Time (samples)")
-                .style(Style::default().fg(Color::Gray))
+                .title(theme.time_axis().title())
+                .style(theme.fg(Color::Gray))
                 .bounds([0.0, x_max])
                 .labels(x_labels.iter().map(|s| s.as_str()).collect::<Vec<_>>()),
         )
         .y_axis(
             Axis::default()
-                .title("Latency (ms)")
-                .style(Style::default().fg(Color::Gray))
+                .title(y_title.as_str())
+                .style(theme.fg(Color::Gray))
                 .bounds([y_min, y_max])
                 .labels(y_labels.iter().map(|s| s.as_str()).collect::<Vec<_>>()),
         );
@@ -836,7 +2925,13 @@ fn render_all_targets_overlay_chart(f: &mut Frame, area: Rect, targets: &[Target
     f.render_widget(chart, area);
 }
 
-fn render_all_targets_ping_chart(f: &mut Frame, area: Rect, targets: &[TargetStats]) {
+fn render_all_targets_ping_chart(
+    f: &mut Frame,
+    area: Rect,
+    targets: &[&TargetStats],
+    axis: &mut AxisBounds,
+    theme: &Theme,
+) {
     if targets.is_empty() {
         let block = Block::default()
             .title("All Targets Ping")
@@ -853,24 +2948,9 @@ fn render_all_targets_ping_chart(f: &mut Frame, area: Rect, targets: &[TargetSta
     let mut min_latency = f64::INFINITY;
     let mut max_length = 0;
 
-    let colors = [
-        Color::Green,
-        Color::Blue,
-        Color::Yellow,
-        Color::Magenta,
-        Color::Cyan,
-        Color::Red,
-        Color::LightGreen,
-        Color::LightBlue,
-        Color::LightYellow,
-        Color::LightMagenta,
-        Color::LightCyan,
-        Color::LightRed,
-    ];
-
     for (target_idx, target) in targets.iter().enumerate() {
         let target_name = target.target.name.as_ref().unwrap_or(&target.target.ip);
-        let color = colors[target_idx % colors.len()];
+        let color = theme.series_color(target_idx, CheckKind::Ping);
 
         if !target.ping_history.is_empty() {
             let ping_data: Vec<(f64, f64)> = target
@@ -881,8 +2961,10 @@ fn render_all_targets_ping_chart(f: &mut Frame, area: Rect, targets: &[TargetSta
                 .collect();
 
             if !ping_data.is_empty() {
-                max_latency =
-                    max_latency.max(ping_data.iter().map(|(_, y)| *y).fold(0.0, f64::max));
+                max_latency = max_latency.max(scale_max(
+                    ping_data.iter().map(|(_, y)| *y),
+                    target.ping_scale_limit(),
+                ));
                 min_latency = min_latency.min(
                     ping_data
                         .iter()
@@ -902,8 +2984,12 @@ fn render_all_targets_ping_chart(f: &mut Frame, area: Rect, targets: &[TargetSta
         let block = Block::default()
             .title("All Targets Ping")
             .borders(Borders::ALL);
-        let paragraph = Paragraph::new("No ping data available for any target").block(block);
-        f.render_widget(paragraph, area);
+        let message = if targets.iter().all(|t| t.ping_history.is_empty()) {
+            collecting("ping data")
+        } else {
+            "No ping data available for any target".to_string()
+        };
+        f.render_widget(Paragraph::new(message).block(block), area);
         return;
     }
 
@@ -911,45 +2997,46 @@ fn render_all_targets_ping_chart(f: &mut Frame, area: Rect, targets: &[TargetSta
         .iter()
         .zip(all_names.iter())
         .zip(all_colors.iter())
-        .map(|((data, name), color)| {
+        .enumerate()
+        .map(|(series_idx, ((data, name), color))| {
             Dataset::default()
                 .name(name.as_str())
-                .marker(symbols::Marker::Braille)
-                .style(Style::default().fg(*color))
-                .graph_type(GraphType::Line)
+                .marker(theme.check_marker(CheckKind::Ping, series_idx))
+                .style(theme.fg(*color))
+                .graph_type(theme.check_graph(CheckKind::Ping))
                 .data(data)
         })
         .collect();
 
-    let y_max = max_latency * 1.1;
+    let y_max = axis.smooth("overview ping".to_string(), max_latency * 1.1, theme);
     let y_min = min_latency.min(0.0);
     let x_max = max_length as f64;
 
-    let y_labels: Vec<String> = (0..=5)
-        .map(|i| format!("{:.1}", y_min + (y_max - y_min) * i as f64 / 5.0))
-        .collect();
+    let unit = theme.axis_unit(y_max);
+    let y_labels = axis_labels(y_min, y_max, unit);
 
-    let x_labels: Vec<String> = (0..=5)
-        .map(|i| format!("{:.0}", x_max * i as f64 / 5.0))
-        .collect();
+    let x_labels = theme.time_labels(x_max, &longest_times(targets.iter().map(|t| ping_times(t))));
 
     let chart = Chart::new(datasets)
         .block(
             Block::default()
-                .title("All Targets Ping Latency (ms) - Press 'p' to cycle views")
+                .title(format!(
+                    "All Targets Ping Latency ({}) - Press 'p' to cycle views",
+                    unit.symbol()
+                ))
                 .borders(Borders::ALL),
         )
         .x_axis(
             Axis::default()
-                .title("Time (samples)")
-                .style(Style::default().fg(Color::Gray))
+                .title(theme.time_axis().title())
+                .style(theme.fg(Color::Gray))
                 .bounds([0.0, x_max])
                 .labels(x_labels.iter().map(|s| s.as_str()).collect::<Vec<_>>()),
         )
         .y_axis(
             Axis::default()
-                .title("Latency (ms)")
-                .style(Style::default().fg(Color::Gray))
+                .title(format!("Latency ({})", unit.symbol()))
+                .style(theme.fg(Color::Gray))
                 .bounds([y_min, y_max])
                 .labels(y_labels.iter().map(|s| s.as_str()).collect::<Vec<_>>()),
         );
@@ -957,7 +3044,13 @@ fn render_all_targets_ping_chart(f: &mut Frame, area: Rect, targets: &[TargetSta
     f.render_widget(chart, area);
 }
 
-fn render_all_targets_ssh_chart(f: &mut Frame, area: Rect, targets: &[TargetStats]) {
+fn render_all_targets_ssh_chart(
+    f: &mut Frame,
+    area: Rect,
+    targets: &[&TargetStats],
+    axis: &mut AxisBounds,
+    theme: &Theme,
+) {
     if targets.is_empty() {
         let block = Block::default()
             .title("All Targets SSH")
@@ -974,24 +3067,9 @@ fn render_all_targets_ssh_chart(f: &mut Frame, area: Rect, targets: &[TargetStat
     let mut min_latency = f64::INFINITY;
     let mut max_length = 0;
 
-    let colors = [
-        Color::Green,
-        Color::Blue,
-        Color::Yellow,
-        Color::Magenta,
-        Color::Cyan,
-        Color::Red,
-        Color::LightGreen,
-        Color::LightBlue,
-        Color::LightYellow,
-        Color::LightMagenta,
-        Color::LightCyan,
-        Color::LightRed,
-    ];
-
     for (target_idx, target) in targets.iter().enumerate() {
         let target_name = target.target.name.as_ref().unwrap_or(&target.target.ip);
-        let color = colors[target_idx % colors.len()];
+        let color = theme.series_color(target_idx, CheckKind::Ssh);
 
         if target.target.ssh_port.is_some() && !target.ssh_history.is_empty() {
             let ssh_data: Vec<(f64, f64)> = target
@@ -1002,7 +3080,10 @@ fn render_all_targets_ssh_chart(f: &mut Frame, area: Rect, targets: &[TargetStat
                 .collect();
 
             if !ssh_data.is_empty() {
-                max_latency = max_latency.max(ssh_data.iter().map(|(_, y)| *y).fold(0.0, f64::max));
+                max_latency = max_latency.max(scale_max(
+                    ssh_data.iter().map(|(_, y)| *y),
+                    target.ssh_scale_limit(),
+                ));
                 min_latency = min_latency.min(
                     ssh_data
                         .iter()
@@ -1022,8 +3103,12 @@ fn render_all_targets_ssh_chart(f: &mut Frame, area: Rect, targets: &[TargetStat
         let block = Block::default()
             .title("All Targets SSH")
             .borders(Borders::ALL);
-        let paragraph = Paragraph::new("No SSH data available for any target").block(block);
-        f.render_widget(paragraph, area);
+        let message = if targets.iter().all(|t| t.ssh_history.is_empty()) {
+            collecting("SSH data")
+        } else {
+            "No SSH data available for any target".to_string()
+        };
+        f.render_widget(Paragraph::new(message).block(block), area);
         return;
     }
 
@@ -1031,45 +3116,46 @@ fn render_all_targets_ssh_chart(f: &mut Frame, area: Rect, targets: &[TargetStat
         .iter()
         .zip(all_names.iter())
         .zip(all_colors.iter())
-        .map(|((data, name), color)| {
+        .enumerate()
+        .map(|(series_idx, ((data, name), color))| {
             Dataset::default()
                 .name(name.as_str())
-                .marker(symbols::Marker::Braille)
-                .style(Style::default().fg(*color))
-                .graph_type(GraphType::Line)
+                .marker(theme.check_marker(CheckKind::Ssh, series_idx))
+                .style(theme.fg(*color))
+                .graph_type(theme.check_graph(CheckKind::Ssh))
                 .data(data)
         })
         .collect();
 
-    let y_max = max_latency * 1.1;
+    let y_max = axis.smooth("overview ssh".to_string(), max_latency * 1.1, theme);
     let y_min = min_latency.min(0.0);
     let x_max = max_length as f64;
 
-    let y_labels: Vec<String> = (0..=5)
-        .map(|i| format!("{:.1}", y_min + (y_max - y_min) * i as f64 / 5.0))
-        .collect();
+    let unit = theme.axis_unit(y_max);
+    let y_labels = axis_labels(y_min, y_max, unit);
 
-    let x_labels: Vec<String> = (0..=5)
-        .map(|i| format!("{:.0}", x_max * i as f64 / 5.0))
-        .collect();
+    let x_labels = theme.time_labels(x_max, &longest_times(targets.iter().map(|t| ssh_times(t))));
 
     let chart = Chart::new(datasets)
         .block(
             Block::default()
-                .title("All Targets SSH Connection Time (ms) - Press 'p' to cycle views")
+                .title(format!(
+                    "All Targets SSH Connection Time ({}) - Press 'p' to cycle views",
+                    unit.symbol()
+                ))
                 .borders(Borders::ALL),
         )
         .x_axis(
             Axis::default()
-                .title("Time (samples)")
-                .style(Style::default().fg(Color::Gray))
+                .title(theme.time_axis().title())
+                .style(theme.fg(Color::Gray))
                 .bounds([0.0, x_max])
                 .labels(x_labels.iter().map(|s| s.as_str()).collect::<Vec<_>>()),
         )
         .y_axis(
             Axis::default()
-                .title("Connection Time (ms)")
-                .style(Style::default().fg(Color::Gray))
+                .title(format!("Connection Time ({})", unit.symbol()))
+                .style(theme.fg(Color::Gray))
                 .bounds([y_min, y_max])
                 .labels(y_labels.iter().map(|s| s.as_str()).collect::<Vec<_>>()),
         );
@@ -1077,12 +3163,18 @@ fn render_all_targets_ssh_chart(f: &mut Frame, area: Rect, targets: &[TargetStat
     f.render_widget(chart, area);
 }
 
-fn render_ssh_chart(f: &mut Frame, area: Rect, target: &TargetStats) {
+fn render_ssh_chart(
+    f: &mut Frame,
+    area: Rect,
+    target: &TargetStats,
+    axis: &mut AxisBounds,
+    theme: &Theme,
+) {
     if target.ssh_history.is_empty() {
         let block = Block::default()
             .title("SSH Connection Time")
             .borders(Borders::ALL);
-        let paragraph = Paragraph::new("No SSH data yet...").block(block);
+        let paragraph = Paragraph::new(collecting("SSH data")).block(block);
         f.render_widget(paragraph, area);
         return;
     }
@@ -1103,7 +3195,7 @@ fn render_ssh_chart(f: &mut Frame, area: Rect, target: &TargetStats) {
         return;
     }
 
-    let max_time = ssh_data.iter().map(|(_, y)| *y).fold(0.0, f64::max);
+    let max_time = scale_max(ssh_data.iter().map(|(_, y)| *y), target.ssh_scale_limit());
     let min_time = ssh_data
         .iter()
         .map(|(_, y)| *y)
@@ -1112,41 +3204,41 @@ fn render_ssh_chart(f: &mut Frame, area: Rect, target: &TargetStats) {
     let datasets = vec![
         Dataset::default()
             .name("SSH")
-            .marker(symbols::Marker::Braille)
-            .style(Style::default().fg(Color::Blue))
-            .graph_type(GraphType::Line)
+            .marker(theme.check_marker(CheckKind::Ssh, 0))
+            .style(theme.fg(Color::Blue))
+            .graph_type(theme.check_graph(CheckKind::Ssh))
             .data(&ssh_data),
     ];
 
-    let y_max = max_time * 1.1;
+    let y_max = axis.smooth(format!("ssh {}", target.target.ip), max_time * 1.1, theme);
     let y_min = min_time.min(0.0);
     let x_max = target.ssh_history.len() as f64;
 
-    let y_labels: Vec<String> = (0..=5)
-        .map(|i| format!("{:.1}", y_min + (y_max - y_min) * i as f64 / 5.0))
-        .collect();
+    let unit = theme.axis_unit(y_max);
+    let y_labels = axis_labels(y_min, y_max, unit);
 
-    let x_labels: Vec<String> = (0..=5)
-        .map(|i| format!("{:.0}", x_max * i as f64 / 5.0))
-        .collect();
+    let x_labels = theme.time_labels(x_max, &ssh_times(target));
 
     let chart = Chart::new(datasets)
         .block(
             Block::default()
-                .title("SSH Connection Time (ms) - Press 'p' to cycle views")
+                .title(format!(
+                    "SSH Connection Time ({}) - Press 'p' to cycle views",
+                    unit.symbol()
+                ))
                 .borders(Borders::ALL),
         )
         .x_axis(
             Axis::default()
-                .title("Time (samples)")
-                .style(Style::default().fg(Color::Gray))
+                .title(theme.time_axis().title())
+                .style(theme.fg(Color::Gray))
                 .bounds([0.0, x_max])
                 .labels(x_labels.iter().map(|s| s.as_str()).collect::<Vec<_>>()),
         )
         .y_axis(
             Axis::default()
-                .title("Connection Time (ms)")
-                .style(Style::default().fg(Color::Gray))
+                .title(format!("Connection Time ({})", unit.symbol()))
+                .style(theme.fg(Color::Gray))
                 .bounds([y_min, y_max])
                 .labels(y_labels.iter().map(|s| s.as_str()).collect::<Vec<_>>()),
         );
@@ -0,0 +1,152 @@
+use crate::config::LatencyUnit;
+use crate::events::{Event, EventKind};
+use crate::monitor::{Diagnostics, TargetStats};
+use crate::theme::Theme;
+use crate::units::format_value;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Color,
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+use std::collections::VecDeque;
+
+fn ms(value: f64) -> String {
+    format!("{}ms", format_value(value, LatencyUnit::Ms, 1))
+}
+
+// e.g. "1.2 MiB"
+fn bytes(count: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = count as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} B", count),
+        _ => format!("{:.1} {}", value, UNITS[unit]),
+    }
+}
+
+fn row(label: &str, value: String, color: Color, theme: &Theme) -> Line<'static> {
+    Line::from(vec![
+        Span::styled(format!("{:<18}", label), theme.fg(Color::Gray)),
+        Span::styled(value, theme.fg(color)),
+    ])
+}
+
+// The monitor's own health, for telling a tool problem from a network one. Anything taking
+// longer than the ping interval is flagged, since the next cycle then starts late
+pub fn render_diagnostics(
+    f: &mut Frame,
+    area: Rect,
+    diagnostics: &Diagnostics,
+    targets: &[TargetStats],
+    events: &VecDeque<Event>,
+    ping_interval_ms: u64,
+    theme: &Theme,
+) {
+    let interval = ping_interval_ms as f64;
+    let over_interval = |value: f64| {
+        if value > interval {
+            Color::Red
+        } else {
+            Color::Green
+        }
+    };
+
+    let ping_cycles = match diagnostics.mean_ping_cycle_ms() {
+        Some(mean) => (
+            format!(
+                "{} - mean {}, latest {}, interval {}",
+                diagnostics.ping_cycles,
+                ms(mean),
+                ms(diagnostics.last_ping_cycle_ms),
+                format_value(interval, LatencyUnit::Ms, 0) + "ms"
+            ),
+            over_interval(mean),
+        ),
+        None => ("none yet".to_string(), Color::White),
+    };
+    let ssh_cycles = match diagnostics.mean_ssh_cycle_ms() {
+        Some(mean) => (
+            format!("{} - mean {}", diagnostics.ssh_cycles, ms(mean)),
+            Color::White,
+        ),
+        None => ("none yet".to_string(), Color::White),
+    };
+    let samples: usize = targets
+        .iter()
+        .map(|t| t.ping_history.len() + t.ssh_history.len())
+        .sum();
+    let restarts = events
+        .iter()
+        .filter(|e| e.kind == EventKind::MonitorRestarted)
+        .count();
+    let last_error = match &diagnostics.last_error {
+        Some((at, error)) => (
+            format!(
+                "{} {}",
+                at.with_timezone(&chrono::Local).format("%H:%M:%S"),
+                error
+            ),
+            Color::Red,
+        ),
+        None => ("none".to_string(), Color::Green),
+    };
+
+    let lines = vec![
+        row("Ping cycles", ping_cycles.0, ping_cycles.1, theme),
+        row("SSH cycles", ssh_cycles.0, ssh_cycles.1, theme),
+        row(
+            "Loop lag",
+            format!(
+                "latest {}, worst {}",
+                ms(diagnostics.loop_lag_ms),
+                ms(diagnostics.max_loop_lag_ms)
+            ),
+            over_interval(diagnostics.loop_lag_ms),
+            theme,
+        ),
+        row(
+            "In-flight probes",
+            diagnostics.in_flight_probes.to_string(),
+            Color::White,
+            theme,
+        ),
+        row(
+            "History memory",
+            format!(
+                "~{} for {} samples across {} target{}",
+                bytes(diagnostics.history_bytes),
+                samples,
+                targets.len(),
+                if targets.len() == 1 { "" } else { "s" }
+            ),
+            Color::White,
+            theme,
+        ),
+        row(
+            "Monitor restarts",
+            restarts.to_string(),
+            if restarts > 0 {
+                Color::Red
+            } else {
+                Color::Green
+            },
+            theme,
+        ),
+        row("Last error", last_error.0, last_error.1, theme),
+    ];
+
+    let block = Block::default()
+        .title("Diagnostics - 'D' to close")
+        .borders(Borders::ALL);
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, area);
+}
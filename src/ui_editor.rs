@@ -0,0 +1,334 @@
+use crate::config::{Target, validate_address};
+use crate::theme::Theme;
+use crate::ui_popup::centered_rect;
+use crossterm::event::KeyCode;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+const MIN_PING_INTERVAL_MS: u64 = 100;
+const DEFAULT_SSH_PORT: u16 = 22;
+const FIELD_LABELS: [&str; 4] = ["IP or hostname", "Name", "SSH user", "SSH port"];
+
+// In-app editing of the target list and ping interval, for people who'd rather not touch the
+// JSON. Works on a copy; nothing changes until it is saved
+pub struct ConfigEditor {
+    targets: Vec<Target>,
+    ping_interval_ms: u64,
+    list_state: ListState,
+    form: Option<Form>,
+    error: Option<String>,
+}
+
+enum Form {
+    // None adds a new target
+    Target {
+        index: Option<usize>,
+        fields: [String; 4],
+        focus: usize,
+    },
+    PingInterval(String),
+}
+
+pub enum EditorAction {
+    None,
+    Close,
+    Save(Vec<Target>, u64),
+}
+
+impl ConfigEditor {
+    pub fn new(targets: Vec<Target>, ping_interval_ms: u64) -> Self {
+        let mut list_state = ListState::default();
+        list_state.select((!targets.is_empty()).then_some(0));
+        Self {
+            targets,
+            ping_interval_ms,
+            list_state,
+            form: None,
+            error: None,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) -> EditorAction {
+        if self.form.is_some() {
+            self.edit_form(key);
+            return EditorAction::None;
+        }
+
+        self.error = None;
+        let selected = self.list_state.selected();
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => return EditorAction::Close,
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Char('a') => {
+                self.form = Some(Form::Target {
+                    index: None,
+                    fields: Default::default(),
+                    focus: 0,
+                });
+            }
+            KeyCode::Enter | KeyCode::Char('e') => {
+                if let Some(index) = selected {
+                    let target = &self.targets[index];
+                    self.form = Some(Form::Target {
+                        index: Some(index),
+                        fields: [
+                            target.ip.clone(),
+                            target.name.clone().unwrap_or_default(),
+                            target.ssh_user.clone().unwrap_or_default(),
+                            target.ssh_port.map(|p| p.to_string()).unwrap_or_default(),
+                        ],
+                        focus: 0,
+                    });
+                }
+            }
+            KeyCode::Char('d') | KeyCode::Delete => {
+                if let Some(index) = selected {
+                    self.targets.remove(index);
+                    let last = self.targets.len().checked_sub(1);
+                    self.list_state.select(last.map(|last| index.min(last)));
+                }
+            }
+            KeyCode::Char('i') => {
+                self.form = Some(Form::PingInterval(self.ping_interval_ms.to_string()));
+            }
+            KeyCode::Char('s') => {
+                if self.targets.is_empty() {
+                    self.error = Some("Add at least one target before saving".to_string());
+                } else {
+                    return EditorAction::Save(self.targets.clone(), self.ping_interval_ms);
+                }
+            }
+            _ => {}
+        }
+        EditorAction::None
+    }
+
+    fn move_selection(&mut self, step: isize) {
+        let count = self.targets.len();
+        if count == 0 {
+            return;
+        }
+        let current = self.list_state.selected().unwrap_or(0) as isize;
+        self.list_state
+            .select(Some((current + step).rem_euclid(count as isize) as usize));
+    }
+
+    fn edit_form(&mut self, key: KeyCode) {
+        let Some(form) = &mut self.form else {
+            return;
+        };
+        let text = match form {
+            Form::Target { fields, focus, .. } => {
+                match key {
+                    KeyCode::Tab | KeyCode::Down => {
+                        *focus = (*focus + 1) % fields.len();
+                        return;
+                    }
+                    KeyCode::BackTab | KeyCode::Up => {
+                        *focus = (*focus + fields.len() - 1) % fields.len();
+                        return;
+                    }
+                    _ => {}
+                }
+                &mut fields[*focus]
+            }
+            Form::PingInterval(text) => text,
+        };
+        match key {
+            KeyCode::Char(c) => text.push(c),
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Esc => {
+                self.form = None;
+                self.error = None;
+            }
+            KeyCode::Enter => match self.apply_form() {
+                Ok(()) => {
+                    self.form = None;
+                    self.error = None;
+                }
+                Err(e) => self.error = Some(e),
+            },
+            _ => {}
+        }
+    }
+
+    // Validates the open form and applies it to the working copy
+    fn apply_form(&mut self) -> Result<(), String> {
+        match &self.form {
+            Some(Form::Target { index, fields, .. }) => {
+                let index = *index;
+                let [ip, name, ssh_user, ssh_port] = fields.clone().map(|f| f.trim().to_string());
+                validate_address(&ip).map_err(|e| e.to_string())?;
+                if self
+                    .targets
+                    .iter()
+                    .enumerate()
+                    .any(|(i, t)| t.ip == ip && Some(i) != index)
+                {
+                    return Err(format!("{} is already in the list", ip));
+                }
+                let ssh_port = match (ssh_user.is_empty(), ssh_port.is_empty()) {
+                    (_, false) => Some(
+                        ssh_port
+                            .parse::<u16>()
+                            .ok()
+                            .filter(|p| *p > 0)
+                            .ok_or_else(|| format!("Invalid SSH port: {}", ssh_port))?,
+                    ),
+                    (false, true) => Some(DEFAULT_SSH_PORT),
+                    (true, true) => None,
+                };
+                if ssh_port.is_some() && ssh_user.is_empty() {
+                    return Err("An SSH port needs an SSH user".to_string());
+                }
+
+                // Editing keeps the settings the form doesn't show
+                let mut target = index.map(|i| self.targets[i].clone()).unwrap_or_default();
+                target.ip = ip;
+                target.name = (!name.is_empty()).then_some(name);
+                target.ssh_user = (!ssh_user.is_empty()).then_some(ssh_user);
+                target.ssh_port = ssh_port;
+                match index {
+                    Some(i) => self.targets[i] = target,
+                    None => {
+                        self.targets.push(target);
+                        self.list_state.select(Some(self.targets.len() - 1));
+                    }
+                }
+            }
+            Some(Form::PingInterval(text)) => {
+                self.ping_interval_ms = text
+                    .trim()
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|ms| *ms >= MIN_PING_INTERVAL_MS)
+                    .ok_or_else(|| {
+                        format!(
+                            "Ping interval must be a whole number of ms, at least {}",
+                            MIN_PING_INTERVAL_MS
+                        )
+                    })?;
+            }
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+pub fn render_config_editor(f: &mut Frame, area: Rect, editor: &mut ConfigEditor, theme: &Theme) {
+    let popup_area = centered_rect(90, 24, area);
+    f.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .title("Config Editor - a add, Enter edit, d delete, i interval, s save, Esc close")
+        .borders(Borders::ALL);
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    f.render_widget(
+        Paragraph::new(format!(
+            "Ping interval: {}ms - saved changes apply on restart",
+            editor.ping_interval_ms
+        ))
+        .style(theme.fg(Color::Gray)),
+        chunks[0],
+    );
+
+    let items: Vec<ListItem> = if editor.targets.is_empty() {
+        vec![ListItem::new("No targets; press 'a' to add one").style(theme.fg(Color::Yellow))]
+    } else {
+        editor
+            .targets
+            .iter()
+            .map(|target| {
+                let ssh = match (&target.ssh_user, target.ssh_port) {
+                    (Some(user), Some(port)) => format!("ssh {}:{}", user, port),
+                    _ => String::new(),
+                };
+                ListItem::new(format!(
+                    "{:<32} {:<28} {}",
+                    target.ip,
+                    target.name.as_deref().unwrap_or("-"),
+                    ssh
+                ))
+            })
+            .collect()
+    };
+    let list = List::new(items)
+        .style(theme.fg(Color::White))
+        .highlight_style(theme.style(theme.fg(Color::Cyan).add_modifier(Modifier::BOLD)))
+        .highlight_symbol("> ");
+    f.render_stateful_widget(list, chunks[1], &mut editor.list_state);
+
+    if let Some(error) = &editor.error {
+        f.render_widget(
+            Paragraph::new(error.as_str()).style(theme.fg(Color::Red)),
+            chunks[2],
+        );
+    }
+
+    match &editor.form {
+        Some(Form::Target {
+            index,
+            fields,
+            focus,
+        }) => {
+            let title = match index {
+                Some(_) => "Edit Target",
+                None => "Add Target",
+            };
+            let lines: Vec<ListItem> = FIELD_LABELS
+                .iter()
+                .zip(fields)
+                .enumerate()
+                .map(|(i, (label, value))| {
+                    let cursor = if i == *focus { "_" } else { "" };
+                    let item = ListItem::new(format!("{:<15} {}{}", label, value, cursor));
+                    if i == *focus {
+                        item.style(theme.fg(Color::Cyan))
+                    } else {
+                        item
+                    }
+                })
+                .collect();
+            render_form(f, area, title, lines, theme);
+        }
+        Some(Form::PingInterval(text)) => {
+            let lines = vec![ListItem::new(format!("Interval (ms)   {}_", text))];
+            render_form(f, area, "Ping Interval", lines, theme);
+        }
+        None => {}
+    }
+}
+
+fn render_form(f: &mut Frame, area: Rect, title: &str, lines: Vec<ListItem>, theme: &Theme) {
+    let popup_area = centered_rect(60, lines.len() as u16 + 2, area);
+    let list = List::new(lines)
+        .block(
+            Block::default()
+                .title(format!(
+                    "{} - Tab next field, Enter to apply, Esc to cancel",
+                    title
+                ))
+                .borders(Borders::ALL),
+        )
+        .style(theme.fg(Color::White));
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(list, popup_area);
+}
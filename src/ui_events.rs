@@ -0,0 +1,100 @@
+use crate::events::{Event, EventKind};
+use crate::theme::Theme;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Rect},
+    style::{Color, Modifier},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+};
+use std::collections::VecDeque;
+
+fn event_color(kind: EventKind) -> Color {
+    match kind {
+        EventKind::Down
+        | EventKind::LocalNetworkLost
+        | EventKind::MacChanged
+        | EventKind::Escalated
+        | EventKind::ReplyAnomaly
+        | EventKind::DuplicateReplies
+        | EventKind::LikelyLocalOutage
+        | EventKind::MonitorRestarted => Color::Red,
+        EventKind::Recovered | EventKind::LocalNetworkRestored => Color::Green,
+        EventKind::Acknowledged
+        | EventKind::AlertsMuted
+        | EventKind::ConfigSaved
+        | EventKind::HistoryCleared => Color::Cyan,
+        EventKind::Degraded
+        | EventKind::DnsChanged
+        | EventKind::LatencyJump
+        | EventKind::ProbeSkipped => Color::Yellow,
+        EventKind::UpstreamDown => Color::DarkGray,
+    }
+}
+
+// `ip` narrows the feed to one target's events, plus those about the local network
+pub fn render_event_feed(
+    f: &mut Frame,
+    area: Rect,
+    events: &VecDeque<Event>,
+    ip: Option<&str>,
+    state: &mut TableState,
+    theme: &Theme,
+) {
+    let title = "Events - Up/Down to scroll, 'p' to cycle views";
+
+    // Most recent first, like the samples table
+    let shown: Vec<&Event> = events
+        .iter()
+        .rev()
+        .filter(|e| ip.is_none_or(|ip| e.ip.is_none() || e.ip.as_deref() == Some(ip)))
+        .collect();
+
+    if shown.is_empty() {
+        let block = Block::default().title(title).borders(Borders::ALL);
+        let paragraph = Paragraph::new("Nothing has happened yet").block(block);
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    match state.selected() {
+        Some(selected) if selected >= shown.len() => state.select(Some(shown.len() - 1)),
+        None => state.select(Some(0)),
+        _ => {}
+    }
+
+    let header = Row::new(vec!["Time", "Target", "Kind", "Event"])
+        .style(theme.fg(Color::Yellow).add_modifier(Modifier::BOLD));
+
+    let rows: Vec<Row> = shown
+        .iter()
+        .map(|event| {
+            Row::new(vec![
+                Cell::from(
+                    event
+                        .timestamp
+                        .with_timezone(&chrono::Local)
+                        .format("%m-%d %H:%M:%S")
+                        .to_string(),
+                ),
+                Cell::from(event.target_name.clone().unwrap_or("-".to_string())),
+                Cell::from(event.kind.label()),
+                Cell::from(event.message.clone()).style(theme.fg(event_color(event.kind))),
+            ])
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Length(15),
+        Constraint::Length(20),
+        Constraint::Length(8),
+        Constraint::Min(10),
+    ];
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .style(theme.fg(Color::White))
+        .highlight_style(theme.bg(Color::DarkGray));
+
+    f.render_stateful_widget(table, area, state);
+}
@@ -1,13 +1,19 @@
 use crate::monitor::TargetStats;
+use crate::theme::Theme;
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
+    style::Color,
     widgets::{BarChart, Block, Borders, List, ListItem, Paragraph},
 };
 use std::collections::HashMap;
 
-pub fn render_all_targets_failure_chart(f: &mut Frame, area: Rect, targets: &[TargetStats]) {
+pub fn render_all_targets_failure_chart(
+    f: &mut Frame,
+    area: Rect,
+    targets: &[&TargetStats],
+    theme: &Theme,
+) {
     if targets.is_empty() {
         let block = Block::default()
             .title("Failure Analysis")
@@ -50,19 +56,24 @@ pub fn render_all_targets_failure_chart(f: &mut Frame, area: Rect, targets: &[Ta
     }
 
     // Render bar chart
-    render_failure_bar_chart(f, chunks[0], &failure_counts);
+    render_failure_bar_chart(f, chunks[0], &failure_counts, theme);
 
     // Render failure log
-    render_failure_log(f, chunks[1], &all_failures);
+    render_failure_log(f, chunks[1], &all_failures, theme);
 }
 
-fn render_failure_bar_chart(f: &mut Frame, area: Rect, failure_counts: &HashMap<String, u64>) {
+fn render_failure_bar_chart(
+    f: &mut Frame,
+    area: Rect,
+    failure_counts: &HashMap<String, u64>,
+    theme: &Theme,
+) {
     // Convert to sorted vector for bar chart
     let mut failure_data: Vec<(String, u64)> = failure_counts
         .iter()
         .map(|(k, v)| (k.clone(), *v))
         .collect();
-    failure_data.sort_by(|a, b| b.1.cmp(&a.1)); // Sort by count descending
+    failure_data.sort_by_key(|b| std::cmp::Reverse(b.1)); // Sort by count descending
     failure_data.truncate(6); // Show top 6 failures to fit better with longer labels
 
     // Truncate long failure reasons for display but keep them readable
@@ -98,8 +109,8 @@ fn render_failure_bar_chart(f: &mut Frame, area: Rect, failure_counts: &HashMap<
         .data(&bar_data_refs)
         .bar_width(3)
         .bar_gap(2) // Add spacing between bars
-        .bar_style(Style::default().fg(Color::Red))
-        .value_style(Style::default().fg(Color::Black).bg(Color::Red))
+        .bar_style(theme.fg(Color::Red))
+        .value_style(theme.style(theme.fg(Color::Black).bg(Color::Red)))
         .max(max_count);
 
     f.render_widget(barchart, area);
@@ -109,10 +120,11 @@ fn render_failure_log(
     f: &mut Frame,
     area: Rect,
     failures: &[(chrono::DateTime<chrono::Utc>, String, String, String)],
+    theme: &Theme,
 ) {
     // Sort failures by timestamp (most recent first)
     let mut sorted_failures = failures.to_vec();
-    sorted_failures.sort_by(|a, b| b.0.cmp(&a.0));
+    sorted_failures.sort_by_key(|b| std::cmp::Reverse(b.0));
     sorted_failures.truncate(20); // Show last 20 failures
 
     let items: Vec<ListItem> = sorted_failures
@@ -130,12 +142,17 @@ fn render_failure_log(
                 .title("Recent Failures")
                 .borders(Borders::ALL),
         )
-        .style(Style::default().fg(Color::White));
+        .style(theme.fg(Color::White));
 
     f.render_widget(list, area);
 }
 
-pub fn render_single_target_failure_chart(f: &mut Frame, area: Rect, target: &TargetStats) {
+pub fn render_single_target_failure_chart(
+    f: &mut Frame,
+    area: Rect,
+    target: &TargetStats,
+    theme: &Theme,
+) {
     if target.failure_log.is_empty() {
         let block = Block::default()
             .title("Failure Analysis - Press 'p' to cycle views")
@@ -167,10 +184,10 @@ pub fn render_single_target_failure_chart(f: &mut Frame, area: Rect, target: &Ta
     }
 
     // Render bar chart
-    render_single_target_bar_chart(f, chunks[0], &failure_counts, target);
+    render_single_target_bar_chart(f, chunks[0], &failure_counts, target, theme);
 
     // Render failure log
-    render_failure_log(f, chunks[1], &target_failures);
+    render_failure_log(f, chunks[1], &target_failures, theme);
 }
 
 fn render_single_target_bar_chart(
@@ -178,13 +195,14 @@ fn render_single_target_bar_chart(
     area: Rect,
     failure_counts: &HashMap<String, u64>,
     target: &TargetStats,
+    theme: &Theme,
 ) {
     // Convert to sorted vector for bar chart
     let mut failure_data: Vec<(String, u64)> = failure_counts
         .iter()
         .map(|(k, v)| (k.clone(), *v))
         .collect();
-    failure_data.sort_by(|a, b| b.1.cmp(&a.1)); // Sort by count descending
+    failure_data.sort_by_key(|b| std::cmp::Reverse(b.1)); // Sort by count descending
     failure_data.truncate(6); // Show top 6 failures to fit better with longer labels
 
     // Truncate long failure reasons for display but keep them readable
@@ -218,8 +236,8 @@ fn render_single_target_bar_chart(
         .data(&bar_data_refs)
         .bar_width(3)
         .bar_gap(2) // Add spacing between bars
-        .bar_style(Style::default().fg(Color::Red))
-        .value_style(Style::default().fg(Color::Black).bg(Color::Red))
+        .bar_style(theme.fg(Color::Red))
+        .value_style(theme.style(theme.fg(Color::Black).bg(Color::Red)))
         .max(max_count);
 
     f.render_widget(barchart, area);
@@ -0,0 +1,151 @@
+use crate::config::PrimaryCheck;
+use crate::monitor::{Health, TargetStats};
+use crate::theme::Theme;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    widgets::{Block, Borders, Gauge, Paragraph},
+};
+
+const NAME_WIDTH: u16 = 24;
+
+// One row of fill bars per target: how much of the primary check succeeds, and how much of the
+// degraded latency threshold the mean has used up. Fills go yellow past a health threshold
+// and red while the target is down
+pub fn render_gauges(f: &mut Frame, area: Rect, targets: &[&TargetStats], theme: &Theme) {
+    let block = Block::default()
+        .title("Gauges - Press 'p' to cycle views")
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    if inner.height < 2 {
+        return;
+    }
+
+    // A header, then a row per target while they fit, keeping the last row for the overflow
+    let fits = (inner.height as usize - 1).min(targets.len());
+    let shown = if fits < targets.len() {
+        fits.saturating_sub(1)
+    } else {
+        fits
+    };
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(1); inner.height as usize])
+        .split(inner);
+
+    let [_, success_header, latency_header] = columns(rows[0]);
+    f.render_widget(
+        Paragraph::new("Success").style(theme.fg(Color::Gray)),
+        success_header,
+    );
+    f.render_widget(
+        Paragraph::new("Mean latency, full at the degraded threshold").style(theme.fg(Color::Gray)),
+        latency_header,
+    );
+
+    for (target, &row) in targets.iter().zip(&rows[1..]).take(shown) {
+        render_gauge_row(f, row, target, theme);
+    }
+    if shown < targets.len() {
+        f.render_widget(
+            Paragraph::new(format!(
+                "... {} more targets; 't' filters by tag",
+                targets.len() - shown
+            ))
+            .style(theme.fg(Color::Gray)),
+            rows[shown + 1],
+        );
+    }
+}
+
+fn columns(row: Rect) -> [Rect; 3] {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Length(NAME_WIDTH),
+            Constraint::Percentage(50),
+            Constraint::Percentage(50),
+        ])
+        .spacing(1)
+        .split(row);
+    [chunks[0], chunks[1], chunks[2]]
+}
+
+fn render_gauge_row(f: &mut Frame, row: Rect, target: &TargetStats, theme: &Theme) {
+    let [name_area, success_area, latency_area] = columns(row);
+    let thresholds = &target.settings.health;
+    let down = target.health == Health::Down;
+    let fill = |over_threshold: bool| match (down, over_threshold) {
+        // Dimmed when the outage is upstream's
+        (true, _) if target.upstream_down.is_some() => Color::DarkGray,
+        (true, _) => Color::Red,
+        (false, true) => Color::Yellow,
+        (false, false) => Color::Green,
+    };
+
+    let name = target.target.name.as_ref().unwrap_or(&target.target.ip);
+    f.render_widget(
+        Paragraph::new(name.as_str()).style(theme.fg(Color::Cyan)),
+        name_area,
+    );
+
+    // Health follows SSH for SSH-primary targets, so their success gauge does too
+    let (check, success_stats, checked) = match target.primary_check() {
+        PrimaryCheck::Ping => (
+            "",
+            target.ping_stats.as_ref(),
+            !target.ping_history.is_empty(),
+        ),
+        PrimaryCheck::Ssh => (
+            "SSH ",
+            target.ssh_stats.as_ref(),
+            !target.ssh_history.is_empty(),
+        ),
+    };
+    let success = match success_stats {
+        Some(stats) => gauge(
+            stats.success_rate / 100.0,
+            format!("{:.1}% {}success", stats.success_rate, check),
+            fill(100.0 - stats.success_rate > thresholds.loss_percent),
+            theme,
+        ),
+        // Statistics only exist once something has answered
+        None if checked => gauge(0.0, format!("0.0% {}success", check), fill(true), theme),
+        None => gauge(0.0, "no data".to_string(), Color::Gray, theme),
+    };
+    f.render_widget(success, success_area);
+
+    let latency = match &target.ping_stats {
+        Some(stats) => {
+            let mut label = format!(
+                "{} of {}",
+                theme.latency(stats.mean, 1),
+                theme.latency(thresholds.latency_ms, 0)
+            );
+            if let Some(baseline) = target.target.baseline_ms {
+                label.push_str(&format!(", baseline {}", theme.latency(baseline, 1)));
+            }
+            gauge(
+                stats.mean / thresholds.latency_ms.max(f64::EPSILON),
+                label,
+                fill(stats.mean > thresholds.latency_ms),
+                theme,
+            )
+        }
+        None if !target.ping_history.is_empty() => {
+            gauge(0.0, "no replies".to_string(), fill(true), theme)
+        }
+        None => gauge(0.0, "no data".to_string(), Color::Gray, theme),
+    };
+    f.render_widget(latency, latency_area);
+}
+
+fn gauge(ratio: f64, label: String, color: Color, theme: &Theme) -> Gauge<'static> {
+    Gauge::default()
+        .ratio(ratio.clamp(0.0, 1.0))
+        .label(label)
+        .use_unicode(true)
+        .gauge_style(theme.style(Style::default().fg(color).bg(Color::DarkGray)))
+}
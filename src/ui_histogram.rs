@@ -0,0 +1,119 @@
+use crate::config::LatencyUnit;
+use crate::monitor::TargetStats;
+use crate::theme::Theme;
+use crate::ui::collecting;
+use crate::units::format_value;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Color,
+    widgets::{BarChart, Block, Borders, Paragraph},
+};
+
+// Fixed-width bins can span a huge range after one outlier; past this many, the width grows
+const MAX_BINS: usize = 200;
+
+struct Histogram {
+    start: f64,
+    width: f64,
+    counts: Vec<u64>,
+}
+
+fn build_histogram(latencies: &[f64], bins: usize, bin_ms: Option<f64>) -> Histogram {
+    let min = latencies.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = latencies.iter().copied().fold(0.0, f64::max);
+
+    let (start, width, count) = match bin_ms.filter(|w| *w > 0.0) {
+        Some(width) => {
+            let start = (min / width).floor() * width;
+            let count = ((max - start) / width).floor() as usize + 1;
+            if count > MAX_BINS {
+                let width = (max - start) / MAX_BINS as f64;
+                (start, width, MAX_BINS)
+            } else {
+                (start, width, count)
+            }
+        }
+        // All samples equal: one bin rather than a zero width
+        None if max <= min => (min, 1.0, 1),
+        None => {
+            let bins = bins.clamp(1, MAX_BINS);
+            (min, (max - min) / bins as f64, bins)
+        }
+    };
+
+    let mut counts = vec![0; count];
+    for latency in latencies {
+        let bin = (((latency - start) / width) as usize).min(count - 1);
+        counts[bin] += 1;
+    }
+
+    Histogram {
+        start,
+        width,
+        counts,
+    }
+}
+
+pub fn render_latency_histogram(f: &mut Frame, area: Rect, target: &TargetStats, theme: &Theme) {
+    let latencies: Vec<f64> = target
+        .ping_history
+        .iter()
+        .filter(|r| !r.warmup)
+        .filter_map(|r| r.latency_ms)
+        .collect();
+
+    if latencies.is_empty() {
+        let block = Block::default()
+            .title("Ping Latency Histogram - Press 'p' to cycle views")
+            .borders(Borders::ALL);
+        let message = if target.ping_history.is_empty() {
+            collecting("ping data")
+        } else {
+            "No ping data available for histogram".to_string()
+        };
+        f.render_widget(Paragraph::new(message).block(block), area);
+        return;
+    }
+
+    let settings = target.settings.histogram;
+    let histogram = build_histogram(&latencies, settings.bins, settings.bin_ms);
+
+    // Each bar is labelled with its bin's lower bound, all in the unit of the highest one
+    let bin_start = |i: usize| histogram.start + i as f64 * histogram.width;
+    let unit = theme.axis_unit(bin_start(histogram.counts.len().saturating_sub(1)));
+    let decimals = if unit == LatencyUnit::S { 1 } else { 0 };
+    let labels: Vec<String> = (0..histogram.counts.len())
+        .map(|i| format_value(bin_start(i), unit, decimals))
+        .collect();
+    let bar_data: Vec<(&str, u64)> = labels
+        .iter()
+        .zip(&histogram.counts)
+        .map(|(label, count)| (label.as_str(), *count))
+        .collect();
+
+    // Bars share the inner width, with a one-column gap between them
+    let inner_width = area.width.saturating_sub(2) as usize;
+    let bar_width = (inner_width / histogram.counts.len())
+        .saturating_sub(1)
+        .max(1) as u16;
+
+    let barchart = BarChart::default()
+        .block(
+            Block::default()
+                .title(format!(
+                    "Ping Latency Histogram ({} samples, {} bins, {}) - Press 'p' to cycle views",
+                    latencies.len(),
+                    theme.latency(histogram.width, 1),
+                    unit.symbol()
+                ))
+                .borders(Borders::ALL),
+        )
+        .data(&bar_data)
+        .bar_width(bar_width)
+        .bar_gap(1)
+        .bar_style(theme.fg(Color::Cyan))
+        .value_style(theme.style(theme.fg(Color::Black).bg(Color::Cyan)));
+
+    f.render_widget(barchart, area);
+}
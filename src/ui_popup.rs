@@ -0,0 +1,197 @@
+use crate::monitor::{BurstResult, TargetStats};
+use crate::theme::Theme;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Color,
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
+};
+
+const PEEK_FAILURES: usize = 3;
+
+pub enum BurstPopup {
+    Running(String),
+    Done(String, BurstResult),
+}
+
+// One-off message such as an export confirmation, dismissed with Esc
+pub struct Notice {
+    pub title: String,
+    pub message: String,
+    pub is_error: bool,
+}
+
+pub fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Fill(1),
+            Constraint::Length(height.min(area.height)),
+            Constraint::Fill(1),
+        ])
+        .split(area);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Fill(1),
+            Constraint::Length(width.min(area.width)),
+            Constraint::Fill(1),
+        ])
+        .split(vertical[1])[1]
+}
+
+pub fn render_burst_popup(f: &mut Frame, area: Rect, popup: &BurstPopup, theme: &Theme) {
+    let (target_name, items) = match popup {
+        BurstPopup::Running(target_name) => {
+            (target_name, vec![ListItem::new("Burst in progress...")])
+        }
+        BurstPopup::Done(target_name, result) => (target_name, burst_result_items(result, theme)),
+    };
+
+    let popup_area = centered_rect(44, 12, area);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(format!("Burst Test: {} - Esc to close", target_name))
+                .borders(Borders::ALL),
+        )
+        .style(theme.fg(Color::White));
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(list, popup_area);
+}
+
+pub fn render_notice_popup(f: &mut Frame, area: Rect, notice: &Notice, theme: &Theme) {
+    let color = if notice.is_error {
+        Color::Red
+    } else {
+        Color::White
+    };
+
+    // Paths can be long, so size to the message and wrap onto extra lines past the terminal width
+    let width = (notice.message.len().max(notice.title.len() + 14) as u16 + 4).min(area.width);
+    let lines = (notice.message.len() as u16).div_ceil(width.saturating_sub(2).max(1));
+    let popup_area = centered_rect(width, lines + 2, area);
+    let paragraph = Paragraph::new(notice.message.clone())
+        .block(
+            Block::default()
+                .title(format!("{} - Esc to close", notice.title))
+                .borders(Borders::ALL),
+        )
+        .wrap(Wrap { trim: false })
+        .style(theme.fg(color));
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(paragraph, popup_area);
+}
+
+// The overview's quick look at one target's latest failures, newest first
+pub fn render_failures_popup(f: &mut Frame, area: Rect, target: &TargetStats, theme: &Theme) {
+    let name = target.target.name.as_ref().unwrap_or(&target.target.ip);
+    let mut items: Vec<ListItem> = target
+        .failure_log
+        .iter()
+        .rev()
+        .take(PEEK_FAILURES)
+        .map(|failure| {
+            ListItem::new(format!(
+                "{} {}: {}",
+                failure
+                    .timestamp
+                    .with_timezone(&chrono::Local)
+                    .format("%H:%M:%S"),
+                failure.failure_type,
+                failure.reason
+            ))
+        })
+        .collect();
+    if items.is_empty() {
+        items.push(ListItem::new("No failures recorded").style(theme.fg(Color::Green)));
+    }
+
+    let popup_area = centered_rect(90, items.len() as u16 + 2, area);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(format!(
+                    "{}: latest failures - Up/Down for others, Enter to open, Esc to close",
+                    name
+                ))
+                .borders(Borders::ALL),
+        )
+        .style(theme.fg(Color::White));
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(list, popup_area);
+}
+
+// Yes/no question for something that can't be undone
+pub fn render_confirm_popup(f: &mut Frame, area: Rect, title: &str, question: &str, theme: &Theme) {
+    let popup_area = centered_rect(question.len().max(title.len() + 30) as u16 + 4, 3, area);
+    let paragraph = Paragraph::new(question.to_string())
+        .block(
+            Block::default()
+                .title(format!("{} - Enter to confirm, Esc to cancel", title))
+                .borders(Borders::ALL),
+        )
+        .style(theme.fg(Color::Yellow));
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(paragraph, popup_area);
+}
+
+// Single-line text entry; the caller owns the text and handles the keys
+pub fn render_input_popup(f: &mut Frame, area: Rect, title: &str, text: &str, theme: &Theme) {
+    let popup_area = centered_rect(60, 3, area);
+    let paragraph = Paragraph::new(format!("Note: {}_", text))
+        .block(
+            Block::default()
+                .title(format!("{} - Enter to confirm, Esc to cancel", title))
+                .borders(Borders::ALL),
+        )
+        .style(theme.fg(Color::White));
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(paragraph, popup_area);
+}
+
+fn burst_result_items(result: &BurstResult, theme: &Theme) -> Vec<ListItem<'static>> {
+    if let Some(error) = &result.error {
+        return vec![ListItem::new(error.clone()).style(theme.fg(Color::Red))];
+    }
+
+    let loss_color = if result.loss_percent > 0.0 {
+        Color::Red
+    } else {
+        Color::Green
+    };
+
+    let mut items = vec![
+        ListItem::new(format!(
+            "Sent/Received: {}/{}",
+            result.sent, result.received
+        )),
+        ListItem::new(format!("Loss: {:.1}%", result.loss_percent)).style(theme.fg(loss_color)),
+    ];
+
+    if let Some(stats) = &result.stats {
+        items.extend([
+            ListItem::new(format!(
+                "Min/Max: {}/{}",
+                theme.latency(stats.min, 2),
+                theme.latency(stats.max, 2)
+            )),
+            ListItem::new(format!("Mean: {}", theme.latency(stats.mean, 2))),
+            ListItem::new(format!("Median: {}", theme.latency(stats.median, 2))),
+            ListItem::new(format!("P95: {}", theme.latency(stats.p95, 2))),
+            ListItem::new(format!("P99: {}", theme.latency(stats.p99, 2))),
+            ListItem::new(format!(
+                "Effective: {}",
+                theme.latency(stats.effective_latency, 2)
+            )),
+        ]);
+    }
+
+    items
+}
@@ -0,0 +1,210 @@
+use crate::monitor::TargetStats;
+use crate::theme::{CheckKind, Theme};
+use crate::units::axis_labels;
+use chrono::{DateTime, Utc};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Color,
+    symbols,
+    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Paragraph},
+};
+
+// The configured `reference_target`, matched by address or name like `boxmonitor query`
+pub fn reference_for<'a>(
+    target: &TargetStats,
+    targets: &'a [TargetStats],
+) -> Option<&'a TargetStats> {
+    let reference = target.settings.reference_target.as_deref()?;
+    targets
+        .iter()
+        .find(|t| t.target.ip == reference || t.target.name.as_deref() == Some(reference))
+        .or_else(|| {
+            targets.iter().find(|t| {
+                t.target
+                    .name
+                    .as_deref()
+                    .is_some_and(|name| name.eq_ignore_ascii_case(reference))
+            })
+        })
+}
+
+fn display_name(target: &TargetStats) -> &str {
+    target.target.name.as_ref().unwrap_or(&target.target.ip)
+}
+
+// Each answered ping of `target` minus the reference's nearest answered ping, as (index in
+// the target's history, difference). Pings more than half the reference's sample spacing
+// apart belong to different cycles and aren't paired
+fn relative_latencies(target: &TargetStats, reference: &TargetStats) -> Vec<(usize, f64)> {
+    let replies: Vec<(DateTime<Utc>, f64)> = reference
+        .ping_history
+        .iter()
+        .filter_map(|r| r.latency_ms.map(|ms| (r.timestamp, ms)))
+        .collect();
+    let (Some(first), Some(last)) = (
+        reference.ping_history.front(),
+        reference.ping_history.back(),
+    ) else {
+        return Vec::new();
+    };
+    let spacing = match reference.ping_history.len() {
+        1 => chrono::Duration::seconds(1),
+        n => (last.timestamp - first.timestamp) / (n as i32 - 1),
+    };
+    let tolerance = spacing / 2;
+
+    target
+        .ping_history
+        .iter()
+        .enumerate()
+        .filter_map(|(i, r)| {
+            let latency = r.latency_ms?;
+            let after = replies.partition_point(|(timestamp, _)| *timestamp < r.timestamp);
+            let nearest = [after.checked_sub(1), Some(after)]
+                .into_iter()
+                .flatten()
+                .filter_map(|j| replies.get(j))
+                .min_by_key(|(timestamp, _)| (*timestamp - r.timestamp).abs())?;
+            ((nearest.0 - r.timestamp).abs() <= tolerance).then_some((i, latency - nearest.1))
+        })
+        .collect()
+}
+
+fn mean(values: &[(usize, f64)]) -> f64 {
+    values.iter().map(|(_, d)| d).sum::<f64>() / values.len() as f64
+}
+
+// e.g. "+8.2ms over Gateway (eth0)", for the target info line
+pub fn relative_summary(
+    target: &TargetStats,
+    reference: &TargetStats,
+    theme: &Theme,
+) -> Option<String> {
+    if target.target.ip == reference.target.ip {
+        return None;
+    }
+    let relative = relative_latencies(target, reference);
+    if relative.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "{} over {}",
+        signed(mean(&relative), theme),
+        display_name(reference)
+    ))
+}
+
+fn signed(ms: f64, theme: &Theme) -> String {
+    if ms < 0.0 {
+        format!("-{}", theme.latency(-ms, 1))
+    } else {
+        format!("+{}", theme.latency(ms, 1))
+    }
+}
+
+pub fn render_relative_chart(
+    f: &mut Frame,
+    area: Rect,
+    target: &TargetStats,
+    reference: Option<&TargetStats>,
+    theme: &Theme,
+) {
+    let title = "Latency vs Reference - Press 'p' to cycle views";
+    let message = match reference {
+        None if target.settings.reference_target.is_none() => Some(
+            "No reference target configured; set `reference_target` to a target's address or name"
+                .to_string(),
+        ),
+        None => Some(format!(
+            "Reference target {} is not monitored",
+            target
+                .settings
+                .reference_target
+                .as_deref()
+                .unwrap_or_default()
+        )),
+        Some(reference) if target.target.ip == reference.target.ip => {
+            Some("This is the reference target; other targets are compared to it".to_string())
+        }
+        Some(_) => None,
+    };
+    if let Some(message) = message {
+        let block = Block::default().title(title).borders(Borders::ALL);
+        f.render_widget(Paragraph::new(message).block(block), area);
+        return;
+    }
+    let Some(reference) = reference else {
+        return;
+    };
+
+    let relative = relative_latencies(target, reference);
+    if relative.is_empty() {
+        let block = Block::default().title(title).borders(Borders::ALL);
+        let paragraph = Paragraph::new(format!(
+            "No pings answered by both this target and {} at the same time yet",
+            display_name(reference)
+        ))
+        .block(block);
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let data: Vec<(f64, f64)> = relative.iter().map(|&(i, d)| (i as f64, d)).collect();
+    let lowest = data.iter().map(|(_, d)| *d).fold(0.0, f64::min);
+    let highest = data.iter().map(|(_, d)| *d).fold(0.0, f64::max);
+    // Padded on both sides, keeping the zero line inside the chart
+    let padding = ((highest - lowest) * 0.1).max(f64::EPSILON);
+    let (y_min, y_max) = (lowest.min(0.0) - padding, highest.max(0.0) + padding);
+    let x_max = target.ping_history.len() as f64;
+    let zero_line = vec![(0.0, 0.0), (x_max, 0.0)];
+
+    let datasets = vec![
+        Dataset::default()
+            .name(display_name(reference))
+            .marker(symbols::Marker::Braille)
+            .style(theme.fg(Color::Yellow))
+            .graph_type(GraphType::Line)
+            .data(&zero_line),
+        Dataset::default()
+            .name("Difference")
+            .marker(theme.check_marker(CheckKind::Ping, 0))
+            .style(theme.fg(Color::Cyan))
+            .graph_type(theme.check_graph(CheckKind::Ping))
+            .data(&data),
+    ];
+
+    let unit = theme.axis_unit(y_max.max(-y_min));
+    let y_labels = axis_labels(y_min, y_max, unit);
+    let timestamps: Vec<_> = target.ping_history.iter().map(|r| r.timestamp).collect();
+    let x_labels = theme.time_labels(x_max, &timestamps);
+
+    let chart = Chart::new(datasets)
+        .block(
+            Block::default()
+                .title(format!(
+                    "Latency over {} ({}) - mean {}, latest {} - Press 'p' to cycle views",
+                    display_name(reference),
+                    unit.symbol(),
+                    signed(mean(&relative), theme),
+                    signed(relative[relative.len() - 1].1, theme)
+                ))
+                .borders(Borders::ALL),
+        )
+        .x_axis(
+            Axis::default()
+                .title(theme.time_axis().title())
+                .style(theme.fg(Color::Gray))
+                .bounds([0.0, x_max])
+                .labels(x_labels.iter().map(|s| s.as_str()).collect::<Vec<_>>()),
+        )
+        .y_axis(
+            Axis::default()
+                .title(format!("Difference ({})", unit.symbol()))
+                .style(theme.fg(Color::Gray))
+                .bounds([y_min, y_max])
+                .labels(y_labels.iter().map(|s| s.as_str()).collect::<Vec<_>>()),
+        );
+
+    f.render_widget(chart, area);
+}
@@ -0,0 +1,158 @@
+use crate::monitor::TargetStats;
+use crate::theme::Theme;
+use chrono::{DateTime, Utc};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Rect},
+    style::{Color, Modifier},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+};
+
+struct SampleRow {
+    timestamp: DateTime<Utc>,
+    target: String,
+    check: &'static str,
+    latency_ms: Option<f64>,
+    success: bool,
+    reason: String,
+}
+
+fn collect_samples(target: &TargetStats, rows: &mut Vec<SampleRow>) {
+    let target_name = target.target.name.as_ref().unwrap_or(&target.target.ip);
+
+    for result in &target.ping_history {
+        rows.push(SampleRow {
+            timestamp: result.timestamp,
+            target: target_name.clone(),
+            check: "Ping",
+            latency_ms: result.latency_ms,
+            success: result.success,
+            reason: result.failure_reason.clone().unwrap_or_default(),
+        });
+    }
+
+    for result in &target.ssh_history {
+        rows.push(SampleRow {
+            timestamp: result.timestamp,
+            target: target_name.clone(),
+            check: "SSH",
+            latency_ms: result.connection_time_ms,
+            success: result.success,
+            reason: result.failure_reason.clone().unwrap_or_default(),
+        });
+    }
+}
+
+pub fn render_all_targets_samples_table(
+    f: &mut Frame,
+    area: Rect,
+    targets: &[&TargetStats],
+    state: &mut TableState,
+    theme: &Theme,
+) {
+    let mut rows = Vec::new();
+    for target in targets {
+        collect_samples(target, &mut rows);
+    }
+
+    render_samples_table(f, area, rows, true, "Raw Samples", state, theme);
+}
+
+pub fn render_single_target_samples_table(
+    f: &mut Frame,
+    area: Rect,
+    target: &TargetStats,
+    state: &mut TableState,
+    theme: &Theme,
+) {
+    let mut rows = Vec::new();
+    collect_samples(target, &mut rows);
+
+    let target_name = target.target.name.as_ref().unwrap_or(&target.target.ip);
+    let title = format!("Raw Samples for {}", target_name);
+    render_samples_table(f, area, rows, false, &title, state, theme);
+}
+
+fn render_samples_table(
+    f: &mut Frame,
+    area: Rect,
+    mut samples: Vec<SampleRow>,
+    show_target: bool,
+    title: &str,
+    state: &mut TableState,
+    theme: &Theme,
+) {
+    let title = format!("{} - Up/Down to scroll, 'p' to cycle views", title);
+
+    if samples.is_empty() {
+        let block = Block::default().title(title).borders(Borders::ALL);
+        let paragraph = Paragraph::new("No samples recorded yet").block(block);
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    // Most recent first, so the top of the table is always the live edge
+    samples.sort_by_key(|s| std::cmp::Reverse(s.timestamp));
+
+    // Keep the selection inside the table when history rotates or shrinks
+    match state.selected() {
+        Some(selected) if selected >= samples.len() => state.select(Some(samples.len() - 1)),
+        None => state.select(Some(0)),
+        _ => {}
+    }
+
+    let mut header_cells = vec!["Time"];
+    if show_target {
+        header_cells.push("Target");
+    }
+    header_cells.extend(["Check", "Latency", "Status", "Reason"]);
+
+    let header = Row::new(header_cells).style(theme.fg(Color::Yellow).add_modifier(Modifier::BOLD));
+
+    let rows: Vec<Row> = samples
+        .iter()
+        .map(|sample| {
+            let mut cells = vec![Cell::from(
+                sample.timestamp.format("%H:%M:%S%.3f").to_string(),
+            )];
+            if show_target {
+                cells.push(Cell::from(sample.target.clone()));
+            }
+
+            let latency = match sample.latency_ms {
+                Some(latency) => theme.latency(latency, 2),
+                None => "-".to_string(),
+            };
+            let (status, status_color) = if sample.success {
+                ("OK", Color::Green)
+            } else {
+                ("FAIL", Color::Red)
+            };
+
+            cells.push(Cell::from(sample.check));
+            cells.push(Cell::from(latency));
+            cells.push(Cell::from(status).style(theme.fg(status_color)));
+            cells.push(Cell::from(sample.reason.clone()));
+            Row::new(cells)
+        })
+        .collect();
+
+    let mut widths = vec![Constraint::Length(12)];
+    if show_target {
+        widths.push(Constraint::Length(20));
+    }
+    widths.extend([
+        Constraint::Length(5),
+        Constraint::Length(10),
+        Constraint::Length(6),
+        Constraint::Min(10),
+    ]);
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .style(theme.fg(Color::White))
+        .highlight_style(theme.bg(Color::DarkGray));
+
+    f.render_stateful_widget(table, area, state);
+}
@@ -0,0 +1,179 @@
+use crate::config::LatencyUnit;
+use crate::monitor::{Sampling, TargetStats};
+use crate::theme::{CheckKind, Theme};
+use crate::ui::collecting;
+use crate::units::format_value;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Rect},
+    style::{Color, Modifier},
+    symbols,
+    widgets::{Axis, Block, Borders, Cell, Chart, Dataset, GraphType, Paragraph, Row, Table},
+};
+
+const TITLE: &str = "Sampling Interval - Press 'p' to cycle views";
+
+fn display_name(target: &TargetStats) -> &str {
+    target.target.name.as_ref().unwrap_or(&target.target.ip)
+}
+
+// e.g. "mean 1,003ms, max 2,410ms, 2 of 99 late"
+fn summary(sampling: &Sampling) -> String {
+    format!(
+        "mean {}, max {}, {} of {} late",
+        interval(sampling.mean_ms),
+        interval(sampling.max_ms),
+        sampling.late,
+        sampling.intervals
+    )
+}
+
+fn interval(ms: f64) -> String {
+    format!("{}ms", format_value(ms, LatencyUnit::Ms, 0))
+}
+
+// While a target backs off, its pings are spaced out on purpose
+fn backoff_note(target: &TargetStats) -> &'static str {
+    if target.backoff.is_some() {
+        " - backing off, so spaced out on purpose"
+    } else {
+        ""
+    }
+}
+
+pub fn render_sampling_chart(
+    f: &mut Frame,
+    area: Rect,
+    target: &TargetStats,
+    expected_ms: u64,
+    theme: &Theme,
+) {
+    let intervals = target.sampling_intervals();
+    let Some(sampling) = target.sampling(expected_ms) else {
+        let block = Block::default().title(TITLE).borders(Borders::ALL);
+        let paragraph = Paragraph::new(collecting("pings")).block(block);
+        f.render_widget(paragraph, area);
+        return;
+    };
+
+    let data: Vec<(f64, f64)> = intervals.iter().map(|&(i, ms)| (i as f64, ms)).collect();
+    let late: Vec<(f64, f64)> = data
+        .iter()
+        .copied()
+        .filter(|&(_, ms)| Sampling::is_late(ms, expected_ms))
+        .collect();
+    let x_max = target.ping_history.len() as f64;
+    let expected = expected_ms as f64;
+    let configured = vec![(0.0, expected), (x_max, expected)];
+    let y_max = sampling.max_ms.max(expected * 1.5) * 1.1;
+
+    let datasets = vec![
+        Dataset::default()
+            .name("Configured")
+            .marker(symbols::Marker::Braille)
+            .style(theme.fg(Color::Yellow))
+            .graph_type(GraphType::Line)
+            .data(&configured),
+        Dataset::default()
+            .name("Actual")
+            .marker(theme.check_marker(CheckKind::Ping, 0))
+            .style(theme.fg(Color::Cyan))
+            .graph_type(theme.check_graph(CheckKind::Ping))
+            .data(&data),
+        Dataset::default()
+            .name("Late")
+            .marker(symbols::Marker::Dot)
+            .style(theme.fg(Color::Red))
+            .graph_type(GraphType::Scatter)
+            .data(&late),
+    ];
+
+    let y_labels = [0.0, y_max / 2.0, y_max].map(interval);
+    let timestamps: Vec<_> = target.ping_history.iter().map(|r| r.timestamp).collect();
+    let x_labels = theme.time_labels(x_max, &timestamps);
+
+    let chart = Chart::new(datasets)
+        .block(
+            Block::default()
+                .title(format!(
+                    "Sampling Interval - {}, configured {}{} - Press 'p' to cycle views",
+                    summary(&sampling),
+                    interval(expected),
+                    backoff_note(target)
+                ))
+                .borders(Borders::ALL),
+        )
+        .x_axis(
+            Axis::default()
+                .title(theme.time_axis().title())
+                .style(theme.fg(Color::Gray))
+                .bounds([0.0, x_max])
+                .labels(x_labels.iter().map(|s| s.as_str()).collect::<Vec<_>>()),
+        )
+        .y_axis(
+            Axis::default()
+                .title("Since previous ping")
+                .style(theme.fg(Color::Gray))
+                .bounds([0.0, y_max])
+                .labels(y_labels.iter().map(|s| s.as_str()).collect::<Vec<_>>()),
+        );
+
+    f.render_widget(chart, area);
+}
+
+// One row per target, so a monitor falling behind on everything stands out from one slow target
+pub fn render_all_targets_sampling(
+    f: &mut Frame,
+    area: Rect,
+    targets: &[&TargetStats],
+    expected_ms: u64,
+    theme: &Theme,
+) {
+    let title = format!(
+        "Sampling Interval - configured {} - Press 'p' to cycle views",
+        interval(expected_ms as f64)
+    );
+    let header = Row::new(["Target", "Intervals", "Mean", "Max", "Late"])
+        .style(theme.fg(Color::Yellow).add_modifier(Modifier::BOLD));
+    let rows: Vec<Row> = targets
+        .iter()
+        .map(|target| {
+            let name = format!("{}{}", display_name(target), backoff_tag(target));
+            let Some(sampling) = target.sampling(expected_ms) else {
+                return Row::new([Cell::from(name), Cell::from("-")]);
+            };
+            let late_color = if sampling.late > 0 {
+                Color::Red
+            } else {
+                Color::Green
+            };
+            Row::new([
+                Cell::from(name),
+                Cell::from(sampling.intervals.to_string()),
+                Cell::from(interval(sampling.mean_ms)),
+                Cell::from(interval(sampling.max_ms)),
+                Cell::from(sampling.late.to_string()).style(theme.fg(late_color)),
+            ])
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Fill(1),
+        Constraint::Length(10),
+        Constraint::Length(12),
+        Constraint::Length(12),
+        Constraint::Length(6),
+    ];
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(Block::default().title(title).borders(Borders::ALL));
+    f.render_widget(table, area);
+}
+
+fn backoff_tag(target: &TargetStats) -> &'static str {
+    if target.backoff.is_some() {
+        " (backing off)"
+    } else {
+        ""
+    }
+}
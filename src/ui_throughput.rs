@@ -0,0 +1,101 @@
+use crate::monitor::TargetStats;
+use crate::theme::{CheckKind, Theme};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Color,
+    widgets::{Axis, Block, Borders, Chart, Dataset, Paragraph},
+};
+
+pub fn render_throughput_chart(f: &mut Frame, area: Rect, target: &TargetStats, theme: &Theme) {
+    let message = if target.target.throughput.is_none() {
+        Some("No throughput test configured; set `throughput` on the target")
+    } else if target.throughput_history.is_empty() {
+        Some("No throughput data yet...")
+    } else {
+        None
+    };
+    if let Some(message) = message {
+        let block = Block::default()
+            .title("SSH Throughput - Press 'p' to cycle views")
+            .borders(Borders::ALL);
+        f.render_widget(Paragraph::new(message).block(block), area);
+        return;
+    }
+
+    let data: Vec<(f64, f64)> = target
+        .throughput_history
+        .iter()
+        .enumerate()
+        .filter_map(|(i, result)| result.mb_per_sec.map(|rate| (i as f64, rate)))
+        .collect();
+    let failed = target.throughput_history.len() - data.len();
+
+    if data.is_empty() {
+        let block = Block::default()
+            .title("SSH Throughput - Press 'p' to cycle views")
+            .borders(Borders::ALL);
+        let error = target
+            .throughput_history
+            .back()
+            .and_then(|r| r.error.as_deref())
+            .unwrap_or("unknown error");
+        let paragraph = Paragraph::new(format!("All throughput tests failed: {}", error))
+            .block(block)
+            .style(theme.fg(Color::Red));
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let latest = data.last().map_or(0.0, |(_, y)| *y);
+    let mean = data.iter().map(|(_, y)| *y).sum::<f64>() / data.len() as f64;
+    let y_max = data.iter().map(|(_, y)| *y).fold(0.0, f64::max) * 1.1;
+    let x_max = target.throughput_history.len() as f64;
+
+    let datasets = vec![
+        Dataset::default()
+            .name("Upload")
+            .marker(theme.check_marker(CheckKind::Ssh, 0))
+            .style(theme.fg(Color::Magenta))
+            .graph_type(theme.check_graph(CheckKind::Ssh))
+            .data(&data),
+    ];
+
+    let y_labels: Vec<String> = (0..=5)
+        .map(|i| format!("{:.1}", y_max * i as f64 / 5.0))
+        .collect();
+    let timestamps: Vec<_> = target
+        .throughput_history
+        .iter()
+        .map(|r| r.timestamp)
+        .collect();
+    let x_labels = theme.time_labels(x_max, &timestamps);
+
+    let mut title = format!(
+        "SSH Throughput (MB/s) - latest {:.2}, mean {:.2}",
+        latest, mean
+    );
+    if failed > 0 {
+        title.push_str(&format!(", {} failed", failed));
+    }
+    title.push_str(" - Press 'p' to cycle views");
+
+    let chart = Chart::new(datasets)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .x_axis(
+            Axis::default()
+                .title(theme.time_axis().title())
+                .style(theme.fg(Color::Gray))
+                .bounds([0.0, x_max])
+                .labels(x_labels.iter().map(|s| s.as_str()).collect::<Vec<_>>()),
+        )
+        .y_axis(
+            Axis::default()
+                .title("Upload (MB/s)")
+                .style(theme.fg(Color::Gray))
+                .bounds([0.0, y_max.max(f64::EPSILON)])
+                .labels(y_labels.iter().map(|s| s.as_str()).collect::<Vec<_>>()),
+        );
+
+    f.render_widget(chart, area);
+}
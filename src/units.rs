@@ -0,0 +1,178 @@
+use crate::config::{ExportTimestamps, ExportTimezone, LatencyUnit, TimestampFormat};
+use chrono::{DateTime, FixedOffset, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize, Serializer};
+
+impl LatencyUnit {
+    // Auto settles on a unit for the value at hand: µs below a millisecond, s from a second up
+    pub fn resolve(self, ms: f64) -> Self {
+        match self {
+            LatencyUnit::Auto if ms.abs() < 1.0 => LatencyUnit::Us,
+            LatencyUnit::Auto if ms.abs() >= 1000.0 => LatencyUnit::S,
+            LatencyUnit::Auto => LatencyUnit::Ms,
+            unit => unit,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            LatencyUnit::Us => "µs",
+            LatencyUnit::S => "s",
+            LatencyUnit::Ms | LatencyUnit::Auto => "ms",
+        }
+    }
+
+    fn scale(self, ms: f64) -> f64 {
+        match self {
+            LatencyUnit::Us => ms * 1000.0,
+            LatencyUnit::S => ms / 1000.0,
+            LatencyUnit::Ms | LatencyUnit::Auto => ms,
+        }
+    }
+}
+
+// A latency in `unit` with its symbol, e.g. "3.02s" or "1,250.50ms". Microseconds are whole
+// numbers; that is already finer than the pings are timed
+pub fn format_latency(ms: f64, unit: LatencyUnit, decimals: usize) -> String {
+    let unit = unit.resolve(ms);
+    format!("{}{}", format_value(ms, unit, decimals), unit.symbol())
+}
+
+// Just the number, for chart axes that name their unit in the title. `unit` should already be
+// resolved, so every label on an axis shares it
+pub fn format_value(ms: f64, unit: LatencyUnit, decimals: usize) -> String {
+    let decimals = if unit == LatencyUnit::Us { 0 } else { decimals };
+    group_thousands(&format!("{:.*}", decimals, unit.scale(ms)))
+}
+
+fn group_thousands(number: &str) -> String {
+    let (sign, unsigned) = match number.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", number),
+    };
+    let (whole, fraction) = match unsigned.find('.') {
+        Some(dot) => unsigned.split_at(dot),
+        None => (unsigned, ""),
+    };
+
+    let mut grouped = String::with_capacity(whole.len() + whole.len() / 3);
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    format!("{}{}{}", sign, grouped, fraction)
+}
+
+// Six evenly spaced y-axis labels in `unit`, which the axis title should name
+pub fn axis_labels(y_min: f64, y_max: f64, unit: LatencyUnit) -> Vec<String> {
+    (0..=5)
+        .map(|i| format_value(y_min + (y_max - y_min) * i as f64 / 5.0, unit, 1))
+        .collect()
+}
+
+// How chart time axes are labelled: by sample number, clock time, or age such as "-30s"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeAxis {
+    #[default]
+    Samples,
+    Clock,
+    Relative,
+}
+
+impl TimeAxis {
+    pub fn next(self) -> Self {
+        match self {
+            TimeAxis::Samples => TimeAxis::Clock,
+            TimeAxis::Clock => TimeAxis::Relative,
+            TimeAxis::Relative => TimeAxis::Samples,
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            TimeAxis::Samples => "Time (samples)",
+            TimeAxis::Clock => "Time",
+            TimeAxis::Relative => "Time (ago)",
+        }
+    }
+}
+
+// Six labels evenly across [0, x_max], where sample i was taken at `timestamps[i]`. Without
+// timestamps the labels fall back to sample numbers
+pub fn time_labels(
+    axis: TimeAxis,
+    x_max: f64,
+    timestamps: &[DateTime<Utc>],
+    now: DateTime<Utc>,
+) -> Vec<String> {
+    (0..=5)
+        .map(|i| {
+            let x = x_max * i as f64 / 5.0;
+            let index = (x.round() as usize).min(timestamps.len().saturating_sub(1));
+            match (axis, timestamps.get(index)) {
+                (TimeAxis::Clock, Some(at)) => at
+                    .with_timezone(&chrono::Local)
+                    .format("%H:%M:%S")
+                    .to_string(),
+                (TimeAxis::Relative, Some(at)) => format_age((now - *at).num_seconds()),
+                _ => format!("{:.0}", x),
+            }
+        })
+        .collect()
+}
+
+// "now", then "-45s", "-12m" and "-3h" as samples get older
+fn format_age(seconds: i64) -> String {
+    match seconds {
+        ..=0 => "now".to_string(),
+        1..120 => format!("-{}s", seconds),
+        120..7200 => format!("-{}m", (seconds + 30) / 60),
+        _ => format!("-{}h", (seconds + 1800) / 3600),
+    }
+}
+
+// A timestamp as written for other tools. Either form is read back, whatever the current setting
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(untagged)]
+pub enum ExportedTimestamp {
+    UnixMs(i64),
+    Rfc3339(DateTime<FixedOffset>),
+}
+
+impl ExportedTimestamp {
+    pub fn to_utc(self) -> DateTime<Utc> {
+        match self {
+            ExportedTimestamp::UnixMs(ms) => {
+                DateTime::from_timestamp_millis(ms).unwrap_or_default()
+            }
+            ExportedTimestamp::Rfc3339(at) => at.to_utc(),
+        }
+    }
+}
+
+impl Serialize for ExportedTimestamp {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ExportedTimestamp::UnixMs(ms) => s.serialize_i64(*ms),
+            // "Z" rather than "+00:00" for UTC
+            ExportedTimestamp::Rfc3339(at) => {
+                s.serialize_str(&at.to_rfc3339_opts(SecondsFormat::Millis, true))
+            }
+        }
+    }
+}
+
+impl ExportTimestamps {
+    pub fn stamp(self, at: DateTime<Utc>) -> ExportedTimestamp {
+        match (self.format, self.timezone) {
+            (TimestampFormat::UnixMs, _) => ExportedTimestamp::UnixMs(at.timestamp_millis()),
+            (TimestampFormat::Rfc3339, ExportTimezone::Utc) => {
+                ExportedTimestamp::Rfc3339(at.fixed_offset())
+            }
+            (TimestampFormat::Rfc3339, ExportTimezone::Local) => {
+                ExportedTimestamp::Rfc3339(at.with_timezone(&chrono::Local).fixed_offset())
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crate::monitor::{FailureLog, TargetStats};
+use chrono::SecondsFormat;
+use color_eyre::Result;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+const POLL_INTERVAL_MS: u64 = 250;
+
+// Headless `tail -f` of the failure log: one tab-separated line per failure
+// (timestamp, target, ip, type, reason), flushed as soon as it is seen
+pub async fn run_watch_failures(targets: Arc<Mutex<Vec<TargetStats>>>) -> Result<()> {
+    // How many failures each target had logged as of the last poll, keyed by position;
+    // nothing from before startup is printed
+    let mut last_seen: HashMap<usize, u64> = HashMap::new();
+    let mut interval = tokio::time::interval(std::time::Duration::from_millis(POLL_INTERVAL_MS));
+
+    loop {
+        interval.tick().await;
+
+        let mut lines = Vec::new();
+        {
+            let targets = targets.lock().await;
+            for (index, target) in targets.iter().enumerate() {
+                let seen = last_seen.entry(index).or_insert(target.failures_logged);
+                let name = target.target.name.as_ref().unwrap_or(&target.target.ip);
+
+                for failure in unseen_failures(target, seen) {
+                    lines.push(format!(
+                        "{}\t{}\t{}\t{}\t{}",
+                        failure
+                            .timestamp
+                            .to_rfc3339_opts(SecondsFormat::Millis, true),
+                        sanitize(name),
+                        target.target.ip,
+                        failure.failure_type.to_lowercase(),
+                        sanitize(&failure.reason)
+                    ));
+                }
+            }
+        }
+
+        if !lines.is_empty() {
+            let mut stdout = io::stdout().lock();
+            for line in lines {
+                writeln!(stdout, "{}", line)?;
+            }
+            stdout.flush()?;
+        }
+    }
+}
+
+// Failures logged since `seen`, oldest first, moving `seen` up to date. Ones that already left
+// the bounded log are gone; a count that went down means the target was replaced, so it starts
+// over from there
+fn unseen_failures<'a>(target: &'a TargetStats, seen: &mut u64) -> Vec<&'a FailureLog> {
+    let logged = target.failures_logged;
+    let new = logged
+        .saturating_sub(*seen)
+        .min(target.failure_log.len() as u64) as usize;
+    *seen = logged;
+    let skip = target.failure_log.len() - new;
+    target.failure_log.iter().skip(skip).collect()
+}
+
+// Keeps each failure on one line with exactly five fields
+fn sanitize(field: &str) -> String {
+    field.replace(['\t', '\n', '\r'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::tests::test_target;
+
+    #[tokio::test(start_paused = true)]
+    async fn failures_sharing_a_timestamp_are_all_printed() {
+        let mut target = test_target("10.0.0.1");
+        let mut seen = target.failures_logged;
+        target.add_failure_log("ping".to_string(), "first".to_string(), 10);
+        target.add_failure_log("ping".to_string(), "second".to_string(), 10);
+        assert_eq!(
+            target.failure_log[0].timestamp,
+            target.failure_log[1].timestamp
+        );
+
+        let reasons: Vec<&str> = unseen_failures(&target, &mut seen)
+            .iter()
+            .map(|f| f.reason.as_str())
+            .collect();
+        assert_eq!(reasons, ["first", "second"]);
+        assert!(unseen_failures(&target, &mut seen).is_empty());
+
+        target.add_failure_log("ping".to_string(), "third".to_string(), 10);
+        let reasons: Vec<&str> = unseen_failures(&target, &mut seen)
+            .iter()
+            .map(|f| f.reason.as_str())
+            .collect();
+        assert_eq!(reasons, ["third"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn failures_that_left_the_log_are_skipped() {
+        let mut target = test_target("10.0.0.1");
+        let mut seen = target.failures_logged;
+        for i in 0..5 {
+            target.add_failure_log("ping".to_string(), i.to_string(), 3);
+        }
+        assert_eq!(unseen_failures(&target, &mut seen).len(), 3);
+        assert_eq!(seen, 5);
+    }
+}
@@ -0,0 +1,75 @@
+use std::net::Ipv6Addr;
+
+// IPv6 link-local targets carry the interface they are reached through after a '%', as in
+// `fe80::1%eth0` or `fe80::1%2`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Zone {
+    pub interface: String,
+    pub scope_id: u32,
+}
+
+// The address and zone of `spec`, or None when it has no zone
+pub fn split_zone(spec: &str) -> Option<Result<(Ipv6Addr, &str), String>> {
+    let (addr, zone) = spec.split_once('%')?;
+    Some(match addr.parse::<Ipv6Addr>() {
+        Ok(_) if zone.is_empty() => Err(format!("Empty zone in {}", spec)),
+        Ok(addr) => Ok((addr, zone)),
+        Err(_) => Err(format!("Only IPv6 addresses take a %zone: {}", spec)),
+    })
+}
+
+// Zones name an interface or give its index; either way it has to exist
+#[cfg(target_os = "linux")]
+pub fn resolve(zone: &str) -> Result<Zone, String> {
+    if let Ok(scope_id) = zone.parse::<u32>() {
+        let mut name = [0 as libc::c_char; libc::IF_NAMESIZE];
+        if unsafe { libc::if_indextoname(scope_id, name.as_mut_ptr()) }.is_null() {
+            return Err(format!(
+                "Invalid zone %{}: no interface with that index",
+                zone
+            ));
+        }
+        let interface = unsafe { std::ffi::CStr::from_ptr(name.as_ptr()) };
+        return Ok(Zone {
+            interface: interface.to_string_lossy().into_owned(),
+            scope_id,
+        });
+    }
+
+    let name = std::ffi::CString::new(zone).map_err(|_| format!("Invalid zone %{}", zone))?;
+    match unsafe { libc::if_nametoindex(name.as_ptr()) } {
+        0 => Err(format!("Invalid zone %{}: no such interface", zone)),
+        scope_id => Ok(Zone {
+            interface: zone.to_string(),
+            scope_id,
+        }),
+    }
+}
+
+// surge-ping can only bind a socket to an interface on Linux
+#[cfg(not(target_os = "linux"))]
+pub fn resolve(zone: &str) -> Result<Zone, String> {
+    Err(format!(
+        "Scoped IPv6 addresses (%{}) are only supported on Linux",
+        zone
+    ))
+}
+
+// A target's `interface`, checked before a socket is bound to it
+#[cfg(target_os = "linux")]
+pub fn check_interface(interface: &str) -> Result<(), String> {
+    let name = std::ffi::CString::new(interface)
+        .map_err(|_| format!("Invalid interface name {:?}", interface))?;
+    match unsafe { libc::if_nametoindex(name.as_ptr()) } {
+        0 => Err(format!("No such interface: {}", interface)),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn check_interface(interface: &str) -> Result<(), String> {
+    Err(format!(
+        "Binding probes to an interface ({}) is only supported on Linux",
+        interface
+    ))
+}